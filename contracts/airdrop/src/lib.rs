@@ -0,0 +1,153 @@
+#![no_std]
+
+use soroban_sdk::{
+    contract, contractimpl, contracttype, symbol_short, Address, BytesN, Env, Vec,
+    token::{self},
+};
+
+use merkleproof::verify;
+use storagettl::{bump_instance, read_persistent, write_persistent};
+
+use crate::merkle::leaf_hash;
+
+// Claims tracked per bitmap word
+const BITS_PER_WORD: u32 = 128;
+
+#[contract]
+pub struct Airdrop;
+
+#[contractimpl]
+impl Airdrop {
+
+    fn check_nonnegative_amount(amount: i128) {
+        if amount < 0 {
+            panic!("negative amount is not allowed: {}", amount)
+        }
+    }
+
+    fn require_admin(e: &Env, admin: &Address) {
+        let stored: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        assert!(stored == *admin, "Unauthorized");
+        admin.require_auth();
+    }
+
+    fn expired(e: &Env) -> bool {
+        let expiry_ledger: u32 = e.storage().instance().get(&DataKey::ExpiryLedger).unwrap();
+        e.ledger().sequence() >= expiry_ledger
+    }
+
+    // Claims keep the airdrop and their bitmap words alive until it expires
+    fn claimed_word(e: &Env, index: u32) -> u128 {
+        bump_instance(e);
+        read_persistent(e, &DataKey::ClaimedWord(index / BITS_PER_WORD)).unwrap_or(0)
+    }
+
+    fn set_claimed(e: &Env, index: u32) {
+        let word = Self::claimed_word(e, index) | (1u128 << (index % BITS_PER_WORD));
+        write_persistent(e, &DataKey::ClaimedWord(index / BITS_PER_WORD), &word);
+    }
+
+    fn token_balance(e: &Env) -> i128 {
+        let token: Address = e.storage().instance().get(&DataKey::Token).unwrap();
+        token::Client::new(e, &token).balance(&e.current_contract_address())
+    }
+
+    pub fn __constructor(e: Env, admin: Address, token: Address, merkle_root: BytesN<32>, expiry_ledger: u32) {
+        assert!(expiry_ledger > e.ledger().sequence(), "Expiry must be in the future");
+        e.storage().instance().set(&DataKey::Admin, &admin);
+        e.storage().instance().set(&DataKey::Token, &token);
+        e.storage().instance().set(&DataKey::MerkleRoot, &merkle_root);
+        e.storage().instance().set(&DataKey::ExpiryLedger, &expiry_ledger);
+    }
+
+    /// Deposit airdrop tokens from the funder
+    pub fn fund(e: Env, funder: Address, amount: i128) {
+        funder.require_auth();
+        Self::check_nonnegative_amount(amount);
+        assert!(!Self::expired(&e), "Airdrop expired");
+
+        let token: Address = e.storage().instance().get(&DataKey::Token).unwrap();
+        token::Client::new(&e, &token).transfer(&funder, &e.current_contract_address(), &amount);
+    }
+
+    /// Pay out the claim at `index` to `account`. Anyone can submit a claim, the tokens
+    /// always go to the account in the leaf
+    pub fn claim(e: Env, index: u32, account: Address, amount: i128, proof: Vec<BytesN<32>>) {
+        Self::check_nonnegative_amount(amount);
+        assert!(!Self::expired(&e), "Airdrop expired");
+        assert!(!Self::is_claimed(e.clone(), index), "Already claimed");
+
+        let root: BytesN<32> = e.storage().instance().get(&DataKey::MerkleRoot).unwrap();
+        assert!(verify(&e, &root, leaf_hash(&e, index, &account, amount), &proof), "Invalid proof");
+
+        Self::set_claimed(&e, index);
+        let token: Address = e.storage().instance().get(&DataKey::Token).unwrap();
+        token::Client::new(&e, &token).transfer(&e.current_contract_address(), &account, &amount);
+
+        e.events().publish((symbol_short!("claimed"), index), (account, amount));
+    }
+
+    /// Send everything left unclaimed to `to` once the airdrop has expired (admin only).
+    /// Returns the amount swept
+    pub fn sweep(e: Env, admin: Address, to: Address) -> i128 {
+        Self::require_admin(&e, &admin);
+        assert!(Self::expired(&e), "Airdrop not expired");
+
+        let amount = Self::token_balance(&e);
+        if amount > 0 {
+            let token: Address = e.storage().instance().get(&DataKey::Token).unwrap();
+            token::Client::new(&e, &token).transfer(&e.current_contract_address(), &to, &amount);
+        }
+
+        e.events().publish((symbol_short!("swept"),), (to, amount));
+        amount
+    }
+
+    pub fn set_admin(e: Env, admin: Address, new_admin: Address) {
+        Self::require_admin(&e, &admin);
+        e.storage().instance().set(&DataKey::Admin, &new_admin);
+    }
+
+    // View functions
+    pub fn get_admin(e: Env) -> Address {
+        e.storage().instance().get(&DataKey::Admin).unwrap()
+    }
+
+    pub fn get_token(e: Env) -> Address {
+        e.storage().instance().get(&DataKey::Token).unwrap()
+    }
+
+    pub fn get_merkle_root(e: Env) -> BytesN<32> {
+        e.storage().instance().get(&DataKey::MerkleRoot).unwrap()
+    }
+
+    pub fn get_expiry_ledger(e: Env) -> u32 {
+        e.storage().instance().get(&DataKey::ExpiryLedger).unwrap()
+    }
+
+    /// Tokens still held for unclaimed allocations
+    pub fn get_remaining(e: Env) -> i128 {
+        Self::token_balance(&e)
+    }
+
+    pub fn is_claimed(e: Env, index: u32) -> bool {
+        Self::claimed_word(&e, index) & (1u128 << (index % BITS_PER_WORD)) != 0
+    }
+
+    pub fn is_expired(e: Env) -> bool {
+        Self::expired(&e)
+    }
+}
+
+#[derive(Clone)]
+#[contracttype]
+enum DataKey {
+    Admin,
+    Token,
+    MerkleRoot,
+    ExpiryLedger,
+    ClaimedWord(u32), // Bitmap of claimed indexes, BITS_PER_WORD per entry
+}
+
+mod merkle;
+mod test;
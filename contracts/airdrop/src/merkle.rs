@@ -0,0 +1,12 @@
+//! Merkle leaves for airdrop claims, verified with the shared `merkleproof` crate.
+//!
+//! Each leaf commits to a claim: sha256(index as 4 big-endian bytes || address XDR || amount as 16
+//! big-endian bytes). The index picks the claim's bit in the claimed bitmap.
+use soroban_sdk::{xdr::ToXdr, Address, Bytes, BytesN, Env};
+
+pub(crate) fn leaf_hash(e: &Env, index: u32, account: &Address, amount: i128) -> BytesN<32> {
+    let mut data = Bytes::from_array(e, &index.to_be_bytes());
+    data.append(&account.clone().to_xdr(e));
+    data.extend_from_array(&amount.to_be_bytes());
+    e.crypto().sha256(&data).to_bytes()
+}
@@ -0,0 +1,744 @@
+#![no_std]
+
+use soroban_sdk::{
+    contract, contractimpl, contracttype, symbol_short, vec, Address, BytesN, Env, Vec, I256, U256,
+    token::{self},
+};
+use storagettl::{read_persistent, write_persistent};
+
+use crate::math::{
+    amounts_for_liquidity, compute_swap_step, liquidity_for_amounts, sqrt_price_at_tick,
+    tick_at_sqrt_price, MAX_TICK, MIN_TICK, Q64_BITS,
+};
+
+// Constants
+const BPS_DENOMINATOR: u32 = 10000;
+const MAX_TICK_SPACING: u32 = 16384;
+const TICKS_PER_WORD: i32 = 128; // Initialized ticks are tracked in u128 bitmap words
+const SCHEMA_VERSION: u32 = 1; // Storage layout version, bump with every PoolInfo, Tick, Position or DataKey change
+
+// Overflow protection functions
+fn checked_add(a: i128, b: i128) -> i128 {
+    a.checked_add(b).expect("Overflow in addition")
+}
+
+fn checked_sub(a: i128, b: i128) -> i128 {
+    a.checked_sub(b).expect("Underflow in subtraction")
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PoolInfo {
+    pub token_a: Address,
+    pub token_b: Address,
+    pub fee_bps: u32,
+    pub tick_spacing: u32,
+    pub sqrt_price: U256,           // sqrt(token_b per token_a) as Q64.64
+    pub tick: i32,                  // Greatest tick whose sqrt price is <= sqrt_price
+    pub liquidity: i128,            // Liquidity of positions whose range contains the price
+    pub fee_growth_global_a: I256,  // Fees per unit of liquidity, Q64.64
+    pub fee_growth_global_b: I256,  // Fees per unit of liquidity, Q64.64
+    pub reserve_a: i128,            // token_a held, including uncollected fees and withdrawals
+    pub reserve_b: i128,            // token_b held, including uncollected fees and withdrawals
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TickInfo {
+    pub liquidity_gross: i128,        // Total liquidity of positions using this tick as a bound
+    pub liquidity_net: i128,          // Liquidity added when the price crosses this tick upwards
+    pub fee_growth_outside_a: I256,   // Fee growth on the other side of this tick from the price
+    pub fee_growth_outside_b: I256,
+}
+
+/// A liquidity position; each one is a non-fungible token identified by its ID
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Position {
+    pub owner: Address,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: i128,
+    pub fee_growth_inside_a_last: I256, // Fee growth inside the range when fees were last settled
+    pub fee_growth_inside_b_last: I256,
+    pub tokens_owed_a: i128,            // Settled fees and withdrawn liquidity awaiting collect
+    pub tokens_owed_b: i128,
+}
+
+#[contract]
+pub struct ConcentratedLiquidityPool;
+
+#[contractimpl]
+impl ConcentratedLiquidityPool {
+
+    fn check_nonnegative_amount(amount: i128) {
+        if amount < 0 {
+            panic!("negative amount is not allowed: {}", amount)
+        }
+    }
+
+    fn check_ticks(pool_info: &PoolInfo, tick_lower: i32, tick_upper: i32) {
+        assert!(tick_lower < tick_upper, "Invalid tick range");
+        assert!(tick_lower >= MIN_TICK && tick_upper <= MAX_TICK, "Tick out of range");
+        let spacing = pool_info.tick_spacing as i32;
+        assert!(tick_lower % spacing == 0 && tick_upper % spacing == 0, "Tick not aligned to spacing");
+    }
+
+    fn get_pool_info(e: &Env) -> PoolInfo {
+        e.storage().instance().get(&symbol_short!("pool")).unwrap()
+    }
+
+    fn set_pool_info(e: &Env, pool_info: &PoolInfo) {
+        e.storage().instance().set(&symbol_short!("pool"), pool_info);
+    }
+
+    fn empty_tick(e: &Env) -> TickInfo {
+        TickInfo {
+            liquidity_gross: 0,
+            liquidity_net: 0,
+            fee_growth_outside_a: I256::from_i32(e, 0),
+            fee_growth_outside_b: I256::from_i32(e, 0),
+        }
+    }
+
+    fn read_tick(e: &Env, tick: i32) -> TickInfo {
+        read_persistent(e, &DataKey::Tick(tick)).unwrap_or(Self::empty_tick(e))
+    }
+
+    // Bitmap word and bit of a tick, counted in units of the tick spacing and rounded down
+    fn tick_position(tick: i32, tick_spacing: u32) -> (i32, u32) {
+        let compressed = tick.div_euclid(tick_spacing as i32);
+        (compressed.div_euclid(TICKS_PER_WORD), compressed.rem_euclid(TICKS_PER_WORD) as u32)
+    }
+
+    fn read_tick_bitmap(e: &Env, word: i32) -> u128 {
+        read_persistent(e, &DataKey::TickBitmap(word)).unwrap_or(0)
+    }
+
+    // Set or clear an aligned tick's bit, dropping words that become empty
+    fn flip_tick(e: &Env, tick_spacing: u32, tick: i32, initialized: bool) {
+        let (word, bit) = Self::tick_position(tick, tick_spacing);
+        let bitmap = if initialized {
+            Self::read_tick_bitmap(e, word) | (1 << bit)
+        } else {
+            Self::read_tick_bitmap(e, word) & !(1 << bit)
+        };
+        if bitmap == 0 {
+            e.storage().persistent().remove(&DataKey::TickBitmap(word));
+        } else {
+            write_persistent(e, &DataKey::TickBitmap(word), &bitmap);
+        }
+    }
+
+    fn read_position(e: &Env, position_id: u64) -> Position {
+        read_persistent(e, &DataKey::Position(position_id)).expect("Position does not exist")
+    }
+
+    fn read_owner_positions(e: &Env, owner: &Address) -> Vec<u64> {
+        read_persistent(e, &DataKey::OwnerPositions(owner.clone())).unwrap_or(vec![e])
+    }
+
+    fn add_owner_position(e: &Env, owner: &Address, position_id: u64) {
+        let mut positions = Self::read_owner_positions(e, owner);
+        positions.push_back(position_id);
+        write_persistent(e, &DataKey::OwnerPositions(owner.clone()), &positions);
+    }
+
+    fn remove_owner_position(e: &Env, owner: &Address, position_id: u64) {
+        let mut positions = Self::read_owner_positions(e, owner);
+        if let Some(index) = positions.first_index_of(position_id) {
+            positions.remove(index);
+        }
+        write_persistent(e, &DataKey::OwnerPositions(owner.clone()), &positions);
+    }
+
+    fn require_position_owner(e: &Env, caller: &Address, position_id: u64) -> Position {
+        let position = Self::read_position(e, position_id);
+        assert!(position.owner == *caller, "Not position owner");
+        position
+    }
+
+    // Add `liquidity_delta` to a tick bound, initializing it on first use
+    fn update_tick(e: &Env, pool_info: &PoolInfo, tick: i32, liquidity_delta: i128, is_upper: bool) {
+        let mut info = Self::read_tick(e, tick);
+        let gross_before = info.liquidity_gross;
+        info.liquidity_gross = checked_add(gross_before, liquidity_delta);
+        assert!(info.liquidity_gross >= 0, "Insufficient position liquidity");
+
+        if gross_before == 0 && info.liquidity_gross > 0 {
+            // By convention all fee growth so far happened below a tick at or below the price
+            if tick <= pool_info.tick {
+                info.fee_growth_outside_a = pool_info.fee_growth_global_a.clone();
+                info.fee_growth_outside_b = pool_info.fee_growth_global_b.clone();
+            }
+            Self::flip_tick(e, pool_info.tick_spacing, tick, true);
+        }
+
+        info.liquidity_net = if is_upper {
+            checked_sub(info.liquidity_net, liquidity_delta)
+        } else {
+            checked_add(info.liquidity_net, liquidity_delta)
+        };
+        write_persistent(e, &DataKey::Tick(tick), &info);
+    }
+
+    // Drop a tick no position uses any more
+    fn clear_tick_if_unused(e: &Env, pool_info: &PoolInfo, tick: i32) {
+        if Self::read_tick(e, tick).liquidity_gross > 0 {
+            return;
+        }
+        e.storage().persistent().remove(&DataKey::Tick(tick));
+        Self::flip_tick(e, pool_info.tick_spacing, tick, false);
+    }
+
+    // Flip a tick's outside fee growth as the price crosses it, returning its liquidity_net
+    fn cross_tick(e: &Env, pool_info: &PoolInfo, tick: i32, commit: bool) -> i128 {
+        let mut info = Self::read_tick(e, tick);
+        if commit {
+            info.fee_growth_outside_a = pool_info.fee_growth_global_a.sub(&info.fee_growth_outside_a);
+            info.fee_growth_outside_b = pool_info.fee_growth_global_b.sub(&info.fee_growth_outside_b);
+            write_persistent(e, &DataKey::Tick(tick), &info);
+        }
+        info.liquidity_net
+    }
+
+    // Fee growth per unit of liquidity that happened while the price was inside [lower, upper)
+    fn fee_growth_inside(e: &Env, pool_info: &PoolInfo, tick_lower: i32, tick_upper: i32) -> (I256, I256) {
+        let lower = Self::read_tick(e, tick_lower);
+        let upper = Self::read_tick(e, tick_upper);
+        let global_a = &pool_info.fee_growth_global_a;
+        let global_b = &pool_info.fee_growth_global_b;
+
+        let (below_a, below_b) = if pool_info.tick >= tick_lower {
+            (lower.fee_growth_outside_a, lower.fee_growth_outside_b)
+        } else {
+            (global_a.sub(&lower.fee_growth_outside_a), global_b.sub(&lower.fee_growth_outside_b))
+        };
+        let (above_a, above_b) = if pool_info.tick < tick_upper {
+            (upper.fee_growth_outside_a, upper.fee_growth_outside_b)
+        } else {
+            (global_a.sub(&upper.fee_growth_outside_a), global_b.sub(&upper.fee_growth_outside_b))
+        };
+
+        (global_a.sub(&below_a).sub(&above_a), global_b.sub(&below_b).sub(&above_b))
+    }
+
+    fn fees_earned(e: &Env, liquidity: i128, growth_delta: &I256) -> i128 {
+        I256::from_i128(e, liquidity)
+            .mul(growth_delta)
+            .shr(Q64_BITS)
+            .to_i128()
+            .expect("Overflow in conversion")
+    }
+
+    // Apply a liquidity change to a position, settling its fees first.
+    // Returns the token amounts the change is worth (rounded in the pool's favour).
+    fn modify_position(e: &Env, pool_info: &mut PoolInfo, position: &mut Position, liquidity_delta: i128) -> (i128, i128) {
+        let (tick_lower, tick_upper) = (position.tick_lower, position.tick_upper);
+        if liquidity_delta != 0 {
+            Self::update_tick(e, pool_info, tick_lower, liquidity_delta, false);
+            Self::update_tick(e, pool_info, tick_upper, liquidity_delta, true);
+        }
+
+        let (inside_a, inside_b) = Self::fee_growth_inside(e, pool_info, tick_lower, tick_upper);
+        let fees_a = Self::fees_earned(e, position.liquidity, &inside_a.sub(&position.fee_growth_inside_a_last));
+        let fees_b = Self::fees_earned(e, position.liquidity, &inside_b.sub(&position.fee_growth_inside_b_last));
+        position.tokens_owed_a = checked_add(position.tokens_owed_a, fees_a);
+        position.tokens_owed_b = checked_add(position.tokens_owed_b, fees_b);
+        position.fee_growth_inside_a_last = inside_a;
+        position.fee_growth_inside_b_last = inside_b;
+
+        position.liquidity = checked_add(position.liquidity, liquidity_delta);
+        assert!(position.liquidity >= 0, "Insufficient position liquidity");
+
+        if liquidity_delta < 0 {
+            Self::clear_tick_if_unused(e, pool_info, tick_lower);
+            Self::clear_tick_if_unused(e, pool_info, tick_upper);
+        }
+
+        if (tick_lower..tick_upper).contains(&pool_info.tick) {
+            pool_info.liquidity = checked_add(pool_info.liquidity, liquidity_delta);
+        }
+        amounts_for_liquidity(
+            e,
+            &pool_info.sqrt_price,
+            &sqrt_price_at_tick(e, tick_lower),
+            &sqrt_price_at_tick(e, tick_upper),
+            liquidity_delta.abs(),
+            liquidity_delta > 0,
+        )
+    }
+
+    // Closest initialized tick in the swap direction within the current bitmap word. Without one,
+    // the word's last tick (or the range bound) is returned uninitialized, so each step reads one word.
+    fn next_initialized_tick(e: &Env, tick_spacing: u32, tick: i32, zero_for_one: bool) -> (i32, bool) {
+        let spacing = tick_spacing as i32;
+        let (next, initialized) = if zero_for_one {
+            // Greatest initialized tick at or below the current one
+            let (word, bit) = Self::tick_position(tick, tick_spacing);
+            let mask = if bit == 127 { u128::MAX } else { (1 << (bit + 1)) - 1 };
+            let masked = Self::read_tick_bitmap(e, word) & mask;
+            let word_start = word * TICKS_PER_WORD;
+            if masked != 0 {
+                ((word_start + 127 - masked.leading_zeros() as i32) * spacing, true)
+            } else {
+                (word_start * spacing, false)
+            }
+        } else {
+            // Smallest initialized tick above the current one
+            let (word, bit) = Self::tick_position(tick.div_euclid(spacing) * spacing + spacing, tick_spacing);
+            let masked = Self::read_tick_bitmap(e, word) & !((1 << bit) - 1);
+            let word_start = word * TICKS_PER_WORD;
+            if masked != 0 {
+                ((word_start + masked.trailing_zeros() as i32) * spacing, true)
+            } else {
+                ((word_start + TICKS_PER_WORD - 1) * spacing, false)
+            }
+        };
+        (next.clamp(MIN_TICK, MAX_TICK), initialized)
+    }
+
+    // Exact input swap across as many ticks as needed. Tick crossings are only written
+    // when `commit` is set so quotes can share the same path.
+    fn run_swap(e: &Env, pool_info: &mut PoolInfo, zero_for_one: bool, amount_in: i128, commit: bool) -> i128 {
+        let mut amount_remaining = amount_in;
+        let mut amount_out = 0;
+
+        while amount_remaining > 0 {
+            let (tick_next, initialized) = Self::next_initialized_tick(e, pool_info.tick_spacing, pool_info.tick, zero_for_one);
+            let sqrt_target = sqrt_price_at_tick(e, tick_next);
+            // A word boundary can sit at the current price, but the range bounds can't be passed
+            let at_bound = tick_next == MIN_TICK || tick_next == MAX_TICK;
+            if sqrt_target == pool_info.sqrt_price && !initialized && at_bound {
+                panic!("Insufficient liquidity for swap");
+            }
+
+            let step = compute_swap_step(
+                e,
+                &pool_info.sqrt_price,
+                &sqrt_target,
+                pool_info.liquidity,
+                amount_remaining,
+                pool_info.fee_bps,
+            );
+            amount_remaining = checked_sub(amount_remaining, checked_add(step.amount_in, step.fee_amount));
+            amount_out = checked_add(amount_out, step.amount_out);
+
+            if pool_info.liquidity > 0 && step.fee_amount > 0 {
+                let growth = I256::from_i128(e, step.fee_amount)
+                    .shl(Q64_BITS)
+                    .div(&I256::from_i128(e, pool_info.liquidity));
+                if zero_for_one {
+                    pool_info.fee_growth_global_a = pool_info.fee_growth_global_a.add(&growth);
+                } else {
+                    pool_info.fee_growth_global_b = pool_info.fee_growth_global_b.add(&growth);
+                }
+            }
+
+            let reached_target = step.sqrt_price_next == sqrt_target;
+            pool_info.sqrt_price = step.sqrt_price_next;
+            if reached_target {
+                if initialized {
+                    let liquidity_net = Self::cross_tick(e, pool_info, tick_next, commit);
+                    pool_info.liquidity = if zero_for_one {
+                        checked_sub(pool_info.liquidity, liquidity_net)
+                    } else {
+                        checked_add(pool_info.liquidity, liquidity_net)
+                    };
+                }
+                pool_info.tick = if zero_for_one { tick_next - 1 } else { tick_next };
+            } else {
+                let (lower, upper) = if zero_for_one {
+                    (tick_next, pool_info.tick)
+                } else {
+                    (pool_info.tick, tick_next)
+                };
+                pool_info.tick = tick_at_sqrt_price(e, &pool_info.sqrt_price, lower, upper);
+            }
+        }
+
+        amount_out
+    }
+
+    pub fn __constructor(
+        e: Env,
+        token_a: Address,
+        token_b: Address,
+        fee_bps: u32,
+        tick_spacing: u32,
+        sqrt_price: U256,
+        factory: Address,
+    ) {
+        assert!(token_a != token_b, "Tokens must be different");
+        assert!(fee_bps < BPS_DENOMINATOR, "Invalid fee");
+        assert!(tick_spacing > 0 && tick_spacing <= MAX_TICK_SPACING, "Invalid tick spacing");
+        assert!(
+            sqrt_price >= sqrt_price_at_tick(&e, MIN_TICK) && sqrt_price < sqrt_price_at_tick(&e, MAX_TICK),
+            "Price out of range"
+        );
+
+        let pool_info = PoolInfo {
+            token_a,
+            token_b,
+            fee_bps,
+            tick_spacing,
+            tick: tick_at_sqrt_price(&e, &sqrt_price, MIN_TICK, MAX_TICK),
+            sqrt_price,
+            liquidity: 0,
+            fee_growth_global_a: I256::from_i32(&e, 0),
+            fee_growth_global_b: I256::from_i32(&e, 0),
+            reserve_a: 0,
+            reserve_b: 0,
+        };
+
+        Self::set_pool_info(&e, &pool_info);
+        e.storage().instance().set(&DataKey::NextPositionId, &1u64);
+        e.storage().instance().set(&DataKey::Factory, &factory);
+        e.storage().instance().set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+    }
+
+    fn require_factory(e: &Env) {
+        let factory: Address = e.storage().instance().get(&DataKey::Factory).unwrap();
+        factory.require_auth();
+    }
+
+    /// Replace the pool code (factory only). The factory calls `migrate` right after, with the new code
+    pub fn upgrade(e: Env, new_wasm_hash: BytesN<32>) {
+        Self::require_factory(&e);
+        e.deployer().update_current_contract_wasm(new_wasm_hash.clone());
+        e.events().publish((symbol_short!("upgrade"),), new_wasm_hash);
+    }
+
+    /// Convert the storage written by older code to this code's layout (factory only).
+    /// Returns the schema version the storage is at
+    pub fn migrate(e: Env) -> u32 {
+        Self::require_factory(&e);
+        let version = Self::schema_version(e.clone());
+        assert!(version <= SCHEMA_VERSION, "Storage is newer than the code");
+        // A layout change adds its conversion here, reading the previous PoolInfo, Tick or Position
+        // struct when `version` is below the one that introduced the change
+        if version < SCHEMA_VERSION {
+            e.storage().instance().set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+            e.events().publish((symbol_short!("migrate"),), (version, SCHEMA_VERSION));
+        }
+        SCHEMA_VERSION
+    }
+
+    /// Open a position over [tick_lower, tick_upper) with as much liquidity as the amounts allow.
+    /// Returns (position_id, liquidity, amount_a, amount_b)
+    pub fn mint_position(
+        e: Env,
+        caller: Address,
+        tick_lower: i32,
+        tick_upper: i32,
+        amount_a_desired: i128,
+        amount_b_desired: i128,
+    ) -> (u64, i128, i128, i128) {
+        caller.require_auth();
+        Self::check_nonnegative_amount(amount_a_desired);
+        Self::check_nonnegative_amount(amount_b_desired);
+
+        let pool_info = Self::get_pool_info(&e);
+        Self::check_ticks(&pool_info, tick_lower, tick_upper);
+
+        let position_id: u64 = e.storage().instance().get(&DataKey::NextPositionId).unwrap();
+        e.storage().instance().set(&DataKey::NextPositionId, &(position_id + 1));
+
+        let mut position = Position {
+            owner: caller.clone(),
+            tick_lower,
+            tick_upper,
+            liquidity: 0,
+            fee_growth_inside_a_last: I256::from_i32(&e, 0),
+            fee_growth_inside_b_last: I256::from_i32(&e, 0),
+            tokens_owed_a: 0,
+            tokens_owed_b: 0,
+        };
+        let (liquidity, amount_a, amount_b) =
+            Self::add_position_liquidity(&e, &caller, &mut position, amount_a_desired, amount_b_desired);
+
+        write_persistent(&e, &DataKey::Position(position_id), &position);
+        Self::add_owner_position(&e, &caller, position_id);
+
+        (position_id, liquidity, amount_a, amount_b)
+    }
+
+    /// Add liquidity to an existing position. Returns (liquidity, amount_a, amount_b)
+    pub fn increase_liquidity(
+        e: Env,
+        caller: Address,
+        position_id: u64,
+        amount_a_desired: i128,
+        amount_b_desired: i128,
+    ) -> (i128, i128, i128) {
+        caller.require_auth();
+        Self::check_nonnegative_amount(amount_a_desired);
+        Self::check_nonnegative_amount(amount_b_desired);
+
+        let mut position = Self::require_position_owner(&e, &caller, position_id);
+        let result = Self::add_position_liquidity(&e, &caller, &mut position, amount_a_desired, amount_b_desired);
+        write_persistent(&e, &DataKey::Position(position_id), &position);
+
+        result
+    }
+
+    fn add_position_liquidity(
+        e: &Env,
+        caller: &Address,
+        position: &mut Position,
+        amount_a_desired: i128,
+        amount_b_desired: i128,
+    ) -> (i128, i128, i128) {
+        let mut pool_info = Self::get_pool_info(e);
+        let liquidity = liquidity_for_amounts(
+            e,
+            &pool_info.sqrt_price,
+            &sqrt_price_at_tick(e, position.tick_lower),
+            &sqrt_price_at_tick(e, position.tick_upper),
+            amount_a_desired,
+            amount_b_desired,
+        );
+        assert!(liquidity > 0, "Insufficient liquidity minted");
+
+        let (amount_a, amount_b) = Self::modify_position(e, &mut pool_info, position, liquidity);
+
+        let token_a_client = token::Client::new(e, &pool_info.token_a);
+        let token_b_client = token::Client::new(e, &pool_info.token_b);
+        if amount_a > 0 {
+            token_a_client.transfer_from(&e.current_contract_address(), caller, &e.current_contract_address(), &amount_a);
+        }
+        if amount_b > 0 {
+            token_b_client.transfer_from(&e.current_contract_address(), caller, &e.current_contract_address(), &amount_b);
+        }
+
+        pool_info.reserve_a = checked_add(pool_info.reserve_a, amount_a);
+        pool_info.reserve_b = checked_add(pool_info.reserve_b, amount_b);
+        Self::set_pool_info(e, &pool_info);
+
+        (liquidity, amount_a, amount_b)
+    }
+
+    /// Remove liquidity from a position. The tokens are credited to the position and paid out by `collect`.
+    /// Returns (amount_a, amount_b)
+    pub fn decrease_liquidity(e: Env, caller: Address, position_id: u64, liquidity: i128) -> (i128, i128) {
+        caller.require_auth();
+        Self::check_nonnegative_amount(liquidity);
+        assert!(liquidity > 0, "Liquidity must be > 0");
+
+        let mut position = Self::require_position_owner(&e, &caller, position_id);
+        assert!(liquidity <= position.liquidity, "Insufficient position liquidity");
+
+        let mut pool_info = Self::get_pool_info(&e);
+        let (amount_a, amount_b) = Self::modify_position(&e, &mut pool_info, &mut position, -liquidity);
+        position.tokens_owed_a = checked_add(position.tokens_owed_a, amount_a);
+        position.tokens_owed_b = checked_add(position.tokens_owed_b, amount_b);
+
+        Self::set_pool_info(&e, &pool_info);
+        write_persistent(&e, &DataKey::Position(position_id), &position);
+
+        (amount_a, amount_b)
+    }
+
+    /// Pay out a position's fees and withdrawn liquidity. Returns (amount_a, amount_b)
+    pub fn collect(e: Env, caller: Address, position_id: u64) -> (i128, i128) {
+        caller.require_auth();
+
+        let mut position = Self::require_position_owner(&e, &caller, position_id);
+        let mut pool_info = Self::get_pool_info(&e);
+        if position.liquidity > 0 {
+            // Settle fees earned since the last change
+            Self::modify_position(&e, &mut pool_info, &mut position, 0);
+        }
+
+        let (amount_a, amount_b) = (position.tokens_owed_a, position.tokens_owed_b);
+        position.tokens_owed_a = 0;
+        position.tokens_owed_b = 0;
+
+        if amount_a > 0 {
+            token::Client::new(&e, &pool_info.token_a).transfer(&e.current_contract_address(), &caller, &amount_a);
+        }
+        if amount_b > 0 {
+            token::Client::new(&e, &pool_info.token_b).transfer(&e.current_contract_address(), &caller, &amount_b);
+        }
+
+        pool_info.reserve_a = checked_sub(pool_info.reserve_a, amount_a);
+        pool_info.reserve_b = checked_sub(pool_info.reserve_b, amount_b);
+        Self::set_pool_info(&e, &pool_info);
+        write_persistent(&e, &DataKey::Position(position_id), &position);
+
+        (amount_a, amount_b)
+    }
+
+    /// Destroy an empty position
+    pub fn burn_position(e: Env, caller: Address, position_id: u64) {
+        caller.require_auth();
+
+        let position = Self::require_position_owner(&e, &caller, position_id);
+        assert!(
+            position.liquidity == 0 && position.tokens_owed_a == 0 && position.tokens_owed_b == 0,
+            "Position not empty"
+        );
+
+        e.storage().persistent().remove(&DataKey::Position(position_id));
+        Self::remove_owner_position(&e, &caller, position_id);
+    }
+
+    /// Transfer ownership of a position, including its uncollected fees
+    pub fn transfer_position(e: Env, from: Address, to: Address, position_id: u64) {
+        from.require_auth();
+
+        let mut position = Self::require_position_owner(&e, &from, position_id);
+        position.owner = to.clone();
+        write_persistent(&e, &DataKey::Position(position_id), &position);
+
+        Self::remove_owner_position(&e, &from, position_id);
+        Self::add_owner_position(&e, &to, position_id);
+    }
+
+    pub fn swap(e: Env, caller: Address, input_token: Address, amount_in: i128) -> i128 {
+        caller.require_auth();
+        Self::check_nonnegative_amount(amount_in);
+
+        let mut pool_info = Self::get_pool_info(&e);
+
+        assert!(amount_in > 0, "Amount in must be > 0");
+        assert!(
+            input_token == pool_info.token_a || input_token == pool_info.token_b,
+            "Invalid token address"
+        );
+
+        // Selling token_a pushes the price (token_b per token_a) down
+        let zero_for_one = input_token == pool_info.token_a;
+        let amount_out = Self::run_swap(&e, &mut pool_info, zero_for_one, amount_in, true);
+        assert!(amount_out > 0, "Insufficient output amount");
+
+        let token_out = if zero_for_one { pool_info.token_b.clone() } else { pool_info.token_a.clone() };
+        token::Client::new(&e, &input_token).transfer_from(&e.current_contract_address(), &caller, &e.current_contract_address(), &amount_in);
+        token::Client::new(&e, &token_out).transfer(&e.current_contract_address(), &caller, &amount_out);
+
+        let (volume_a, volume_b) = Self::get_token_volumes(e.clone());
+        let volumes = if zero_for_one {
+            pool_info.reserve_a = checked_add(pool_info.reserve_a, amount_in);
+            pool_info.reserve_b = checked_sub(pool_info.reserve_b, amount_out);
+            (checked_add(volume_a, amount_in), checked_add(volume_b, amount_out))
+        } else {
+            pool_info.reserve_b = checked_add(pool_info.reserve_b, amount_in);
+            pool_info.reserve_a = checked_sub(pool_info.reserve_a, amount_out);
+            (checked_add(volume_a, amount_out), checked_add(volume_b, amount_in))
+        };
+        Self::set_pool_info(&e, &pool_info);
+        e.storage().instance().set(&DataKey::TokenVolumes, &volumes);
+
+        amount_out
+    }
+
+    // View functions
+    pub fn get_factory(e: Env) -> Address {
+        e.storage().instance().get(&DataKey::Factory).unwrap()
+    }
+
+    pub fn schema_version(e: Env) -> u32 {
+        e.storage().instance().get(&DataKey::SchemaVersion).unwrap_or(1)
+    }
+
+    pub fn get_token_a(e: Env) -> Address {
+        Self::get_pool_info(&e).token_a
+    }
+
+    pub fn get_token_b(e: Env) -> Address {
+        Self::get_pool_info(&e).token_b
+    }
+
+    pub fn get_reserves(e: Env) -> (i128, i128) {
+        let pool_info = Self::get_pool_info(&e);
+        (pool_info.reserve_a, pool_info.reserve_b)
+    }
+
+    /// All-time swap volume of (token A, token B), each counting what was swapped in and out in that token
+    pub fn get_token_volumes(e: Env) -> (i128, i128) {
+        e.storage().instance().get(&DataKey::TokenVolumes).unwrap_or((0, 0))
+    }
+
+    pub fn get_fee_bps(e: Env) -> u32 {
+        Self::get_pool_info(&e).fee_bps
+    }
+
+    pub fn get_tick_spacing(e: Env) -> u32 {
+        Self::get_pool_info(&e).tick_spacing
+    }
+
+    pub fn get_sqrt_price(e: Env) -> U256 {
+        Self::get_pool_info(&e).sqrt_price
+    }
+
+    pub fn get_tick(e: Env) -> i32 {
+        Self::get_pool_info(&e).tick
+    }
+
+    pub fn get_liquidity(e: Env) -> i128 {
+        Self::get_pool_info(&e).liquidity
+    }
+
+    pub fn get_fee_growth_global(e: Env) -> (I256, I256) {
+        let pool_info = Self::get_pool_info(&e);
+        (pool_info.fee_growth_global_a, pool_info.fee_growth_global_b)
+    }
+
+    pub fn get_tick_info(e: Env, tick: i32) -> TickInfo {
+        Self::read_tick(&e, tick)
+    }
+
+    /// Initialized ticks of a bitmap word: bit `i` of word `w` is tick `(w * 128 + i) * tick_spacing`
+    pub fn get_tick_bitmap(e: Env, word: i32) -> u128 {
+        Self::read_tick_bitmap(&e, word)
+    }
+
+    pub fn get_amount_out(e: Env, input_token: Address, amount_in: i128) -> i128 {
+        Self::check_nonnegative_amount(amount_in);
+        let mut pool_info = Self::get_pool_info(&e);
+        assert!(
+            input_token == pool_info.token_a || input_token == pool_info.token_b,
+            "Invalid token address"
+        );
+        let zero_for_one = input_token == pool_info.token_a;
+        Self::run_swap(&e, &mut pool_info, zero_for_one, amount_in, false)
+    }
+
+    pub fn get_position(e: Env, position_id: u64) -> Position {
+        Self::read_position(&e, position_id)
+    }
+
+    /// Fees owed to a position, including those not yet settled into it
+    pub fn get_position_fees(e: Env, position_id: u64) -> (i128, i128) {
+        let position = Self::read_position(&e, position_id);
+        let pool_info = Self::get_pool_info(&e);
+        let (inside_a, inside_b) = Self::fee_growth_inside(&e, &pool_info, position.tick_lower, position.tick_upper);
+        let fees_a = Self::fees_earned(&e, position.liquidity, &inside_a.sub(&position.fee_growth_inside_a_last));
+        let fees_b = Self::fees_earned(&e, position.liquidity, &inside_b.sub(&position.fee_growth_inside_b_last));
+        (checked_add(position.tokens_owed_a, fees_a), checked_add(position.tokens_owed_b, fees_b))
+    }
+
+    pub fn owner_of(e: Env, position_id: u64) -> Address {
+        Self::read_position(&e, position_id).owner
+    }
+
+    pub fn get_positions(e: Env, owner: Address) -> Vec<u64> {
+        Self::read_owner_positions(&e, &owner)
+    }
+}
+
+#[derive(Clone)]
+#[contracttype]
+enum DataKey {
+    Tick(i32),
+    TickBitmap(i32), // Word of 128 ticks, one bit per tick with liquidity_gross > 0
+    Position(u64),
+    OwnerPositions(Address),
+    NextPositionId,
+    TokenVolumes, // All-time swap volume of (token A, token B)
+    Factory, // Factory allowed to upgrade the pool
+    SchemaVersion, // Storage layout version, see SCHEMA_VERSION
+}
+
+mod math;
+mod test;
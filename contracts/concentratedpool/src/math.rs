@@ -0,0 +1,282 @@
+//! Tick and sqrt price math for concentrated liquidity.
+//!
+//! Square root prices are Q64.64 fixed point numbers (token_b per token_a) held in U256.
+//! The tick range is limited so sqrt prices stay within [2^32, 2^96], which keeps every
+//! intermediate product below 2^256.
+use soroban_sdk::{Env, U256};
+
+pub(crate) const MIN_TICK: i32 = -443636;
+pub(crate) const MAX_TICK: i32 = 443636;
+pub(crate) const Q64_BITS: u32 = 64;
+const BPS_DENOMINATOR: u32 = 10000;
+
+// 2^128 / sqrt(1.0001)^(2^i) for each bit i of the absolute tick
+const TICK_RATIOS: [u128; 19] = [
+    0xfffcb933bd6fad37aa2d162d1a594001,
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x9aa508b5b7a84e1c677de54f3e99bc9,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe98,
+];
+
+pub(crate) fn to_u256(e: &Env, value: i128) -> U256 {
+    if value < 0 {
+        panic!("negative amount is not allowed: {}", value)
+    }
+    U256::from_u128(e, value as u128)
+}
+
+pub(crate) fn to_i128(value: &U256) -> i128 {
+    let value = value.to_u128().expect("Overflow in conversion");
+    i128::try_from(value).expect("Overflow in conversion")
+}
+
+fn zero(e: &Env) -> U256 {
+    U256::from_u32(e, 0)
+}
+
+fn div_up(a: &U256, b: &U256) -> U256 {
+    let quotient = a.div(b);
+    if a.rem_euclid(b) == zero(a.env()) {
+        quotient
+    } else {
+        quotient.add(&U256::from_u32(a.env(), 1))
+    }
+}
+
+/// sqrt(1.0001^tick) as Q64.64
+pub(crate) fn sqrt_price_at_tick(e: &Env, tick: i32) -> U256 {
+    assert!((MIN_TICK..=MAX_TICK).contains(&tick), "Tick out of range");
+    let abs_tick = tick.unsigned_abs();
+
+    // Q128.128 ratio built from the precomputed powers of each set bit
+    let mut ratio = if abs_tick & 1 != 0 {
+        U256::from_u128(e, TICK_RATIOS[0])
+    } else {
+        U256::from_u32(e, 1).shl(128)
+    };
+    for (bit, tick_ratio) in TICK_RATIOS.iter().enumerate().skip(1) {
+        if abs_tick & (1 << bit) != 0 {
+            ratio = ratio.mul(&U256::from_u128(e, *tick_ratio)).shr(128);
+        }
+    }
+    if tick > 0 {
+        let max = U256::from_parts(e, u64::MAX, u64::MAX, u64::MAX, u64::MAX);
+        ratio = max.div(&ratio);
+    }
+
+    // Q128.128 -> Q64.64, rounding up so the price at a tick is never understated
+    div_up(&ratio, &U256::from_u32(e, 1).shl(Q64_BITS))
+}
+
+/// Greatest tick in [lower, upper] whose sqrt price is <= `sqrt_price`
+pub(crate) fn tick_at_sqrt_price(e: &Env, sqrt_price: &U256, lower: i32, upper: i32) -> i32 {
+    let mut low = lower.max(MIN_TICK);
+    let mut high = upper.min(MAX_TICK);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if sqrt_price_at_tick(e, mid) <= *sqrt_price {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    low
+}
+
+/// token_a needed to move between two sqrt prices: L * (sb - sa) / (sa * sb)
+pub(crate) fn amount_a_delta(e: &Env, sqrt_a: &U256, sqrt_b: &U256, liquidity: i128, round_up: bool) -> i128 {
+    let (lower, upper) = if sqrt_a <= sqrt_b { (sqrt_a, sqrt_b) } else { (sqrt_b, sqrt_a) };
+    let numerator = to_u256(e, liquidity).shl(Q64_BITS);
+
+    // Split as L/sa - L/sb so the product never needs more than 256 bits
+    let amount = if round_up {
+        div_up(&numerator, lower).sub(&numerator.div(upper))
+    } else {
+        let low_part = numerator.div(lower);
+        let high_part = div_up(&numerator, upper);
+        if low_part > high_part { low_part.sub(&high_part) } else { zero(e) }
+    };
+    to_i128(&amount)
+}
+
+/// token_b needed to move between two sqrt prices: L * (sb - sa)
+pub(crate) fn amount_b_delta(e: &Env, sqrt_a: &U256, sqrt_b: &U256, liquidity: i128, round_up: bool) -> i128 {
+    let (lower, upper) = if sqrt_a <= sqrt_b { (sqrt_a, sqrt_b) } else { (sqrt_b, sqrt_a) };
+    let product = to_u256(e, liquidity).mul(&upper.sub(lower));
+    let amount = if round_up {
+        div_up(&product, &U256::from_u32(e, 1).shl(Q64_BITS))
+    } else {
+        product.shr(Q64_BITS)
+    };
+    to_i128(&amount)
+}
+
+/// Liquidity bought by `amount` of token_a over a range entirely above the price
+pub(crate) fn liquidity_for_amount_a(e: &Env, sqrt_a: &U256, sqrt_b: &U256, amount: i128) -> i128 {
+    let (lower, upper) = if sqrt_a <= sqrt_b { (sqrt_a, sqrt_b) } else { (sqrt_b, sqrt_a) };
+    let intermediate = lower.mul(upper).shr(Q64_BITS);
+    to_i128(&to_u256(e, amount).mul(&intermediate).div(&upper.sub(lower)))
+}
+
+/// Liquidity bought by `amount` of token_b over a range entirely below the price
+pub(crate) fn liquidity_for_amount_b(e: &Env, sqrt_a: &U256, sqrt_b: &U256, amount: i128) -> i128 {
+    let (lower, upper) = if sqrt_a <= sqrt_b { (sqrt_a, sqrt_b) } else { (sqrt_b, sqrt_a) };
+    to_i128(&to_u256(e, amount).shl(Q64_BITS).div(&upper.sub(lower)))
+}
+
+/// Token amounts worth `liquidity` over [sqrt_lower, sqrt_upper] at the current price
+pub(crate) fn amounts_for_liquidity(
+    e: &Env,
+    sqrt_price: &U256,
+    sqrt_lower: &U256,
+    sqrt_upper: &U256,
+    liquidity: i128,
+    round_up: bool,
+) -> (i128, i128) {
+    if sqrt_price < sqrt_lower {
+        (amount_a_delta(e, sqrt_lower, sqrt_upper, liquidity, round_up), 0)
+    } else if sqrt_price < sqrt_upper {
+        (
+            amount_a_delta(e, sqrt_price, sqrt_upper, liquidity, round_up),
+            amount_b_delta(e, sqrt_lower, sqrt_price, liquidity, round_up),
+        )
+    } else {
+        (0, amount_b_delta(e, sqrt_lower, sqrt_upper, liquidity, round_up))
+    }
+}
+
+fn max_liquidity(
+    e: &Env,
+    sqrt_price: &U256,
+    sqrt_lower: &U256,
+    sqrt_upper: &U256,
+    amount_a: i128,
+    amount_b: i128,
+) -> i128 {
+    if sqrt_price <= sqrt_lower {
+        liquidity_for_amount_a(e, sqrt_lower, sqrt_upper, amount_a)
+    } else if sqrt_price < sqrt_upper {
+        let liquidity_a = liquidity_for_amount_a(e, sqrt_price, sqrt_upper, amount_a);
+        let liquidity_b = liquidity_for_amount_b(e, sqrt_lower, sqrt_price, amount_b);
+        liquidity_a.min(liquidity_b)
+    } else {
+        liquidity_for_amount_b(e, sqrt_lower, sqrt_upper, amount_b)
+    }
+}
+
+/// Largest liquidity whose rounded up cost fits within `amount_a` and `amount_b`
+pub(crate) fn liquidity_for_amounts(
+    e: &Env,
+    sqrt_price: &U256,
+    sqrt_lower: &U256,
+    sqrt_upper: &U256,
+    amount_a: i128,
+    amount_b: i128,
+) -> i128 {
+    // Rounding can make the cost of the computed liquidity overshoot by a unit or two,
+    // so shrink the budget by the overshoot until it fits
+    let (mut budget_a, mut budget_b) = (amount_a, amount_b);
+    loop {
+        let liquidity = max_liquidity(e, sqrt_price, sqrt_lower, sqrt_upper, budget_a, budget_b);
+        let (cost_a, cost_b) = amounts_for_liquidity(e, sqrt_price, sqrt_lower, sqrt_upper, liquidity, true);
+        if cost_a <= amount_a && cost_b <= amount_b {
+            return liquidity;
+        }
+        budget_a -= (cost_a - amount_a).max(0);
+        budget_b -= (cost_b - amount_b).max(0);
+    }
+}
+
+// Price after adding `amount_in` of the input token, rounded so the pool never gives away extra
+fn next_sqrt_price_from_input(e: &Env, sqrt_price: &U256, liquidity: i128, amount_in: i128, zero_for_one: bool) -> U256 {
+    let liquidity = to_u256(e, liquidity);
+    if zero_for_one {
+        // Flooring L / sqrt_price can push the rounded up result past the starting price
+        let numerator = liquidity.shl(Q64_BITS);
+        let next = div_up(&numerator, &numerator.div(sqrt_price).add(&to_u256(e, amount_in)));
+        if next > *sqrt_price { sqrt_price.clone() } else { next }
+    } else {
+        sqrt_price.add(&to_u256(e, amount_in).shl(Q64_BITS).div(&liquidity))
+    }
+}
+
+pub(crate) struct SwapStep {
+    pub sqrt_price_next: U256,
+    pub amount_in: i128,
+    pub amount_out: i128,
+    pub fee_amount: i128,
+}
+
+/// Swap as much of `amount_remaining` as fits before the price reaches `sqrt_target`
+pub(crate) fn compute_swap_step(
+    e: &Env,
+    sqrt_price: &U256,
+    sqrt_target: &U256,
+    liquidity: i128,
+    amount_remaining: i128,
+    fee_bps: u32,
+) -> SwapStep {
+    let zero_for_one = sqrt_price >= sqrt_target;
+    let fee_bps = fee_bps as i128;
+    let denominator = BPS_DENOMINATOR as i128;
+    let remaining_less_fee = amount_remaining
+        .checked_mul(denominator - fee_bps)
+        .expect("Overflow in multiplication")
+        / denominator;
+
+    let max_amount_in = if zero_for_one {
+        amount_a_delta(e, sqrt_target, sqrt_price, liquidity, true)
+    } else {
+        amount_b_delta(e, sqrt_price, sqrt_target, liquidity, true)
+    };
+    let sqrt_price_next = if remaining_less_fee >= max_amount_in {
+        sqrt_target.clone()
+    } else {
+        next_sqrt_price_from_input(e, sqrt_price, liquidity, remaining_less_fee, zero_for_one)
+    };
+    let reached_target = sqrt_price_next == *sqrt_target;
+
+    // Rounding up can overshoot the remaining amount by a unit when the fee is zero
+    let amount_in = if reached_target {
+        max_amount_in
+    } else if zero_for_one {
+        amount_a_delta(e, &sqrt_price_next, sqrt_price, liquidity, true).min(amount_remaining)
+    } else {
+        amount_b_delta(e, sqrt_price, &sqrt_price_next, liquidity, true).min(amount_remaining)
+    };
+    let amount_out = if zero_for_one {
+        amount_b_delta(e, &sqrt_price_next, sqrt_price, liquidity, false)
+    } else {
+        amount_a_delta(e, sqrt_price, &sqrt_price_next, liquidity, false)
+    };
+
+    // When the step stops short of the target everything left over is fee
+    let fee_amount = if reached_target {
+        let fee = to_u256(e, amount_in).mul(&to_u256(e, fee_bps));
+        to_i128(&div_up(&fee, &to_u256(e, denominator - fee_bps)))
+    } else {
+        amount_remaining - amount_in
+    };
+
+    SwapStep {
+        sqrt_price_next,
+        amount_in,
+        amount_out,
+        fee_amount,
+    }
+}
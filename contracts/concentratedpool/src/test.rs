@@ -0,0 +1,356 @@
+#![cfg(test)]
+extern crate std;
+use soroban_sdk::{
+    Env, String, Address, FromVal, U256,
+    testutils::Address as _,
+};
+use crate::ConcentratedLiquidityPool;
+use crate::ConcentratedLiquidityPoolClient;
+use crate::math::{sqrt_price_at_tick, tick_at_sqrt_price};
+use ::token::Token;
+use ::token::TokenClient;
+
+// Helper function to create a test token
+fn create_token<'a>(e: &Env, admin: &Address) -> TokenClient<'a> {
+    let token_contract = e.register(
+        Token,
+        (
+            admin,
+            7_u32,
+            String::from_val(e, &"name"),
+            String::from_val(e, &"symbol"),
+        ),
+    );
+    TokenClient::new(e, &token_contract)
+}
+
+// Pool with 0.3% fee, tick spacing 60 and a starting price of 1
+fn deploy_pool<'a>(e: &Env, token_a: &TokenClient<'a>, token_b: &TokenClient<'a>) -> ConcentratedLiquidityPoolClient<'a> {
+    let contract_id = e.register(
+        ConcentratedLiquidityPool,
+        (
+            &token_a.address,
+            &token_b.address,
+            30_u32,
+            60_u32,
+            U256::from_u32(e, 1).shl(64),
+            Address::generate(e),
+        ),
+    );
+    ConcentratedLiquidityPoolClient::new(e, &contract_id)
+}
+
+fn mint_position(
+    pool: &ConcentratedLiquidityPoolClient,
+    token_a: &TokenClient,
+    token_b: &TokenClient,
+    user: &Address,
+    tick_lower: i32,
+    tick_upper: i32,
+    amount: i128,
+) -> (u64, i128, i128, i128) {
+    token_a.approve(user, &pool.address, &amount, &1000);
+    token_b.approve(user, &pool.address, &amount, &1000);
+    pool.mint_position(user, &tick_lower, &tick_upper, &amount, &amount)
+}
+
+fn swap(pool: &ConcentratedLiquidityPoolClient, token_in: &TokenClient, user: &Address, amount: i128) -> i128 {
+    token_in.approve(user, &pool.address, &amount, &1000);
+    pool.swap(user, &token_in.address, &amount)
+}
+
+#[test]
+fn test_sqrt_price_at_tick() {
+    let env = Env::default();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let ticks = [-443636, -200000, -60, -1, 0, 1, 60, 887, 100000, 443636];
+    for tick in ticks {
+        let expected = 1.0001_f64.powf(tick as f64 / 2.0);
+        let actual = sqrt_price_at_tick(&env, tick).to_u128().unwrap() as f64 / 2_f64.powi(64);
+        assert!((actual - expected).abs() / expected < 1e-9, "tick {}", tick);
+
+        // The tick lookup inverts the price lookup
+        let sqrt_price = sqrt_price_at_tick(&env, tick);
+        assert_eq!(tick_at_sqrt_price(&env, &sqrt_price, -443636, 443636), tick);
+    }
+}
+
+#[test]
+fn test_mint_in_range_position() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user);
+    let token_b = create_token(&env, &user);
+    token_a.mint(&user, &10_000_000_000);
+    token_b.mint(&user, &10_000_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b);
+    assert_eq!(pool.get_tick(), 0);
+
+    let (position_id, liquidity, amount_a, amount_b) =
+        mint_position(&pool, &token_a, &token_b, &user, -600, 600, 10_000_000_000);
+
+    // A symmetric range around the price takes equal amounts of both tokens
+    assert_eq!(position_id, 1);
+    assert!(liquidity > 0);
+    assert_eq!(amount_a, amount_b);
+    assert!(amount_a <= 10_000_000_000 && amount_a > 9_999_000_000);
+    assert_eq!(pool.get_liquidity(), liquidity);
+    assert_eq!(pool.get_reserves(), (amount_a, amount_b));
+    // Ticks -600 and 600 are -10 and 10 spacings, in bitmap words -1 and 0
+    assert_eq!(pool.get_tick_bitmap(&-1), 1 << 118);
+    assert_eq!(pool.get_tick_bitmap(&0), 1 << 10);
+
+    let position = pool.get_position(&position_id);
+    assert_eq!(position.owner, user);
+    assert_eq!(position.liquidity, liquidity);
+    assert_eq!(pool.owner_of(&position_id), user);
+    assert_eq!(pool.get_positions(&user).len(), 1);
+}
+
+#[test]
+fn test_out_of_range_position_holds_one_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user);
+    let token_b = create_token(&env, &user);
+    token_a.mint(&user, &10_000_000_000);
+    token_b.mint(&user, &10_000_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b);
+
+    // Above the price only token_a is needed, below it only token_b
+    let (_, _, amount_a, amount_b) = mint_position(&pool, &token_a, &token_b, &user, 600, 1200, 1_000_000_000);
+    assert!(amount_a <= 1_000_000_000 && amount_a > 999_999_990);
+    assert_eq!(amount_b, 0);
+
+    let (_, _, amount_a, amount_b) = mint_position(&pool, &token_a, &token_b, &user, -1200, -600, 1_000_000_000);
+    assert_eq!(amount_a, 0);
+    assert!(amount_b > 0);
+
+    // Neither range contains the price
+    assert_eq!(pool.get_liquidity(), 0);
+}
+
+#[test]
+fn test_swap_and_collect_fees() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let lp = Address::generate(&env);
+    let trader = Address::generate(&env);
+
+    let token_a = create_token(&env, &lp);
+    let token_b = create_token(&env, &lp);
+    token_a.mint(&lp, &10_000_000_000);
+    token_b.mint(&lp, &10_000_000_000);
+    token_a.mint(&trader, &100_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b);
+    let (position_id, _, amount_a, amount_b) =
+        mint_position(&pool, &token_a, &token_b, &lp, -600, 600, 10_000_000_000);
+
+    let quoted = pool.get_amount_out(&token_a.address, &100_000_000);
+    let amount_out = swap(&pool, &token_a, &trader, 100_000_000);
+
+    assert_eq!(amount_out, quoted);
+    assert!(amount_out < 100_000_000 && amount_out > 99_000_000);
+    assert_eq!(token_b.balance(&trader), amount_out);
+    assert!(pool.get_tick() < 0);
+    assert_eq!(pool.get_reserves(), (amount_a + 100_000_000, amount_b - amount_out));
+    assert_eq!(pool.get_token_volumes(), (100_000_000, amount_out));
+
+    // The only LP earns the whole 0.3% fee, less rounding
+    let (fees_a, fees_b) = pool.get_position_fees(&position_id);
+    assert!((299_990..=300_000).contains(&fees_a));
+    assert_eq!(fees_b, 0);
+
+    let (collected_a, collected_b) = pool.collect(&lp, &position_id);
+    assert_eq!((collected_a, collected_b), (fees_a, 0));
+    assert_eq!(token_a.balance(&lp), 10_000_000_000 - amount_a + fees_a);
+    assert_eq!(pool.get_position_fees(&position_id), (0, 0));
+}
+
+#[test]
+fn test_swap_crosses_initialized_tick() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let lp = Address::generate(&env);
+    let trader = Address::generate(&env);
+
+    let token_a = create_token(&env, &lp);
+    let token_b = create_token(&env, &lp);
+    token_a.mint(&lp, &20_000_000_000);
+    token_b.mint(&lp, &20_000_000_000);
+    token_b.mint(&trader, &2_000_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b);
+    let (_, inner_liquidity, _, _) = mint_position(&pool, &token_a, &token_b, &lp, -60, 60, 1_000_000_000);
+    let (outer_id, outer_liquidity, _, _) = mint_position(&pool, &token_a, &token_b, &lp, 60, 1200, 10_000_000_000);
+    assert_eq!(pool.get_liquidity(), inner_liquidity);
+
+    // Buying token_a pushes the price up through tick 60 into the second range
+    swap(&pool, &token_b, &trader, 2_000_000_000);
+    assert!(pool.get_tick() >= 60);
+    assert_eq!(pool.get_liquidity(), outer_liquidity);
+
+    // Fees earned after the crossing accrue to the upper range
+    let (_, fees_b) = pool.get_position_fees(&outer_id);
+    assert!(fees_b > 0);
+}
+
+#[test]
+fn test_swap_reaches_liquidity_in_later_bitmap_words() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let lp = Address::generate(&env);
+    let trader = Address::generate(&env);
+
+    let token_a = create_token(&env, &lp);
+    let token_b = create_token(&env, &lp);
+    token_a.mint(&lp, &10_000_000_000);
+    token_b.mint(&lp, &10_000_000_000);
+    token_a.mint(&trader, &100_000_000);
+    token_b.mint(&trader, &100_000_000);
+
+    // Ranges several bitmap words (128 spacings of 60) away from the price on either side
+    let pool = deploy_pool(&env, &token_a, &token_b);
+    mint_position(&pool, &token_a, &token_b, &lp, 24_000, 25_200, 1_000_000_000);
+    mint_position(&pool, &token_a, &token_b, &lp, -25_200, -24_000, 1_000_000_000);
+    assert_eq!(pool.get_liquidity(), 0);
+
+    // The price moves through the empty words until it reaches the range above it
+    assert!(swap(&pool, &token_b, &trader, 100_000_000) > 0);
+    assert!((24_000..25_200).contains(&pool.get_tick()));
+
+    // And back down past the price it started at into the range below it
+    swap(&pool, &token_a, &trader, 100_000_000);
+    assert!((-25_200..-24_000).contains(&pool.get_tick()));
+}
+
+#[test]
+fn test_decrease_liquidity_and_burn() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user);
+    let token_b = create_token(&env, &user);
+    token_a.mint(&user, &1_000_000_000);
+    token_b.mint(&user, &1_000_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b);
+    let (position_id, liquidity, amount_a, amount_b) =
+        mint_position(&pool, &token_a, &token_b, &user, -600, 600, 1_000_000_000);
+
+    let (removed_a, removed_b) = pool.decrease_liquidity(&user, &position_id, &liquidity);
+    assert!(removed_a <= amount_a && amount_a - removed_a <= 1);
+    assert!(removed_b <= amount_b && amount_b - removed_b <= 1);
+    assert_eq!(pool.get_liquidity(), 0);
+    assert_eq!(pool.get_tick_bitmap(&-1), 0);
+    assert_eq!(pool.get_tick_bitmap(&0), 0);
+
+    pool.collect(&user, &position_id);
+    assert_eq!(token_a.balance(&user), 1_000_000_000 - amount_a + removed_a);
+
+    pool.burn_position(&user, &position_id);
+    assert_eq!(pool.get_positions(&user).len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Position not empty")]
+fn test_burn_non_empty_position_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user);
+    let token_b = create_token(&env, &user);
+    token_a.mint(&user, &1_000_000_000);
+    token_b.mint(&user, &1_000_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b);
+    let (position_id, _, _, _) = mint_position(&pool, &token_a, &token_b, &user, -600, 600, 1_000_000_000);
+    pool.burn_position(&user, &position_id);
+}
+
+#[test]
+fn test_transfer_position() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+
+    let token_a = create_token(&env, &user1);
+    let token_b = create_token(&env, &user1);
+    token_a.mint(&user1, &1_000_000_000);
+    token_b.mint(&user1, &1_000_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b);
+    let (position_id, liquidity, _, _) = mint_position(&pool, &token_a, &token_b, &user1, -600, 600, 1_000_000_000);
+
+    pool.transfer_position(&user1, &user2, &position_id);
+    assert_eq!(pool.owner_of(&position_id), user2);
+    assert_eq!(pool.get_positions(&user1).len(), 0);
+    assert_eq!(pool.get_positions(&user2).len(), 1);
+
+    // The new owner controls the liquidity
+    let (amount_a, _) = pool.decrease_liquidity(&user2, &position_id, &liquidity);
+    pool.collect(&user2, &position_id);
+    assert_eq!(token_a.balance(&user2), amount_a);
+}
+
+#[test]
+#[should_panic(expected = "Not position owner")]
+fn test_non_owner_cannot_decrease_liquidity() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+
+    let token_a = create_token(&env, &user1);
+    let token_b = create_token(&env, &user1);
+    token_a.mint(&user1, &1_000_000_000);
+    token_b.mint(&user1, &1_000_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b);
+    let (position_id, liquidity, _, _) = mint_position(&pool, &token_a, &token_b, &user1, -600, 600, 1_000_000_000);
+    pool.decrease_liquidity(&user2, &position_id, &liquidity);
+}
+
+#[test]
+#[should_panic(expected = "Tick not aligned to spacing")]
+fn test_unaligned_tick_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user);
+    let token_b = create_token(&env, &user);
+    token_a.mint(&user, &1_000_000_000);
+    token_b.mint(&user, &1_000_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b);
+    mint_position(&pool, &token_a, &token_b, &user, -600, 610, 1_000_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient liquidity for swap")]
+fn test_swap_past_all_liquidity_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user);
+    let token_b = create_token(&env, &user);
+    token_a.mint(&user, &10_000_000_000);
+    token_b.mint(&user, &1_000_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b);
+    mint_position(&pool, &token_a, &token_b, &user, -60, 60, 1_000_000_000);
+    swap(&pool, &token_a, &user, 9_000_000_000);
+}
@@ -0,0 +1,354 @@
+#![no_std]
+
+use soroban_sdk::{
+    contract, contractclient, contractimpl, contracttype, Address, BytesN, Env, String, Vec,
+    token::{self},
+};
+
+use poolseed::{get_or_create_pool, seed_pool};
+use storagettl::{read_persistent, write_persistent};
+
+// Prices are quoted in XLM per token, scaled by 1e18
+const PRICE_SCALE: i128 = 1_000_000_000_000_000_000;
+
+// Overflow protection functions
+fn checked_add(a: i128, b: i128) -> i128 {
+    a.checked_add(b).expect("Overflow in addition")
+}
+
+fn checked_sub(a: i128, b: i128) -> i128 {
+    a.checked_sub(b).expect("Underflow in subtraction")
+}
+
+fn checked_mul(a: i128, b: i128) -> i128 {
+    a.checked_mul(b).expect("Overflow in multiplication")
+}
+
+fn checked_div(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        panic!("Division by zero");
+    }
+    a.checked_div(b).expect("Overflow in division")
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct CurveConfig {
+    pub token_decimals: u32,
+    pub token_supply: i128,          // Minted into the launchpad for every launch
+    pub virtual_xlm_reserve: i128,   // XLM the curve is priced against before any buys
+    pub graduation_market_cap: i128, // Market cap in XLM at which a curve moves into a pool
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct Curve {
+    pub creator: Address,
+    pub token_reserve: i128,   // Tokens left on the curve
+    pub xlm_reserve: i128,     // XLM raised, excluding the virtual reserve
+    pub pool: Option<Address>, // Pool the curve graduated into
+}
+
+// Mirror of `tokenfactory::TokenConfig`
+#[derive(Clone)]
+#[contracttype]
+pub struct TokenConfig {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u32,
+    pub initial_supply: i128,
+    pub max_supply: i128,
+    pub owner: Address,
+    pub metadata: String,
+    pub clawback_enabled: bool,
+}
+
+#[contractclient(name = "TokenFactoryClient")]
+pub trait TokenFactoryInterface {
+    fn create_token(env: Env, admin_addr: Address, config: TokenConfig, salt: BytesN<32>) -> Address;
+    fn sync_token_owner(env: Env, token_addr: Address) -> Option<Address>;
+}
+
+#[contractclient(name = "LaunchTokenClient")]
+pub trait LaunchTokenInterface {
+    fn renounce_ownership(e: Env);
+}
+
+#[contractclient(name = "PoolClient")]
+pub trait PoolInterface {
+    fn balance_of(e: Env, id: Address) -> i128;
+}
+
+#[contract]
+pub struct Launchpad;
+
+#[contractimpl]
+impl Launchpad {
+
+    fn check_nonnegative_amount(amount: i128) {
+        if amount < 0 {
+            panic!("negative amount is not allowed: {}", amount)
+        }
+    }
+
+    fn get_config(env: &Env) -> CurveConfig {
+        env.storage().instance().get(&DataKey::Config).unwrap()
+    }
+
+    fn get_xlm_token(env: &Env) -> Address {
+        env.storage().instance().get(&DataKey::XlmToken).unwrap()
+    }
+
+    fn load_curve(env: &Env, token: &Address) -> Curve {
+        read_persistent(env, &DataKey::Curve(token.clone())).expect("Launch not found")
+    }
+
+    fn save_curve(env: &Env, token: &Address, curve: &Curve) {
+        write_persistent(env, &DataKey::Curve(token.clone()), curve);
+    }
+
+    // XLM side of the curve, including the virtual reserve
+    fn total_xlm_reserve(config: &CurveConfig, curve: &Curve) -> i128 {
+        checked_add(config.virtual_xlm_reserve, curve.xlm_reserve)
+    }
+
+    fn calculate_tokens_out(config: &CurveConfig, curve: &Curve, xlm_in: i128) -> i128 {
+        let xlm_reserve = Self::total_xlm_reserve(config, curve);
+        checked_div(checked_mul(curve.token_reserve, xlm_in), checked_add(xlm_reserve, xlm_in))
+    }
+
+    fn calculate_xlm_out(config: &CurveConfig, curve: &Curve, tokens_in: i128) -> i128 {
+        let xlm_reserve = Self::total_xlm_reserve(config, curve);
+        checked_div(checked_mul(xlm_reserve, tokens_in), checked_add(curve.token_reserve, tokens_in))
+    }
+
+    fn calculate_market_cap(config: &CurveConfig, curve: &Curve) -> i128 {
+        let xlm_reserve = Self::total_xlm_reserve(config, curve);
+        checked_div(checked_mul(xlm_reserve, config.token_supply), curve.token_reserve)
+    }
+
+    // Seed the token/XLM pool with the raised XLM and enough tokens to keep the curve's final
+    // price, burn the tokens left over and keep the LP tokens. A pool seeded ahead of graduation
+    // is swapped to that price first, so nobody can set the price the curve graduates at
+    fn graduate(env: &Env, token: &Address, curve: &mut Curve) {
+        let config = Self::get_config(env);
+        let xlm_token = Self::get_xlm_token(env);
+        let factory_addr: Address = env.storage().instance().get(&DataKey::PoolFactory).unwrap();
+
+        let pool_addr = get_or_create_pool(
+            env,
+            &factory_addr,
+            token,
+            &xlm_token,
+            String::from_str(env, "Launchpad LP"),
+            String::from_str(env, "LLP"),
+        )
+        .expect("Pool factory unavailable");
+
+        // Target XLM per token, as an (xlm, token) ratio
+        let price = (Self::total_xlm_reserve(&config, curve), curve.token_reserve);
+        let (token_amount, xlm_amount) = seed_pool(env, &pool_addr, token, curve.token_reserve, curve.xlm_reserve, price);
+
+        let current = env.current_contract_address();
+        let token_client = token::Client::new(env, token);
+        let xlm_client = token::Client::new(env, &xlm_token);
+        let tokens_left = checked_sub(curve.token_reserve, token_amount);
+        if tokens_left > 0 {
+            token_client.burn(&current, &tokens_left);
+        }
+        let xlm_left = checked_sub(curve.xlm_reserve, xlm_amount);
+        if xlm_left > 0 {
+            xlm_client.transfer(&current, &curve.creator, &xlm_left);
+        }
+
+        curve.token_reserve = 0;
+        curve.xlm_reserve = 0;
+        curve.pool = Some(pool_addr);
+    }
+
+    pub fn __constructor(env: Env, token_factory: Address, pool_factory: Address, xlm_token: Address, config: CurveConfig) {
+        assert!(config.token_supply > 0, "Token supply must be > 0");
+        assert!(config.virtual_xlm_reserve > 0, "Virtual reserve must be > 0");
+        // The market cap starts at the virtual reserve
+        assert!(config.graduation_market_cap > config.virtual_xlm_reserve, "Invalid graduation market cap");
+
+        env.storage().instance().set(&DataKey::TokenFactory, &token_factory);
+        env.storage().instance().set(&DataKey::PoolFactory, &pool_factory);
+        env.storage().instance().set(&DataKey::XlmToken, &xlm_token);
+        env.storage().instance().set(&DataKey::Config, &config);
+    }
+
+    /// Create a token through the TokenFactory with its whole supply on a new curve
+    pub fn launch(
+        env: Env,
+        creator: Address,
+        token_name: String,
+        token_symbol: String,
+        token_metadata: String,
+        salt: BytesN<32>,
+    ) -> Address {
+        creator.require_auth();
+        let config = Self::get_config(&env);
+
+        // The launchpad is the token's admin and owner, so the supply is minted to it. Launched
+        // tokens have a fixed supply and no owner: everything is minted up front, then the
+        // launchpad renounces ownership so the metadata can't change either
+        let factory_addr: Address = env.storage().instance().get(&DataKey::TokenFactory).unwrap();
+        let token_factory = TokenFactoryClient::new(&env, &factory_addr);
+        let token_config = TokenConfig {
+            name: token_name,
+            symbol: token_symbol,
+            decimals: config.token_decimals,
+            initial_supply: config.token_supply,
+            max_supply: config.token_supply,
+            owner: env.current_contract_address(),
+            metadata: token_metadata,
+            clawback_enabled: false,
+        };
+        let token = token_factory.create_token(&env.current_contract_address(), &token_config, &salt);
+        LaunchTokenClient::new(&env, &token).renounce_ownership();
+        token_factory.sync_token_owner(&token);
+
+        let curve = Curve {
+            creator,
+            token_reserve: config.token_supply,
+            xlm_reserve: 0,
+            pool: None,
+        };
+        Self::save_curve(&env, &token, &curve);
+
+        let index = Self::get_launch_count(env.clone());
+        write_persistent(&env, &DataKey::LaunchAt(index), &token);
+        env.storage().instance().set(&DataKey::LaunchCount, &(index + 1));
+
+        token
+    }
+
+    /// Buy tokens from the curve with XLM. The buy that reaches the graduation market cap
+    /// moves the curve into a pool. Returns the tokens bought
+    pub fn buy(env: Env, buyer: Address, token: Address, xlm_in: i128, min_tokens_out: i128) -> i128 {
+        buyer.require_auth();
+        Self::check_nonnegative_amount(min_tokens_out);
+        assert!(xlm_in > 0, "Amount in must be > 0");
+
+        let config = Self::get_config(&env);
+        let mut curve = Self::load_curve(&env, &token);
+        assert!(curve.pool.is_none(), "Curve graduated");
+
+        let tokens_out = Self::calculate_tokens_out(&config, &curve, xlm_in);
+        assert!(tokens_out > 0, "Insufficient output amount");
+        assert!(tokens_out >= min_tokens_out, "Insufficient output amount");
+
+        token::Client::new(&env, &Self::get_xlm_token(&env)).transfer(&buyer, &env.current_contract_address(), &xlm_in);
+        token::Client::new(&env, &token).transfer(&env.current_contract_address(), &buyer, &tokens_out);
+
+        curve.xlm_reserve = checked_add(curve.xlm_reserve, xlm_in);
+        curve.token_reserve = checked_sub(curve.token_reserve, tokens_out);
+        if Self::calculate_market_cap(&config, &curve) >= config.graduation_market_cap {
+            Self::graduate(&env, &token, &mut curve);
+        }
+        Self::save_curve(&env, &token, &curve);
+
+        tokens_out
+    }
+
+    /// Sell tokens back to the curve for XLM. Returns the XLM received
+    pub fn sell(env: Env, seller: Address, token: Address, tokens_in: i128, min_xlm_out: i128) -> i128 {
+        seller.require_auth();
+        Self::check_nonnegative_amount(min_xlm_out);
+        assert!(tokens_in > 0, "Amount in must be > 0");
+
+        let config = Self::get_config(&env);
+        let mut curve = Self::load_curve(&env, &token);
+        assert!(curve.pool.is_none(), "Curve graduated");
+
+        let xlm_out = Self::calculate_xlm_out(&config, &curve, tokens_in);
+        assert!(xlm_out > 0, "Insufficient output amount");
+        assert!(xlm_out >= min_xlm_out, "Insufficient output amount");
+        assert!(xlm_out <= curve.xlm_reserve, "Insufficient reserves");
+
+        token::Client::new(&env, &token).transfer(&seller, &env.current_contract_address(), &tokens_in);
+        token::Client::new(&env, &Self::get_xlm_token(&env)).transfer(&env.current_contract_address(), &seller, &xlm_out);
+
+        curve.token_reserve = checked_add(curve.token_reserve, tokens_in);
+        curve.xlm_reserve = checked_sub(curve.xlm_reserve, xlm_out);
+        Self::save_curve(&env, &token, &curve);
+
+        xlm_out
+    }
+
+    // View functions
+    pub fn get_curve(env: Env, token: Address) -> Curve {
+        Self::load_curve(&env, &token)
+    }
+
+    pub fn get_curve_config(env: Env) -> CurveConfig {
+        Self::get_config(&env)
+    }
+
+    /// Get up to `limit` launched tokens, in launch order, starting at index `offset`
+    pub fn get_launches(env: Env, offset: u32, limit: u32) -> Vec<Address> {
+        let end = offset.saturating_add(limit).min(Self::get_launch_count(env.clone()));
+        let mut launches = Vec::new(&env);
+        for i in offset..end {
+            launches.push_back(read_persistent(&env, &DataKey::LaunchAt(i)).unwrap());
+        }
+        launches
+    }
+
+    pub fn get_launch_count(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::LaunchCount).unwrap_or(0)
+    }
+
+    pub fn is_graduated(env: Env, token: Address) -> bool {
+        Self::load_curve(&env, &token).pool.is_some()
+    }
+
+    /// Current curve price in XLM per token, scaled by 1e18
+    pub fn get_price(env: Env, token: Address) -> i128 {
+        let config = Self::get_config(&env);
+        let curve = Self::load_curve(&env, &token);
+        assert!(curve.pool.is_none(), "Curve graduated");
+        checked_div(checked_mul(Self::total_xlm_reserve(&config, &curve), PRICE_SCALE), curve.token_reserve)
+    }
+
+    /// Current market cap in XLM: the curve price times the full supply
+    pub fn get_market_cap(env: Env, token: Address) -> i128 {
+        let config = Self::get_config(&env);
+        let curve = Self::load_curve(&env, &token);
+        assert!(curve.pool.is_none(), "Curve graduated");
+        Self::calculate_market_cap(&config, &curve)
+    }
+
+    pub fn get_buy_quote(env: Env, token: Address, xlm_in: i128) -> i128 {
+        Self::check_nonnegative_amount(xlm_in);
+        Self::calculate_tokens_out(&Self::get_config(&env), &Self::load_curve(&env, &token), xlm_in)
+    }
+
+    pub fn get_sell_quote(env: Env, token: Address, tokens_in: i128) -> i128 {
+        Self::check_nonnegative_amount(tokens_in);
+        Self::calculate_xlm_out(&Self::get_config(&env), &Self::load_curve(&env, &token), tokens_in)
+    }
+
+    /// LP tokens locked in the launchpad for a graduated token
+    pub fn get_locked_liquidity(env: Env, token: Address) -> i128 {
+        match Self::load_curve(&env, &token).pool {
+            Some(pool_addr) => PoolClient::new(&env, &pool_addr).balance_of(&env.current_contract_address()),
+            None => 0,
+        }
+    }
+}
+
+#[derive(Clone)]
+#[contracttype]
+enum DataKey {
+    TokenFactory,
+    PoolFactory,
+    XlmToken,
+    Config,
+    LaunchCount,    // Number of tokens launched
+    LaunchAt(u32),  // Launch index to token, in persistent storage
+    Curve(Address), // Curve state by token
+}
+
+mod test;
@@ -0,0 +1,287 @@
+#![cfg(test)]
+extern crate std;
+use soroban_sdk::{
+    Env, String, Address, BytesN, FromVal, vec,
+    testutils::Address as _,
+    token,
+};
+use crate::{CurveConfig, Launchpad, LaunchpadClient};
+use pool::LiquidityPoolClient;
+use poolfactory::PoolFactory;
+use poolfactory::PoolFactoryClient;
+use tokenfactory::TokenFactory;
+use tokenfactory::TokenFactoryClient;
+use ::token::Token;
+use ::token::TokenClient;
+
+mod pool_contract {
+    soroban_sdk::contractimport!(file = "../../target/wasm32v1-none/release/pool.wasm");
+}
+
+mod token_contract {
+    soroban_sdk::contractimport!(file = "../../target/wasm32v1-none/release/tokenlaunch.wasm");
+}
+
+// 1B tokens priced against 30k virtual XLM, graduating at a 100k XLM market cap
+const SUPPLY: i128 = 10_000_000_000_000_000;
+const VIRTUAL_XLM: i128 = 300_000_000_000;
+const GRADUATION_MARKET_CAP: i128 = 1_000_000_000_000;
+
+// Helper function to create a test token
+fn create_token<'a>(e: &Env, admin: &Address) -> TokenClient<'a> {
+    let token_contract = e.register(
+        Token,
+        (
+            admin,
+            7_u32,
+            String::from_val(e, &"name"),
+            String::from_val(e, &"symbol"),
+        ),
+    );
+    TokenClient::new(e, &token_contract)
+}
+
+struct Setup<'a> {
+    xlm: TokenClient<'a>,
+    pool_factory: PoolFactoryClient<'a>,
+    launchpad: LaunchpadClient<'a>,
+}
+
+fn setup<'a>(e: &Env) -> Setup<'a> {
+    let admin = Address::generate(e);
+    let xlm = create_token(e, &admin);
+
+    let token_factory = TokenFactoryClient::new(e, &e.register(TokenFactory, (&admin,)));
+    token_factory.update_pool_wasm_hash(&admin, &e.deployer().upload_contract_wasm(token_contract::WASM));
+    let pool_factory = PoolFactoryClient::new(e, &e.register(PoolFactory, (&admin,)));
+    pool_factory.update_pool_wasm_hash(&admin, &e.deployer().upload_contract_wasm(pool_contract::WASM));
+
+    let config = CurveConfig {
+        token_decimals: 7,
+        token_supply: SUPPLY,
+        virtual_xlm_reserve: VIRTUAL_XLM,
+        graduation_market_cap: GRADUATION_MARKET_CAP,
+    };
+    let launchpad = LaunchpadClient::new(
+        e,
+        &e.register(Launchpad, (&token_factory.address, &pool_factory.address, &xlm.address, config)),
+    );
+
+    Setup { xlm, pool_factory, launchpad }
+}
+
+fn launch<'a>(e: &Env, launchpad: &LaunchpadClient, creator: &Address) -> token::Client<'a> {
+    let token_addr = launchpad.launch(
+        creator,
+        &String::from_str(e, "Meme"),
+        &String::from_str(e, "MEME"),
+        &String::from_str(e, "ipfs://meme"),
+        &BytesN::from_array(e, &[1; 32]),
+    );
+    token::Client::new(e, &token_addr)
+}
+
+#[test]
+fn test_launch_mints_supply_into_curve() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let creator = Address::generate(&env);
+
+    let Setup { launchpad, .. } = setup(&env);
+    let token = launch(&env, &launchpad, &creator);
+
+    assert_eq!(token.balance(&launchpad.address), SUPPLY);
+    assert_eq!(token.symbol(), String::from_str(&env, "MEME"));
+    assert_eq!(launchpad.get_launch_count(), 1);
+    assert_eq!(launchpad.get_launches(&0, &10), vec![&env, token.address.clone()]);
+    assert_eq!(token_contract::Client::new(&env, &token.address).owner(), None);
+
+    let curve = launchpad.get_curve(&token.address);
+    assert_eq!(curve.creator, creator);
+    assert_eq!(curve.token_reserve, SUPPLY);
+    assert_eq!(curve.xlm_reserve, 0);
+
+    // The market cap starts at the virtual reserve
+    assert_eq!(launchpad.get_price(&token.address), VIRTUAL_XLM * 1_000_000_000_000_000_000 / SUPPLY);
+    assert_eq!(launchpad.get_market_cap(&token.address), VIRTUAL_XLM);
+}
+
+#[test]
+fn test_buy_and_sell_follow_curve() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let creator = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    let Setup { xlm, launchpad, .. } = setup(&env);
+    let token = launch(&env, &launchpad, &creator);
+    xlm.mint(&buyer, &10_000_000_000);
+
+    let price_before = launchpad.get_price(&token.address);
+    let quoted = launchpad.get_buy_quote(&token.address, &10_000_000_000);
+    let bought = launchpad.buy(&buyer, &token.address, &10_000_000_000, &quoted);
+    assert_eq!(bought, SUPPLY * 10_000_000_000 / (VIRTUAL_XLM + 10_000_000_000));
+    assert_eq!(bought, quoted);
+    assert_eq!(token.balance(&buyer), bought);
+    assert!(launchpad.get_price(&token.address) > price_before);
+
+    // Selling everything back returns the XLM, less rounding
+    let returned = launchpad.sell(&buyer, &token.address, &bought, &0);
+    assert!((10_000_000_000 - 1..=10_000_000_000).contains(&returned));
+    assert_eq!(xlm.balance(&buyer), returned);
+    assert_eq!(token.balance(&buyer), 0);
+    assert_eq!(launchpad.get_curve(&token.address).xlm_reserve, 10_000_000_000 - returned);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient output amount")]
+fn test_buy_below_min_out_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let creator = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    let Setup { xlm, launchpad, .. } = setup(&env);
+    let token = launch(&env, &launchpad, &creator);
+    xlm.mint(&buyer, &10_000_000_000);
+
+    let quoted = launchpad.get_buy_quote(&token.address, &10_000_000_000);
+    launchpad.buy(&buyer, &token.address, &10_000_000_000, &(quoted + 1));
+}
+
+#[test]
+fn test_graduation_seeds_pool_and_locks_liquidity() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let creator = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    let Setup { xlm, pool_factory, launchpad } = setup(&env);
+    let token = launch(&env, &launchpad, &creator);
+    xlm.mint(&buyer, &300_000_000_000);
+
+    // Raising 30k XLM doubles the XLM side, so the price is 4x and the market cap 120k XLM
+    let bought = launchpad.buy(&buyer, &token.address, &300_000_000_000, &0);
+    assert_eq!(bought, SUPPLY / 2);
+    assert!(launchpad.is_graduated(&token.address));
+
+    // The pool opens at the curve's final price: 30k XLM against 2.5M tokens
+    let pool_addr = pool_factory.get_pool(&token.address, &xlm.address).unwrap();
+    assert_eq!(launchpad.get_curve(&token.address).pool, Some(pool_addr.clone()));
+    let pool = LiquidityPoolClient::new(&env, &pool_addr);
+    assert_eq!(pool.get_reserves(), (SUPPLY / 4, 300_000_000_000));
+
+    // The remaining tokens are burned and the launchpad keeps the LP tokens
+    assert_eq!(token.balance(&launchpad.address), 0);
+    assert_eq!(xlm.balance(&launchpad.address), 0);
+    let locked = launchpad.get_locked_liquidity(&token.address);
+    assert!(locked > 0);
+    assert_eq!(pool.balance_of(&launchpad.address), locked);
+}
+
+#[test]
+fn test_graduation_uses_existing_pool() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let creator = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    let Setup { xlm, pool_factory, launchpad } = setup(&env);
+    let token = launch(&env, &launchpad, &creator);
+    xlm.mint(&buyer, &300_000_000_000);
+
+    // An empty pool created ahead of graduation, in reverse order, does not block it
+    let pool_addr = pool_factory.create_pool(
+        &xlm.address,
+        &token.address,
+        &Some(String::from_str(&env, "LPToken")),
+        &Some(String::from_str(&env, "LP")),
+    );
+
+    launchpad.buy(&buyer, &token.address, &300_000_000_000, &0);
+    assert_eq!(launchpad.get_curve(&token.address).pool, Some(pool_addr.clone()));
+    let pool = LiquidityPoolClient::new(&env, &pool_addr);
+    assert_eq!(pool.get_reserves(), (300_000_000_000, SUPPLY / 4));
+}
+
+#[test]
+fn test_graduation_into_seeded_pool_restores_curve_price() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let creator = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    let Setup { xlm, pool_factory, launchpad } = setup(&env);
+    let token = launch(&env, &launchpad, &creator);
+    xlm.mint(&buyer, &302_000_000_000);
+
+    // A buyer seeds the pool at their own price before the curve graduates
+    let bought = launchpad.buy(&buyer, &token.address, &1_000_000_000, &0);
+    let pool_addr = pool_factory.create_pool(&token.address, &xlm.address, &None, &None);
+    let pool = LiquidityPoolClient::new(&env, &pool_addr);
+    token.approve(&buyer, &pool_addr, &bought, &1000);
+    xlm.approve(&buyer, &pool_addr, &1_000_000, &1000);
+    pool.add_liquidity(&buyer, &bought, &1_000_000);
+
+    launchpad.buy(&buyer, &token.address, &300_000_000_000, &0);
+    assert!(launchpad.is_graduated(&token.address));
+    assert_eq!(launchpad.get_curve(&token.address).pool, Some(pool_addr.clone()));
+
+    // The pool is swapped back to the curve's final price after raising 30.1k XLM, within rounding
+    let (reserve_a, reserve_b) = pool.get_reserves();
+    let (reserve_token, reserve_xlm) = if pool.get_token_a() == token.address { (reserve_a, reserve_b) } else { (reserve_b, reserve_a) };
+    let total_xlm = VIRTUAL_XLM + 301_000_000_000;
+    let curve_price = total_xlm * total_xlm / (VIRTUAL_XLM * SUPPLY / 1_000_000_000_000);
+    let pool_price = reserve_xlm * 1_000_000_000_000 / reserve_token;
+    assert!((pool_price - curve_price).abs() <= curve_price / 1000);
+    assert_eq!(token.balance(&launchpad.address), 0);
+    assert_eq!(xlm.balance(&launchpad.address), 0);
+    assert!(launchpad.get_locked_liquidity(&token.address) > 0);
+}
+
+#[test]
+#[should_panic(expected = "Curve graduated")]
+fn test_buy_after_graduation_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let creator = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    let Setup { xlm, launchpad, .. } = setup(&env);
+    let token = launch(&env, &launchpad, &creator);
+    xlm.mint(&buyer, &310_000_000_000);
+
+    launchpad.buy(&buyer, &token.address, &300_000_000_000, &0);
+    launchpad.buy(&buyer, &token.address, &10_000_000_000, &0);
+}
+
+#[test]
+#[should_panic(expected = "Curve graduated")]
+fn test_sell_after_graduation_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let creator = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    let Setup { xlm, launchpad, .. } = setup(&env);
+    let token = launch(&env, &launchpad, &creator);
+    xlm.mint(&buyer, &300_000_000_000);
+
+    let bought = launchpad.buy(&buyer, &token.address, &300_000_000_000, &0);
+    launchpad.sell(&buyer, &token.address, &bought, &0);
+}
+
+#[test]
+#[should_panic(expected = "Invalid graduation market cap")]
+fn test_graduation_below_starting_market_cap_panics() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+
+    let config = CurveConfig {
+        token_decimals: 7,
+        token_supply: SUPPLY,
+        virtual_xlm_reserve: VIRTUAL_XLM,
+        graduation_market_cap: VIRTUAL_XLM,
+    };
+    env.register(Launchpad, (&admin, &admin, &admin, config));
+}
@@ -0,0 +1,370 @@
+#![no_std]
+
+use soroban_sdk::{
+    contract, contractclient, contractimpl, contracttype, symbol_short, Address, Env, String,
+    token::{self},
+};
+
+use poolseed::{get_or_create_pool, seed_pool};
+use weightedmath::{bps_to_fixed, mul_div_up, out_given_in, spot_price, ONE};
+
+// Constants
+const BPS_DENOMINATOR: u32 = 10000;
+const MIN_WEIGHT_BPS: u32 = 100; // 1%
+const MAX_WEIGHT_BPS: u32 = BPS_DENOMINATOR - MIN_WEIGHT_BPS;
+const MAX_FEE_BPS: u32 = 1000; // 10%
+
+// Overflow protection functions
+fn checked_add(a: i128, b: i128) -> i128 {
+    a.checked_add(b).expect("Overflow in addition")
+}
+
+fn checked_sub(a: i128, b: i128) -> i128 {
+    a.checked_sub(b).expect("Underflow in subtraction")
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PoolInfo {
+    pub project_token: Address,    // Token being launched
+    pub collateral_token: Address, // Token raised, e.g. XLM
+    pub project_balance: i128,
+    pub collateral_balance: i128,
+    pub start_weight_bps: u32,     // Project token weight at start_ledger
+    pub end_weight_bps: u32,       // Project token weight at end_ledger
+    pub start_ledger: u32,
+    pub end_ledger: u32,
+    pub fee_bps: u32,
+}
+
+/// Weight shift applied over the sale: the project token's weight moves linearly from
+/// `start_weight_bps` at `start_ledger` to `end_weight_bps` at `end_ledger`
+#[derive(Clone)]
+#[contracttype]
+pub struct SaleSchedule {
+    pub start_weight_bps: u32,
+    pub end_weight_bps: u32,
+    pub start_ledger: u32,
+    pub end_ledger: u32,
+}
+
+#[contractclient(name = "PoolClient")]
+pub trait PoolInterface {
+    fn get_token_a(e: Env) -> Address;
+    fn get_token_b(e: Env) -> Address;
+    fn remove_liquidity(e: Env, caller: Address, liquidity: i128) -> (i128, i128);
+    fn balance_of(e: Env, id: Address) -> i128;
+}
+
+#[contract]
+pub struct LiquidityBootstrappingPool;
+
+#[contractimpl]
+impl LiquidityBootstrappingPool {
+
+    fn check_nonnegative_amount(amount: i128) {
+        if amount < 0 {
+            panic!("negative amount is not allowed: {}", amount)
+        }
+    }
+
+    fn get_pool_info(e: &Env) -> PoolInfo {
+        e.storage().instance().get(&symbol_short!("pool")).unwrap()
+    }
+
+    fn set_pool_info(e: &Env, pool_info: &PoolInfo) {
+        e.storage().instance().set(&symbol_short!("pool"), pool_info);
+    }
+
+    fn require_owner(e: &Env, owner: &Address) {
+        let stored: Address = e.storage().instance().get(&DataKey::Owner).unwrap();
+        assert!(stored == *owner, "Unauthorized");
+        owner.require_auth();
+    }
+
+    fn require_open(e: &Env) {
+        assert!(!e.storage().instance().has(&DataKey::Closed), "Sale closed");
+    }
+
+    fn require_ended(e: &Env, pool_info: &PoolInfo) {
+        assert!(e.ledger().sequence() >= pool_info.end_ledger, "Sale not ended");
+    }
+
+    // Project and collateral weights at the current ledger, as 18-decimal fractions
+    fn current_weights(e: &Env, pool_info: &PoolInfo) -> (i128, i128) {
+        let ledger = e.ledger().sequence().clamp(pool_info.start_ledger, pool_info.end_ledger);
+        let start = bps_to_fixed(pool_info.start_weight_bps);
+        let end = bps_to_fixed(pool_info.end_weight_bps);
+        let elapsed = (ledger - pool_info.start_ledger) as i128;
+        let duration = (pool_info.end_ledger - pool_info.start_ledger) as i128;
+
+        let project_weight = start + (end - start) * elapsed / duration;
+        (project_weight, ONE - project_weight)
+    }
+
+    fn calculate_amount_out(e: &Env, pool_info: &PoolInfo, is_project_in: bool, amount_in: i128) -> i128 {
+        let (project_weight, collateral_weight) = Self::current_weights(e, pool_info);
+        let fee = mul_div_up(e, amount_in, pool_info.fee_bps as i128, BPS_DENOMINATOR as i128);
+        let amount_in_after_fee = checked_sub(amount_in, fee);
+
+        if is_project_in {
+            out_given_in(e, pool_info.project_balance, project_weight, pool_info.collateral_balance, collateral_weight, amount_in_after_fee)
+        } else {
+            out_given_in(e, pool_info.collateral_balance, collateral_weight, pool_info.project_balance, project_weight, amount_in_after_fee)
+        }
+    }
+
+    pub fn __constructor(
+        e: Env,
+        owner: Address,
+        project_token: Address,
+        collateral_token: Address,
+        schedule: SaleSchedule,
+        fee_bps: u32,
+    ) {
+        let SaleSchedule { start_weight_bps, end_weight_bps, start_ledger, end_ledger } = schedule;
+        assert!(project_token != collateral_token, "Tokens must be different");
+        assert!(
+            (MIN_WEIGHT_BPS..=MAX_WEIGHT_BPS).contains(&start_weight_bps)
+                && (MIN_WEIGHT_BPS..=MAX_WEIGHT_BPS).contains(&end_weight_bps),
+            "Weight out of range"
+        );
+        assert!(start_ledger < end_ledger, "Invalid ledger range");
+        assert!(fee_bps <= MAX_FEE_BPS, "Invalid fee");
+
+        let pool_info = PoolInfo {
+            project_token,
+            collateral_token,
+            project_balance: 0,
+            collateral_balance: 0,
+            start_weight_bps,
+            end_weight_bps,
+            start_ledger,
+            end_ledger,
+            fee_bps,
+        };
+
+        Self::set_pool_info(&e, &pool_info);
+        e.storage().instance().set(&DataKey::Owner, &owner);
+    }
+
+    /// Deposit the sale's starting balances (owner only, before the sale starts)
+    pub fn fund(e: Env, owner: Address, project_amount: i128, collateral_amount: i128) {
+        Self::require_owner(&e, &owner);
+        Self::check_nonnegative_amount(project_amount);
+        Self::check_nonnegative_amount(collateral_amount);
+
+        let mut pool_info = Self::get_pool_info(&e);
+        assert!(e.ledger().sequence() < pool_info.start_ledger, "Sale already started");
+
+        if project_amount > 0 {
+            token::Client::new(&e, &pool_info.project_token).transfer(&owner, &e.current_contract_address(), &project_amount);
+        }
+        if collateral_amount > 0 {
+            token::Client::new(&e, &pool_info.collateral_token).transfer(&owner, &e.current_contract_address(), &collateral_amount);
+        }
+
+        pool_info.project_balance = checked_add(pool_info.project_balance, project_amount);
+        pool_info.collateral_balance = checked_add(pool_info.collateral_balance, collateral_amount);
+        Self::set_pool_info(&e, &pool_info);
+    }
+
+    pub fn swap(e: Env, caller: Address, input_token: Address, amount_in: i128) -> i128 {
+        caller.require_auth();
+        Self::check_nonnegative_amount(amount_in);
+        Self::require_open(&e);
+
+        let mut pool_info = Self::get_pool_info(&e);
+        let ledger = e.ledger().sequence();
+        assert!(ledger >= pool_info.start_ledger && ledger < pool_info.end_ledger, "Sale not active");
+
+        assert!(amount_in > 0, "Amount in must be > 0");
+        assert!(
+            input_token == pool_info.project_token || input_token == pool_info.collateral_token,
+            "Invalid token address"
+        );
+
+        let is_project_in = input_token == pool_info.project_token;
+        let token_out = if is_project_in {
+            pool_info.collateral_token.clone()
+        } else {
+            pool_info.project_token.clone()
+        };
+
+        let amount_out = Self::calculate_amount_out(&e, &pool_info, is_project_in, amount_in);
+        assert!(amount_out > 0, "Insufficient output amount");
+
+        token::Client::new(&e, &input_token).transfer(&caller, &e.current_contract_address(), &amount_in);
+        token::Client::new(&e, &token_out).transfer(&e.current_contract_address(), &caller, &amount_out);
+
+        // The fee stays in the balances and goes to the owner at the end of the sale
+        if is_project_in {
+            pool_info.project_balance = checked_add(pool_info.project_balance, amount_in);
+            pool_info.collateral_balance = checked_sub(pool_info.collateral_balance, amount_out);
+        } else {
+            pool_info.collateral_balance = checked_add(pool_info.collateral_balance, amount_in);
+            pool_info.project_balance = checked_sub(pool_info.project_balance, amount_out);
+        }
+        Self::set_pool_info(&e, &pool_info);
+
+        amount_out
+    }
+
+    /// Withdraw the remaining balances once the sale has ended (owner only).
+    /// Returns (project_amount, collateral_amount)
+    pub fn withdraw(e: Env, owner: Address) -> (i128, i128) {
+        Self::require_owner(&e, &owner);
+        Self::require_open(&e);
+
+        let mut pool_info = Self::get_pool_info(&e);
+        Self::require_ended(&e, &pool_info);
+
+        let (project_amount, collateral_amount) = (pool_info.project_balance, pool_info.collateral_balance);
+        if project_amount > 0 {
+            token::Client::new(&e, &pool_info.project_token).transfer(&e.current_contract_address(), &owner, &project_amount);
+        }
+        if collateral_amount > 0 {
+            token::Client::new(&e, &pool_info.collateral_token).transfer(&e.current_contract_address(), &owner, &collateral_amount);
+        }
+
+        pool_info.project_balance = 0;
+        pool_info.collateral_balance = 0;
+        Self::set_pool_info(&e, &pool_info);
+        e.storage().instance().set(&DataKey::Closed, &true);
+
+        (project_amount, collateral_amount)
+    }
+
+    /// Move the remaining balances into the factory's constant product pool for the pair, creating it
+    /// if needed, once the sale has ended (owner only). The pool opens at the sale's final price; one
+    /// seeded at another price is swapped to it first. Whatever doesn't fit the ratio is returned to the owner.
+    /// The LP tokens stay in this contract and are redeemed with `withdraw_liquidity`.
+    pub fn migrate(
+        e: Env,
+        owner: Address,
+        factory: Address,
+        lp_token_name: String,
+        lp_token_symbol: String,
+    ) -> Address {
+        Self::require_owner(&e, &owner);
+        Self::require_open(&e);
+
+        let mut pool_info = Self::get_pool_info(&e);
+        Self::require_ended(&e, &pool_info);
+        assert!(pool_info.project_balance > 0 && pool_info.collateral_balance > 0, "Nothing to migrate");
+
+        let pool_addr = get_or_create_pool(
+            &e,
+            &factory,
+            &pool_info.project_token,
+            &pool_info.collateral_token,
+            lp_token_name,
+            lp_token_symbol,
+        )
+        .expect("Pool factory unavailable");
+
+        // Target collateral per project token, as a (collateral, project) ratio
+        let price = (Self::get_spot_price(e.clone()), ONE);
+        let (project_amount, collateral_amount) = seed_pool(
+            &e,
+            &pool_addr,
+            &pool_info.project_token,
+            pool_info.project_balance,
+            pool_info.collateral_balance,
+            price,
+        );
+
+        let project_left = checked_sub(pool_info.project_balance, project_amount);
+        let collateral_left = checked_sub(pool_info.collateral_balance, collateral_amount);
+        if project_left > 0 {
+            token::Client::new(&e, &pool_info.project_token).transfer(&e.current_contract_address(), &owner, &project_left);
+        }
+        if collateral_left > 0 {
+            token::Client::new(&e, &pool_info.collateral_token).transfer(&e.current_contract_address(), &owner, &collateral_left);
+        }
+
+        pool_info.project_balance = 0;
+        pool_info.collateral_balance = 0;
+        Self::set_pool_info(&e, &pool_info);
+        e.storage().instance().set(&DataKey::Closed, &true);
+        e.storage().instance().set(&DataKey::MigratedPool, &pool_addr);
+
+        pool_addr
+    }
+
+    /// Redeem migrated LP tokens from the pool and forward the tokens to the owner (owner only).
+    /// Returns (amount_a, amount_b) in the pool's token order
+    pub fn withdraw_liquidity(e: Env, owner: Address, liquidity: i128) -> (i128, i128) {
+        Self::require_owner(&e, &owner);
+        Self::check_nonnegative_amount(liquidity);
+
+        let pool_addr: Address = e.storage().instance().get(&DataKey::MigratedPool).expect("Not migrated");
+        let pool = PoolClient::new(&e, &pool_addr);
+        let (amount_a, amount_b) = pool.remove_liquidity(&e.current_contract_address(), &liquidity);
+
+        if amount_a > 0 {
+            token::Client::new(&e, &pool.get_token_a()).transfer(&e.current_contract_address(), &owner, &amount_a);
+        }
+        if amount_b > 0 {
+            token::Client::new(&e, &pool.get_token_b()).transfer(&e.current_contract_address(), &owner, &amount_b);
+        }
+
+        (amount_a, amount_b)
+    }
+
+    // View functions
+    pub fn get_owner(e: Env) -> Address {
+        e.storage().instance().get(&DataKey::Owner).unwrap()
+    }
+
+    /// Sale schedule as (start_ledger, end_ledger)
+    pub fn get_schedule(e: Env) -> (u32, u32) {
+        let pool_info = Self::get_pool_info(&e);
+        (pool_info.start_ledger, pool_info.end_ledger)
+    }
+
+    pub fn get_balances(e: Env) -> (i128, i128) {
+        let pool_info = Self::get_pool_info(&e);
+        (pool_info.project_balance, pool_info.collateral_balance)
+    }
+
+    /// Current (project, collateral) weights, scaled by 1e18
+    pub fn get_weights(e: Env) -> (i128, i128) {
+        Self::current_weights(&e, &Self::get_pool_info(&e))
+    }
+
+    /// Price of one project token in collateral tokens without fees, scaled by 1e18
+    pub fn get_spot_price(e: Env) -> i128 {
+        let pool_info = Self::get_pool_info(&e);
+        let (project_weight, collateral_weight) = Self::current_weights(&e, &pool_info);
+        spot_price(&e, pool_info.collateral_balance, collateral_weight, pool_info.project_balance, project_weight)
+    }
+
+    pub fn get_amount_out(e: Env, input_token: Address, amount_in: i128) -> i128 {
+        Self::check_nonnegative_amount(amount_in);
+        let pool_info = Self::get_pool_info(&e);
+        assert!(
+            input_token == pool_info.project_token || input_token == pool_info.collateral_token,
+            "Invalid token address"
+        );
+        Self::calculate_amount_out(&e, &pool_info, input_token == pool_info.project_token, amount_in)
+    }
+
+    pub fn is_closed(e: Env) -> bool {
+        e.storage().instance().has(&DataKey::Closed)
+    }
+
+    pub fn get_migrated_pool(e: Env) -> Option<Address> {
+        e.storage().instance().get(&DataKey::MigratedPool)
+    }
+}
+
+#[derive(Clone)]
+#[contracttype]
+enum DataKey {
+    Owner,
+    Closed,       // Set once the balances have been withdrawn or migrated
+    MigratedPool, // Constant product pool holding the migrated liquidity
+}
+
+mod test;
@@ -0,0 +1,303 @@
+#![cfg(test)]
+extern crate std;
+use soroban_sdk::{
+    Env, String, Address, FromVal,
+    testutils::{Address as _, Ledger},
+};
+use crate::LiquidityBootstrappingPool;
+use crate::LiquidityBootstrappingPoolClient;
+use crate::SaleSchedule;
+use pool::LiquidityPoolClient;
+use poolfactory::PoolFactory;
+use poolfactory::PoolFactoryClient;
+use ::token::Token;
+use ::token::TokenClient;
+
+mod contract {
+    soroban_sdk::contractimport!(file = "../../target/wasm32v1-none/release/pool.wasm");
+}
+
+const ONE: i128 = 1_000_000_000_000_000_000;
+const START_LEDGER: u32 = 100;
+const END_LEDGER: u32 = 1100;
+
+// Helper function to create a test token
+fn create_token<'a>(e: &Env, admin: &Address) -> TokenClient<'a> {
+    let token_contract = e.register(
+        Token,
+        (
+            admin,
+            7_u32,
+            String::from_val(e, &"name"),
+            String::from_val(e, &"symbol"),
+        ),
+    );
+    TokenClient::new(e, &token_contract)
+}
+
+fn set_ledger(e: &Env, sequence: u32) {
+    e.ledger().set_sequence_number(sequence);
+}
+
+// 96/4 -> 50/50 sale funded with 10000 project tokens against 100 collateral tokens
+fn setup<'a>(e: &Env, owner: &Address) -> (TokenClient<'a>, TokenClient<'a>, LiquidityBootstrappingPoolClient<'a>) {
+    let project = create_token(e, owner);
+    let collateral = create_token(e, owner);
+    project.mint(owner, &100_000_000_000);
+    collateral.mint(owner, &1_000_000_000);
+
+    let contract_id = e.register(
+        LiquidityBootstrappingPool,
+        (
+            owner,
+            &project.address,
+            &collateral.address,
+            SaleSchedule {
+                start_weight_bps: 9600,
+                end_weight_bps: 5000,
+                start_ledger: START_LEDGER,
+                end_ledger: END_LEDGER,
+            },
+            30_u32,
+        ),
+    );
+    let lbp = LiquidityBootstrappingPoolClient::new(e, &contract_id);
+    lbp.fund(owner, &100_000_000_000, &1_000_000_000);
+
+    (project, collateral, lbp)
+}
+
+fn assert_close(actual: i128, expected: f64, tolerance: f64) {
+    let difference = (actual as f64 - expected).abs() / expected;
+    assert!(difference < tolerance, "{} vs {}", actual, expected);
+}
+
+#[test]
+fn test_weights_shift_linearly() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+
+    let (_, _, lbp) = setup(&env, &owner);
+    assert_eq!(lbp.get_weights(), (ONE * 96 / 100, ONE * 4 / 100));
+
+    set_ledger(&env, START_LEDGER + 250);
+    assert_eq!(lbp.get_weights(), (ONE * 845 / 1000, ONE * 155 / 1000));
+
+    set_ledger(&env, END_LEDGER + 500);
+    assert_eq!(lbp.get_weights(), (ONE / 2, ONE / 2));
+}
+
+#[test]
+fn test_price_falls_without_trades() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+
+    let (_, _, lbp) = setup(&env, &owner);
+
+    // (collateral / 0.04) / (project / 0.96) at the start, collateral / project at the end
+    set_ledger(&env, START_LEDGER);
+    let start_price = lbp.get_spot_price();
+    assert_close(start_price, 0.24 * ONE as f64, 1e-9);
+
+    set_ledger(&env, START_LEDGER + 500);
+    let mid_price = lbp.get_spot_price();
+    assert!(mid_price < start_price);
+
+    set_ledger(&env, END_LEDGER);
+    assert_eq!(lbp.get_spot_price(), ONE / 100);
+}
+
+#[test]
+fn test_swap_during_sale() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    let (project, collateral, lbp) = setup(&env, &owner);
+    collateral.mint(&buyer, &100_000_000);
+
+    set_ledger(&env, START_LEDGER + 500);
+    let amount_in = 100_000_000;
+    let quoted = lbp.get_amount_out(&collateral.address, &amount_in);
+    let amount_out = lbp.swap(&buyer, &collateral.address, &amount_in);
+    assert_eq!(amount_out, quoted);
+
+    // Halfway through, the weights are 73/27
+    let amount_in_after_fee = amount_in as f64 * 0.997;
+    let expected = 100_000_000_000_f64 * (1.0 - (1_000_000_000_f64 / (1_000_000_000_f64 + amount_in_after_fee)).powf(27.0 / 73.0));
+    assert_close(amount_out, expected, 1e-9);
+
+    assert_eq!(project.balance(&buyer), amount_out);
+    assert_eq!(lbp.get_balances(), (100_000_000_000 - amount_out, 1_000_000_000 + amount_in));
+
+    // Selling back returns less because of the fee on both legs
+    let returned = lbp.swap(&buyer, &project.address, &amount_out);
+    assert!(returned < amount_in);
+    assert_eq!(collateral.balance(&buyer), returned);
+}
+
+#[test]
+#[should_panic(expected = "Sale not active")]
+fn test_swap_before_start_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+
+    let (_, collateral, lbp) = setup(&env, &owner);
+    collateral.mint(&owner, &1_000);
+    lbp.swap(&owner, &collateral.address, &1_000);
+}
+
+#[test]
+#[should_panic(expected = "Sale not active")]
+fn test_swap_after_end_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+
+    let (_, collateral, lbp) = setup(&env, &owner);
+    collateral.mint(&owner, &1_000);
+    set_ledger(&env, END_LEDGER);
+    lbp.swap(&owner, &collateral.address, &1_000);
+}
+
+#[test]
+#[should_panic(expected = "Sale already started")]
+fn test_fund_after_start_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+
+    let (_, _, lbp) = setup(&env, &owner);
+    set_ledger(&env, START_LEDGER);
+    lbp.fund(&owner, &1_000, &0);
+}
+
+#[test]
+fn test_withdraw_after_end() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    let (project, collateral, lbp) = setup(&env, &owner);
+    collateral.mint(&buyer, &100_000_000);
+
+    set_ledger(&env, START_LEDGER + 100);
+    let bought = lbp.swap(&buyer, &collateral.address, &100_000_000);
+
+    set_ledger(&env, END_LEDGER);
+    let (project_amount, collateral_amount) = lbp.withdraw(&owner);
+    assert_eq!(project_amount, 100_000_000_000 - bought);
+    assert_eq!(collateral_amount, 1_100_000_000);
+    assert_eq!(project.balance(&owner), 100_000_000_000 - bought);
+    assert_eq!(collateral.balance(&owner), 1_100_000_000);
+    assert_eq!(lbp.get_balances(), (0, 0));
+    assert!(lbp.is_closed());
+}
+
+#[test]
+#[should_panic(expected = "Sale not ended")]
+fn test_withdraw_before_end_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+
+    let (_, _, lbp) = setup(&env, &owner);
+    set_ledger(&env, END_LEDGER - 1);
+    lbp.withdraw(&owner);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized")]
+fn test_withdraw_by_non_owner_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let other = Address::generate(&env);
+
+    let (_, _, lbp) = setup(&env, &owner);
+    set_ledger(&env, END_LEDGER);
+    lbp.withdraw(&other);
+}
+
+#[test]
+fn test_migrate_to_factory_pool() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+
+    let (project, collateral, lbp) = setup(&env, &owner);
+    let factory = PoolFactoryClient::new(&env, &env.register(PoolFactory, (&admin,)));
+    factory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+
+    set_ledger(&env, END_LEDGER);
+    let pool_addr = lbp.migrate(
+        &owner,
+        &factory.address,
+        &String::from_val(&env, &"LPToken"),
+        &String::from_val(&env, &"LP"),
+    );
+    assert_eq!(factory.get_pool(&project.address, &collateral.address), Some(pool_addr.clone()));
+    assert_eq!(lbp.get_migrated_pool(), Some(pool_addr.clone()));
+    assert!(lbp.is_closed());
+
+    // The pool opens at the sale's final price and the LBP holds the LP tokens
+    let pool = LiquidityPoolClient::new(&env, &pool_addr);
+    assert_eq!(pool.get_reserves(), (100_000_000_000, 1_000_000_000));
+    let liquidity = pool.balance_of(&lbp.address);
+    assert!(liquidity > 0);
+    assert_eq!(project.balance(&owner), 0);
+
+    let (amount_a, amount_b) = lbp.withdraw_liquidity(&owner, &liquidity);
+    assert_eq!((amount_a, amount_b), (100_000_000_000, 1_000_000_000));
+    assert_eq!(project.balance(&owner), 100_000_000_000);
+    assert_eq!(collateral.balance(&owner), 1_000_000_000);
+}
+
+#[test]
+fn test_migrate_into_seeded_pool_restores_sale_price() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let provider = Address::generate(&env);
+
+    let (project, collateral, lbp) = setup(&env, &owner);
+    let factory = PoolFactoryClient::new(&env, &env.register(PoolFactory, (&admin,)));
+    factory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+
+    // Someone seeds the pool, created in reverse order, at their own price of 0.02
+    let pool_addr = factory.create_pool(
+        &collateral.address,
+        &project.address,
+        &Some(String::from_val(&env, &"LPToken")),
+        &Some(String::from_val(&env, &"LP")),
+    );
+    let pool = LiquidityPoolClient::new(&env, &pool_addr);
+    project.mint(&provider, &1_000_000_000);
+    collateral.mint(&provider, &20_000_000);
+    collateral.approve(&provider, &pool_addr, &20_000_000, &1000);
+    project.approve(&provider, &pool_addr, &1_000_000_000, &1000);
+    pool.add_liquidity(&provider, &20_000_000, &1_000_000_000);
+
+    set_ledger(&env, END_LEDGER);
+    let migrated_pool = lbp.migrate(
+        &owner,
+        &factory.address,
+        &String::from_val(&env, &"LPToken"),
+        &String::from_val(&env, &"LP"),
+    );
+    assert_eq!(migrated_pool, pool_addr);
+
+    // The pool is swapped back to the sale's final price of 0.01, within rounding
+    let (reserve_collateral, reserve_project) = pool.get_reserves();
+    assert!((reserve_collateral * 1_000_000 / reserve_project - 10_000).abs() <= 10);
+    assert!(pool.balance_of(&lbp.address) > 0);
+    assert_eq!(project.balance(&lbp.address), 0);
+    assert_eq!(collateral.balance(&lbp.address), 0);
+}
@@ -0,0 +1,283 @@
+#![no_std]
+
+use soroban_sdk::{
+    contract, contractclient, contractimpl, contracttype, symbol_short, Address, Env, Vec,
+    token::{self},
+};
+use storagettl::{read_persistent, write_persistent};
+
+// Fees per locked LP token are tracked with 1e18 precision, like the pool does
+const FEE_SCALE: i128 = 1_000_000_000_000_000_000;
+
+// Overflow protection functions
+fn checked_add(a: i128, b: i128) -> i128 {
+    a.checked_add(b).expect("Overflow in addition")
+}
+
+fn checked_sub(a: i128, b: i128) -> i128 {
+    a.checked_sub(b).expect("Underflow in subtraction")
+}
+
+fn checked_mul(a: i128, b: i128) -> i128 {
+    a.checked_mul(b).expect("Overflow in multiplication")
+}
+
+fn checked_div(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        panic!("Division by zero");
+    }
+    a.checked_div(b).expect("Overflow in division")
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Lock {
+    pub owner: Address,
+    pub pool: Address,
+    pub amount: i128,       // LP tokens held for the owner
+    pub unlock_ledger: u32, // First ledger at which the LP tokens can be withdrawn
+    pub withdrawn: bool,
+    pub fees_a_checkpoint: i128, // Pool fees per locked LP token already paid out to this lock
+    pub fees_b_checkpoint: i128,
+}
+
+// Trading fees claimed from a pool on behalf of all of its locks
+#[derive(Clone)]
+#[contracttype]
+struct PoolFees {
+    fees_a_per_lp: i128,
+    fees_b_per_lp: i128,
+}
+
+#[contractclient(name = "PoolClient")]
+pub trait PoolInterface {
+    fn get_token_a(e: Env) -> Address;
+    fn get_token_b(e: Env) -> Address;
+    fn claim_fees(e: Env, caller: Address) -> i128;
+}
+
+#[contract]
+pub struct LpLocker;
+
+#[contractimpl]
+impl LpLocker {
+
+    fn check_nonnegative_amount(amount: i128) {
+        if amount < 0 {
+            panic!("negative amount is not allowed: {}", amount)
+        }
+    }
+
+    fn load_lock(e: &Env, lock_id: u64) -> Lock {
+        read_persistent(e, &DataKey::Lock(lock_id)).expect("Lock not found")
+    }
+
+    fn save_lock(e: &Env, lock_id: u64, lock: &Lock) {
+        write_persistent(e, &DataKey::Lock(lock_id), lock);
+    }
+
+    fn load_owned_lock(e: &Env, owner: &Address, lock_id: u64) -> Lock {
+        let lock = Self::load_lock(e, lock_id);
+        assert!(lock.owner == *owner, "Unauthorized");
+        owner.require_auth();
+        assert!(!lock.withdrawn, "Lock already withdrawn");
+        lock
+    }
+
+    // Append a lock ID to an index kept as a count plus one entry per position
+    fn push_lock_id(e: &Env, count_key: &DataKey, at_key: impl Fn(u32) -> DataKey, lock_id: u64) {
+        let count: u32 = read_persistent(e, count_key).unwrap_or(0);
+        write_persistent(e, &at_key(count), &lock_id);
+        write_persistent(e, count_key, &(count + 1));
+    }
+
+    // Up to `limit` lock IDs of an index, starting at `offset`
+    fn read_lock_ids(e: &Env, count_key: &DataKey, at_key: impl Fn(u32) -> DataKey, offset: u32, limit: u32) -> Vec<u64> {
+        let count: u32 = read_persistent(e, count_key).unwrap_or(0);
+        let end = offset.saturating_add(limit).min(count);
+        let mut lock_ids = Vec::new(e);
+        for i in offset..end {
+            lock_ids.push_back(read_persistent(e, &at_key(i)).unwrap());
+        }
+        lock_ids
+    }
+
+    fn total_locked(e: &Env, pool: &Address) -> i128 {
+        read_persistent(e, &DataKey::TotalLocked(pool.clone())).unwrap_or(0)
+    }
+
+    // Claim the pool fees owed to the locker and spread them over the LP tokens locked in that
+    // pool. Must run before the locked total of the pool changes
+    fn harvest(e: &Env, pool: &Address) -> PoolFees {
+        let key = DataKey::PoolFees(pool.clone());
+        let mut fees: PoolFees = read_persistent(e, &key).unwrap_or(PoolFees {
+            fees_a_per_lp: 0,
+            fees_b_per_lp: 0,
+        });
+        let total_locked = Self::total_locked(e, pool);
+        if total_locked == 0 {
+            return fees;
+        }
+
+        let pool_client = PoolClient::new(e, pool);
+        let token_a = token::Client::new(e, &pool_client.get_token_a());
+        let token_b = token::Client::new(e, &pool_client.get_token_b());
+        let current = e.current_contract_address();
+        let balance_a = token_a.balance(&current);
+        let balance_b = token_b.balance(&current);
+
+        // Only constant product pools pay fees out separately; the others compound them
+        // into the reserves and have no `claim_fees`, so a failed claim just means no fees
+        let _ = pool_client.try_claim_fees(&current);
+
+        let claimed_a = checked_sub(token_a.balance(&current), balance_a);
+        let claimed_b = checked_sub(token_b.balance(&current), balance_b);
+        fees.fees_a_per_lp = checked_add(fees.fees_a_per_lp, checked_div(checked_mul(claimed_a, FEE_SCALE), total_locked));
+        fees.fees_b_per_lp = checked_add(fees.fees_b_per_lp, checked_div(checked_mul(claimed_b, FEE_SCALE), total_locked));
+        write_persistent(e, &key, &fees);
+        fees
+    }
+
+    // Pay the lock owner their share of the harvested fees and move the lock's checkpoints up
+    fn pay_fees(e: &Env, lock: &mut Lock, fees: &PoolFees) -> (i128, i128) {
+        let owed_a = checked_div(checked_mul(lock.amount, checked_sub(fees.fees_a_per_lp, lock.fees_a_checkpoint)), FEE_SCALE);
+        let owed_b = checked_div(checked_mul(lock.amount, checked_sub(fees.fees_b_per_lp, lock.fees_b_checkpoint)), FEE_SCALE);
+        lock.fees_a_checkpoint = fees.fees_a_per_lp;
+        lock.fees_b_checkpoint = fees.fees_b_per_lp;
+
+        let pool_client = PoolClient::new(e, &lock.pool);
+        if owed_a > 0 {
+            token::Client::new(e, &pool_client.get_token_a()).transfer(&e.current_contract_address(), &lock.owner, &owed_a);
+        }
+        if owed_b > 0 {
+            token::Client::new(e, &pool_client.get_token_b()).transfer(&e.current_contract_address(), &lock.owner, &owed_b);
+        }
+        (owed_a, owed_b)
+    }
+
+    /// Lock `amount` LP tokens of `pool` from the owner until `unlock_ledger`. Returns the lock ID
+    pub fn lock(e: Env, owner: Address, pool: Address, amount: i128, unlock_ledger: u32) -> u64 {
+        owner.require_auth();
+        Self::check_nonnegative_amount(amount);
+        assert!(amount > 0, "Amount must be > 0");
+        assert!(unlock_ledger > e.ledger().sequence(), "Unlock ledger must be in the future");
+
+        // Fees the owner earned before locking stay theirs instead of being shared with other locks
+        let _ = PoolClient::new(&e, &pool).try_claim_fees(&owner);
+        let fees = Self::harvest(&e, &pool);
+
+        token::Client::new(&e, &pool).transfer(&owner, &e.current_contract_address(), &amount);
+
+        let lock_id: u64 = e.storage().instance().get(&DataKey::NextLockId).unwrap_or(0);
+        e.storage().instance().set(&DataKey::NextLockId, &(lock_id + 1));
+
+        let lock = Lock {
+            owner: owner.clone(),
+            pool: pool.clone(),
+            amount,
+            unlock_ledger,
+            withdrawn: false,
+            fees_a_checkpoint: fees.fees_a_per_lp,
+            fees_b_checkpoint: fees.fees_b_per_lp,
+        };
+        Self::save_lock(&e, lock_id, &lock);
+        Self::push_lock_id(&e, &DataKey::PoolLockCount(pool.clone()), |i| DataKey::PoolLockAt(pool.clone(), i), lock_id);
+        Self::push_lock_id(&e, &DataKey::OwnerLockCount(owner.clone()), |i| DataKey::OwnerLockAt(owner.clone(), i), lock_id);
+
+        let total_locked = checked_add(Self::total_locked(&e, &pool), amount);
+        write_persistent(&e, &DataKey::TotalLocked(pool.clone()), &total_locked);
+
+        e.events().publish((symbol_short!("locked"), lock_id), (pool, owner, amount, unlock_ledger));
+        lock_id
+    }
+
+    /// Move the unlock ledger of a lock later. A lock can never be shortened
+    pub fn extend_lock(e: Env, owner: Address, lock_id: u64, unlock_ledger: u32) {
+        let mut lock = Self::load_owned_lock(&e, &owner, lock_id);
+        assert!(unlock_ledger > lock.unlock_ledger, "Cannot shorten lock");
+
+        lock.unlock_ledger = unlock_ledger;
+        Self::save_lock(&e, lock_id, &lock);
+
+        e.events().publish((symbol_short!("extended"), lock_id), unlock_ledger);
+    }
+
+    /// Collect the trading fees earned by the locked LP tokens. Returns the (token_a, token_b) amounts paid
+    pub fn claim_fees(e: Env, owner: Address, lock_id: u64) -> (i128, i128) {
+        let mut lock = Self::load_owned_lock(&e, &owner, lock_id);
+        let fees = Self::harvest(&e, &lock.pool);
+        let paid = Self::pay_fees(&e, &mut lock, &fees);
+        Self::save_lock(&e, lock_id, &lock);
+
+        e.events().publish((symbol_short!("fees"), lock_id), paid);
+        paid
+    }
+
+    /// Return the LP tokens and any unclaimed fees to the owner once the lock has expired.
+    /// Returns the LP amount withdrawn
+    pub fn withdraw(e: Env, owner: Address, lock_id: u64) -> i128 {
+        let mut lock = Self::load_owned_lock(&e, &owner, lock_id);
+        assert!(e.ledger().sequence() >= lock.unlock_ledger, "Lock not expired");
+
+        let fees = Self::harvest(&e, &lock.pool);
+        Self::pay_fees(&e, &mut lock, &fees);
+        token::Client::new(&e, &lock.pool).transfer(&e.current_contract_address(), &owner, &lock.amount);
+
+        let total_locked = checked_sub(Self::total_locked(&e, &lock.pool), lock.amount);
+        write_persistent(&e, &DataKey::TotalLocked(lock.pool.clone()), &total_locked);
+
+        let amount = lock.amount;
+        lock.withdrawn = true;
+        Self::save_lock(&e, lock_id, &lock);
+
+        e.events().publish((symbol_short!("unlocked"), lock_id), (owner, amount));
+        amount
+    }
+
+    // View functions
+    pub fn get_lock(e: Env, lock_id: u64) -> Lock {
+        Self::load_lock(&e, lock_id)
+    }
+
+    /// Up to `limit` of the locks ever made on a pool, withdrawn ones included, starting at `offset`
+    pub fn get_pool_locks(e: Env, pool: Address, offset: u32, limit: u32) -> Vec<u64> {
+        Self::read_lock_ids(&e, &DataKey::PoolLockCount(pool.clone()), |i| DataKey::PoolLockAt(pool.clone(), i), offset, limit)
+    }
+
+    pub fn get_pool_lock_count(e: Env, pool: Address) -> u32 {
+        read_persistent(&e, &DataKey::PoolLockCount(pool)).unwrap_or(0)
+    }
+
+    /// Up to `limit` of the locks ever made by an owner, starting at `offset`
+    pub fn get_owner_locks(e: Env, owner: Address, offset: u32, limit: u32) -> Vec<u64> {
+        Self::read_lock_ids(&e, &DataKey::OwnerLockCount(owner.clone()), |i| DataKey::OwnerLockAt(owner.clone(), i), offset, limit)
+    }
+
+    pub fn get_owner_lock_count(e: Env, owner: Address) -> u32 {
+        read_persistent(&e, &DataKey::OwnerLockCount(owner)).unwrap_or(0)
+    }
+
+    /// LP tokens of a pool currently held by the locker
+    pub fn get_total_locked(e: Env, pool: Address) -> i128 {
+        Self::total_locked(&e, &pool)
+    }
+
+    pub fn is_unlocked(e: Env, lock_id: u64) -> bool {
+        e.ledger().sequence() >= Self::load_lock(&e, lock_id).unlock_ledger
+    }
+}
+
+#[derive(Clone)]
+#[contracttype]
+enum DataKey {
+    NextLockId,
+    Lock(u64),
+    PoolLockCount(Address), // Number of locks ever made on a pool
+    PoolLockAt(Address, u32), // Per-pool index to lock ID
+    OwnerLockCount(Address), // Number of locks ever made by an owner
+    OwnerLockAt(Address, u32), // Per-owner index to lock ID
+    TotalLocked(Address),
+    PoolFees(Address),
+}
+
+mod test;
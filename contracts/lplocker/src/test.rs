@@ -0,0 +1,287 @@
+#![cfg(test)]
+extern crate std;
+use soroban_sdk::{
+    Env, String, Address, FromVal, IntoVal, symbol_short, vec,
+    testutils::{Address as _, Events, Ledger},
+};
+use crate::{LpLocker, LpLockerClient};
+use pool::{LiquidityPool, LiquidityPoolClient};
+use stablepool::{StableSwapPool, StableSwapPoolClient};
+use ::token::Token;
+use ::token::TokenClient;
+
+// Helper function to create a test token
+fn create_token<'a>(e: &Env, admin: &Address) -> TokenClient<'a> {
+    let token_contract = e.register(
+        Token,
+        (
+            admin,
+            7_u32,
+            String::from_val(e, &"name"),
+            String::from_val(e, &"symbol"),
+        ),
+    );
+    TokenClient::new(e, &token_contract)
+}
+
+struct Setup<'a> {
+    token_a: TokenClient<'a>,
+    token_b: TokenClient<'a>,
+    pool: LiquidityPoolClient<'a>,
+    locker: LpLockerClient<'a>,
+}
+
+fn setup<'a>(e: &Env, admin: &Address) -> Setup<'a> {
+    let token_a = create_token(e, admin);
+    let token_b = create_token(e, admin);
+    let pool_id = e.register(
+        LiquidityPool,
+        (
+            &token_a.address,
+            &token_b.address,
+            String::from_val(e, &"LPToken"),
+            String::from_val(e, &"LP"),
+            Address::generate(e),
+        ),
+    );
+    let pool = LiquidityPoolClient::new(e, &pool_id);
+    let locker = LpLockerClient::new(e, &e.register(LpLocker, ()));
+    Setup { token_a, token_b, pool, locker }
+}
+
+fn provide_liquidity(s: &Setup, provider: &Address, amount: i128) -> i128 {
+    s.token_a.mint(provider, &amount);
+    s.token_b.mint(provider, &amount);
+    s.token_a.approve(provider, &s.pool.address, &amount, &1000);
+    s.token_b.approve(provider, &s.pool.address, &amount, &1000);
+    s.pool.add_liquidity(provider, &amount, &amount)
+}
+
+fn generate_fees(s: &Setup, trader: &Address) {
+    s.token_a.mint(trader, &1_000_000_000);
+    s.token_a.approve(trader, &s.pool.address, &1_000_000_000, &1000);
+    s.pool.swap(trader, &s.token_a.address, &1_000_000_000);
+}
+
+#[test]
+fn test_lock_and_views() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+
+    let s = setup(&env, &admin);
+    let liquidity = provide_liquidity(&s, &creator, 20_000_000_000);
+    let lock_id = s.locker.lock(&creator, &s.pool.address, &liquidity, &1000);
+
+    let (contract, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(contract, s.locker.address);
+    assert_eq!(topics, (symbol_short!("locked"), lock_id).into_val(&env));
+
+    assert_eq!(s.pool.balance(&creator), 0);
+    assert_eq!(s.pool.balance(&s.locker.address), liquidity);
+    assert_eq!(s.locker.get_total_locked(&s.pool.address), liquidity);
+    assert_eq!(s.locker.get_pool_locks(&s.pool.address, &0, &10), vec![&env, lock_id]);
+    assert_eq!(s.locker.get_owner_locks(&creator, &0, &10), vec![&env, lock_id]);
+
+    let lock = s.locker.get_lock(&lock_id);
+    assert_eq!(lock.owner, creator);
+    assert_eq!(lock.amount, liquidity);
+    assert_eq!(lock.unlock_ledger, 1000);
+    assert!(!s.locker.is_unlocked(&lock_id));
+}
+
+#[test]
+fn test_withdraw_after_unlock() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+
+    let s = setup(&env, &admin);
+    let liquidity = provide_liquidity(&s, &creator, 20_000_000_000);
+    let lock_id = s.locker.lock(&creator, &s.pool.address, &liquidity, &1000);
+
+    env.ledger().set_sequence_number(1000);
+    assert_eq!(s.locker.withdraw(&creator, &lock_id), liquidity);
+    assert_eq!(s.pool.balance(&creator), liquidity);
+    assert_eq!(s.locker.get_total_locked(&s.pool.address), 0);
+    assert!(s.locker.get_lock(&lock_id).withdrawn);
+}
+
+#[test]
+#[should_panic(expected = "Lock not expired")]
+fn test_withdraw_before_unlock_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+
+    let s = setup(&env, &admin);
+    let liquidity = provide_liquidity(&s, &creator, 20_000_000_000);
+    let lock_id = s.locker.lock(&creator, &s.pool.address, &liquidity, &1000);
+
+    env.ledger().set_sequence_number(999);
+    s.locker.withdraw(&creator, &lock_id);
+}
+
+#[test]
+fn test_extend_lock() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+
+    let s = setup(&env, &admin);
+    let liquidity = provide_liquidity(&s, &creator, 20_000_000_000);
+    let lock_id = s.locker.lock(&creator, &s.pool.address, &liquidity, &1000);
+
+    s.locker.extend_lock(&creator, &lock_id, &2000);
+    assert_eq!(s.locker.get_lock(&lock_id).unlock_ledger, 2000);
+    env.ledger().set_sequence_number(1500);
+    assert!(!s.locker.is_unlocked(&lock_id));
+}
+
+#[test]
+#[should_panic(expected = "Cannot shorten lock")]
+fn test_shorten_lock_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+
+    let s = setup(&env, &admin);
+    let liquidity = provide_liquidity(&s, &creator, 20_000_000_000);
+    let lock_id = s.locker.lock(&creator, &s.pool.address, &liquidity, &1000);
+    s.locker.extend_lock(&creator, &lock_id, &999);
+}
+
+#[test]
+fn test_claim_fees_while_locked() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let trader = Address::generate(&env);
+
+    let s = setup(&env, &admin);
+    let liquidity = provide_liquidity(&s, &creator, 20_000_000_000);
+    let lock_id = s.locker.lock(&creator, &s.pool.address, &liquidity, &1000);
+
+    generate_fees(&s, &trader);
+    let (reserve_a, reserve_b) = s.pool.get_reserves();
+    let (paid_a, paid_b) = s.locker.claim_fees(&creator, &lock_id);
+
+    // The lock holds every LP token, so it earns the whole 0.3% fee of the 1_000_000_000 swap,
+    // which was held outside the reserves
+    assert_eq!((paid_a, paid_b), (3_000_000, 0));
+    assert_eq!(s.pool.get_reserves(), (reserve_a, reserve_b));
+    assert_eq!(s.token_a.balance(&s.pool.address), reserve_a);
+    assert_eq!(s.token_b.balance(&s.pool.address), reserve_b);
+    assert_eq!(s.token_a.balance(&creator), paid_a);
+    assert_eq!(s.token_b.balance(&creator), paid_b);
+
+    // Claimed fees are not paid twice
+    assert_eq!(s.locker.claim_fees(&creator, &lock_id), (0, 0));
+    assert_eq!(s.pool.balance(&s.locker.address), liquidity);
+}
+
+#[test]
+fn test_fees_split_between_locks() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let owner1 = Address::generate(&env);
+    let owner2 = Address::generate(&env);
+    let trader = Address::generate(&env);
+
+    let s = setup(&env, &admin);
+    let liquidity1 = provide_liquidity(&s, &owner1, 20_000_000_000);
+    let liquidity2 = provide_liquidity(&s, &owner2, 20_000_000_000);
+    let lock1 = s.locker.lock(&owner1, &s.pool.address, &liquidity1, &1000);
+    let lock2 = s.locker.lock(&owner2, &s.pool.address, &liquidity2, &1000);
+    assert_eq!(s.locker.get_pool_lock_count(&s.pool.address), 2);
+    assert_eq!(s.locker.get_pool_locks(&s.pool.address, &0, &10), vec![&env, lock1, lock2]);
+    assert_eq!(s.locker.get_pool_locks(&s.pool.address, &1, &10), vec![&env, lock2]);
+    assert_eq!(s.locker.get_owner_lock_count(&owner1), 1);
+
+    generate_fees(&s, &trader);
+    let paid1 = s.locker.claim_fees(&owner1, &lock1);
+    let paid2 = s.locker.claim_fees(&owner2, &lock2);
+    assert!(paid1.0 > 0 || paid1.1 > 0);
+    assert!((paid1.0 - paid2.0).abs() <= 1);
+    assert!((paid1.1 - paid2.1).abs() <= 1);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized")]
+fn test_claim_by_other_address_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let other = Address::generate(&env);
+
+    let s = setup(&env, &admin);
+    let liquidity = provide_liquidity(&s, &creator, 20_000_000_000);
+    let lock_id = s.locker.lock(&creator, &s.pool.address, &liquidity, &1000);
+    s.locker.claim_fees(&other, &lock_id);
+}
+
+#[test]
+#[should_panic(expected = "Unlock ledger must be in the future")]
+fn test_lock_in_the_past_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+
+    let s = setup(&env, &admin);
+    let liquidity = provide_liquidity(&s, &creator, 20_000_000_000);
+    env.ledger().set_sequence_number(500);
+    s.locker.lock(&creator, &s.pool.address, &liquidity, &500);
+}
+
+#[test]
+fn test_lock_stable_pool_lp() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let trader = Address::generate(&env);
+
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    let pool = StableSwapPoolClient::new(
+        &env,
+        &env.register(
+            StableSwapPool,
+            (
+                &token_a.address,
+                &token_b.address,
+                String::from_val(&env, &"StableLPToken"),
+                String::from_val(&env, &"SLP"),
+                &admin,
+                100_u32,
+            ),
+        ),
+    );
+    let locker = LpLockerClient::new(&env, &env.register(LpLocker, ()));
+
+    token_a.mint(&creator, &10_000_000_000);
+    token_b.mint(&creator, &10_000_000_000);
+    token_a.approve(&creator, &pool.address, &10_000_000_000, &1000);
+    token_b.approve(&creator, &pool.address, &10_000_000_000, &1000);
+    let liquidity = pool.add_liquidity(&creator, &10_000_000_000, &10_000_000_000);
+
+    // The stable pool has no `claim_fees`; its fees compound into the locked LP tokens
+    let lock_id = locker.lock(&creator, &pool.address, &liquidity, &1000);
+    token_a.mint(&trader, &1_000_000_000);
+    token_a.approve(&trader, &pool.address, &1_000_000_000, &1000);
+    pool.swap(&trader, &token_a.address, &1_000_000_000);
+    assert_eq!(locker.claim_fees(&creator, &lock_id), (0, 0));
+
+    env.ledger().set_sequence_number(1000);
+    assert_eq!(locker.withdraw(&creator, &lock_id), liquidity);
+    assert_eq!(pool.balance(&creator), liquidity);
+}
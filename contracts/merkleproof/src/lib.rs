@@ -0,0 +1,65 @@
+//! Merkle proofs over sha256 leaves, shared by the presale allowlist and the airdrop.
+//!
+//! Each contract hashes its own leaves. Pairs are hashed in sorted order, so a proof is just the
+//! list of siblings from the leaf up to the root.
+#![no_std]
+
+use soroban_sdk::{Bytes, BytesN, Env, Vec};
+
+#[cfg(any(test, feature = "testutils"))]
+extern crate std;
+
+pub fn hash_pair(e: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+    let (first, second) = if a < b { (a, b) } else { (b, a) };
+    let mut data = Bytes::from_array(e, &first.to_array());
+    data.extend_from_array(&second.to_array());
+    e.crypto().sha256(&data).to_bytes()
+}
+
+pub fn verify(e: &Env, root: &BytesN<32>, leaf: BytesN<32>, proof: &Vec<BytesN<32>>) -> bool {
+    let mut hash = leaf;
+    for sibling in proof.iter() {
+        hash = hash_pair(e, &hash, &sibling);
+    }
+    hash == *root
+}
+
+/// Builds trees and proofs in tests. A node without a sibling moves up a level unchanged.
+#[cfg(any(test, feature = "testutils"))]
+pub struct MerkleTree {
+    levels: std::vec::Vec<std::vec::Vec<BytesN<32>>>,
+}
+
+#[cfg(any(test, feature = "testutils"))]
+impl MerkleTree {
+    pub fn new(e: &Env, leaves: std::vec::Vec<BytesN<32>>) -> Self {
+        let mut levels = std::vec![leaves];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| if pair.len() == 2 { hash_pair(e, &pair[0], &pair[1]) } else { pair[0].clone() })
+                .collect();
+            levels.push(next);
+        }
+        MerkleTree { levels }
+    }
+
+    pub fn root(&self) -> BytesN<32> {
+        self.levels.last().unwrap()[0].clone()
+    }
+
+    /// Siblings from the leaf at `position` (in the order the leaves were given) up to the root
+    pub fn proof(&self, e: &Env, position: usize) -> Vec<BytesN<32>> {
+        let mut proof = Vec::new(e);
+        let mut position = position;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(position ^ 1) {
+                proof.push_back(sibling.clone());
+            }
+            position /= 2;
+        }
+        proof
+    }
+}
@@ -1,17 +1,16 @@
 #![no_std]
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short, Address, Env, String,
-    token::{self},
+    contract, contractimpl, contracttype, symbol_short, Address, BytesN, Env, String,
+    token::{self, Interface as _},
 };
 
 // Constants
 const FEE_BPS: i128 = 30; // 0.3%
 const BPS_DENOMINATOR: i128 = 10000;
 const MINIMUM_LIQUIDITY: i128 = 1000; // Minimum liquidity to prevent division by zero
-
-// XLM Native Asset Address
-const XLM_ASSET: &str = "native";
+const FEE_SCALE: i128 = 1_000_000_000_000_000_000; // Precision of the fees per LP token (1e18)
+const SCHEMA_VERSION: u32 = 3; // Storage layout version, bump with every PoolInfo, FeeTracker or DataKey change
 
 // Helper function to check if an address represents native XLM
 fn is_native_xlm(address: &Address) -> bool {
@@ -57,9 +56,20 @@ pub struct PoolInfo {
 pub struct FeeTracker {
     pub total_fees_earned: i128,      // Total fees earned by the pool
     pub fees_per_lp_token: i128,      // Fees per LP token (scaled by 1e18)
+    pub fees_per_lp_token_a: i128,    // Token A fees per LP token (scaled by 1e18)
+    pub fees_per_lp_token_b: i128,    // Token B fees per LP token (scaled by 1e18)
     pub last_update_ledger: u32,      // Last ledger when fees were updated
 }
 
+// FeeTracker layout of schema version 1, before fees were tracked per token
+#[derive(Clone)]
+#[contracttype]
+struct FeeTrackerV1 {
+    total_fees_earned: i128,
+    fees_per_lp_token: i128,
+    last_update_ledger: u32,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub struct VolumeTracker {
@@ -127,6 +137,8 @@ impl LiquidityPool {
         e.storage().instance().get(&DataKey::FeeTracker).unwrap_or(FeeTracker {
             total_fees_earned: 0,
             fees_per_lp_token: 0,
+            fees_per_lp_token_a: 0,
+            fees_per_lp_token_b: 0,
             last_update_ledger: 0,
         })
     }
@@ -135,38 +147,74 @@ impl LiquidityPool {
         e.storage().instance().set(&DataKey::FeeTracker, tracker);
     }
 
-    fn update_fees(e: &Env, fee_amount: i128) {
+    // Credit a swap fee to the LPs and return the part held outside the reserves for them to claim
+    fn update_fees(e: &Env, is_token_a: bool, fee_amount: i128) -> i128 {
         let mut tracker = Self::get_fee_tracker(e);
         let current_ledger = e.ledger().sequence();
         tracker.total_fees_earned = checked_add(tracker.total_fees_earned, fee_amount);
         let total_supply = Self::total_supply(e.clone());
+        let mut held_fee = 0;
         if total_supply > 0 {
+            held_fee = fee_amount;
             // Use high precision for per-LP-token fee math (1e18 scaling)
-            let fee_increment = checked_div(checked_mul(fee_amount, 1_000_000_000_000_000_000), total_supply);
+            let fee_increment = checked_div(checked_mul(fee_amount, FEE_SCALE), total_supply);
             tracker.fees_per_lp_token = checked_add(tracker.fees_per_lp_token, fee_increment);
+            if is_token_a {
+                tracker.fees_per_lp_token_a = checked_add(tracker.fees_per_lp_token_a, fee_increment);
+            } else {
+                tracker.fees_per_lp_token_b = checked_add(tracker.fees_per_lp_token_b, fee_increment);
+            }
         }
         tracker.last_update_ledger = current_ledger;
         Self::set_fee_tracker(e, &tracker);
+        held_fee
     }
 
-    fn get_user_last_fees_per_lp_token(e: &Env, user: &Address) -> i128 {
-        e.storage().instance().get(&DataKey::UserLastFeesPerLpToken(user.clone())).unwrap_or(0)
+    // User's (token A, token B) fees per LP token already accounted for
+    fn get_user_fee_checkpoint(e: &Env, user: &Address) -> (i128, i128) {
+        e.storage().instance().get(&DataKey::UserFeeCheckpoint(user.clone())).unwrap_or((0, 0))
     }
-    fn set_user_last_fees_per_lp_token(e: &Env, user: &Address, value: i128) {
-        e.storage().instance().set(&DataKey::UserLastFeesPerLpToken(user.clone()), &value);
+    fn set_user_fee_checkpoint(e: &Env, user: &Address, value: (i128, i128)) {
+        e.storage().instance().set(&DataKey::UserFeeCheckpoint(user.clone()), &value);
     }
 
-    // Calculate user's unclaimed fees using per-LP-token fee accounting
-    fn calculate_user_unclaimed_fees(e: &Env, user: &Address) -> i128 {
+    // Checkpoint raised to the fees per LP token at the version 3 migration. Fees from before
+    // it were compounded into the reserves and can't be claimed separately
+    fn get_effective_fee_checkpoint(e: &Env, user: &Address) -> (i128, i128) {
+        let (last_a, last_b) = Self::get_user_fee_checkpoint(e, user);
+        let (base_a, base_b): (i128, i128) = e.storage().instance().get(&DataKey::FeeBaseline).unwrap_or((0, 0));
+        (last_a.max(base_a), last_b.max(base_b))
+    }
+
+    // Blend the receiver's checkpoint with the one the incoming LP tokens carry, so fees
+    // pending on those tokens move with them instead of being lost or claimed twice
+    fn merge_fee_checkpoint(e: &Env, to: &Address, amount: i128, incoming_checkpoint: (i128, i128)) {
+        let to_balance = Self::balance_of(e.clone(), to.clone());
+        let new_balance = checked_add(to_balance, amount);
+        if new_balance == 0 {
+            return;
+        }
+        let to_checkpoint = Self::get_effective_fee_checkpoint(e, to);
+        let weighted_a = checked_add(checked_mul(to_balance, to_checkpoint.0), checked_mul(amount, incoming_checkpoint.0));
+        let weighted_b = checked_add(checked_mul(to_balance, to_checkpoint.1), checked_mul(amount, incoming_checkpoint.1));
+        Self::set_user_fee_checkpoint(e, to, (checked_div(weighted_a, new_balance), checked_div(weighted_b, new_balance)));
+    }
+
+    // Calculate user's unclaimed (token A, token B) fees using per-LP-token fee accounting
+    fn calculate_user_unclaimed_fees(e: &Env, user: &Address) -> (i128, i128) {
         let tracker = Self::get_fee_tracker(e);
         let user_balance = Self::balance_of(e.clone(), user.clone());
         if user_balance == 0 {
-            return 0;
+            return (0, 0);
         }
-        let last_claimed = Self::get_user_last_fees_per_lp_token(e, user);
-        let delta = checked_sub(tracker.fees_per_lp_token, last_claimed);
+        let (last_a, last_b) = Self::get_effective_fee_checkpoint(e, user);
+        let delta_a = checked_sub(tracker.fees_per_lp_token_a, last_a);
+        let delta_b = checked_sub(tracker.fees_per_lp_token_b, last_b);
         // Divide by 1e18 to get actual fee amount
-        checked_div(checked_mul(user_balance, delta), 1_000_000_000_000_000_000)
+        (
+            checked_div(checked_mul(user_balance, delta_a), FEE_SCALE),
+            checked_div(checked_mul(user_balance, delta_b), FEE_SCALE),
+        )
     }
 
     // Volume tracking functions
@@ -198,6 +246,17 @@ impl LiquidityPool {
         Self::set_volume_tracker(e, &tracker);
     }
 
+    // Add a swap to the volume of each token, in that token's units
+    fn update_token_volumes(e: &Env, is_token_a_in: bool, amount_in: i128, amount_out: i128) {
+        let (volume_a, volume_b) = Self::get_token_volumes(e.clone());
+        let volumes = if is_token_a_in {
+            (checked_add(volume_a, amount_in), checked_add(volume_b, amount_out))
+        } else {
+            (checked_add(volume_a, amount_out), checked_add(volume_b, amount_in))
+        };
+        e.storage().instance().set(&DataKey::TokenVolumes, &volumes);
+    }
+
     // User fee tracking functions
     fn get_user_fees_claimed(e: &Env, user: &Address) -> i128 {
         e.storage().instance().get(&DataKey::UserFeesClaimed(user.clone())).unwrap_or(0)
@@ -213,6 +272,7 @@ impl LiquidityPool {
         token_b: Address,
         lp_token_name: String,
         lp_token_symbol: String,
+        factory: Address,
     ) {
         // Determine if this is an XLM pool
         let is_xlm_pool = is_native_xlm(&token_a) || is_native_xlm(&token_b);
@@ -237,6 +297,53 @@ impl LiquidityPool {
         e.storage().instance().set(&symbol_short!("name"), &lp_token_name);
         e.storage().instance().set(&symbol_short!("symbol"), &lp_token_symbol);
         e.storage().instance().set(&symbol_short!("decimals"), &18u32);
+        e.storage().instance().set(&DataKey::Factory, &factory);
+        e.storage().instance().set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+    }
+
+    fn require_factory(e: &Env) {
+        let factory: Address = e.storage().instance().get(&DataKey::Factory).unwrap();
+        factory.require_auth();
+    }
+
+    /// Replace the pool code (factory only). The factory calls `migrate` right after, with the new code
+    pub fn upgrade(e: Env, new_wasm_hash: BytesN<32>) {
+        Self::require_factory(&e);
+        e.deployer().update_current_contract_wasm(new_wasm_hash.clone());
+        e.events().publish((symbol_short!("upgrade"),), new_wasm_hash);
+    }
+
+    /// Convert the storage written by older code to this code's layout (factory only).
+    /// Returns the schema version the storage is at
+    pub fn migrate(e: Env) -> u32 {
+        Self::require_factory(&e);
+        let version = Self::schema_version(e.clone());
+        assert!(version <= SCHEMA_VERSION, "Storage is newer than the code");
+        // A layout change adds its conversion here, reading the previous PoolInfo or FeeTracker
+        // struct when `version` is below the one that introduced the change
+        if version < 2 {
+            // Version 2 tracks fees per token. Fees earned before it stay in the reserves
+            if let Some(tracker) = e.storage().instance().get::<_, FeeTrackerV1>(&DataKey::FeeTracker) {
+                Self::set_fee_tracker(&e, &FeeTracker {
+                    total_fees_earned: tracker.total_fees_earned,
+                    fees_per_lp_token: tracker.fees_per_lp_token,
+                    fees_per_lp_token_a: 0,
+                    fees_per_lp_token_b: 0,
+                    last_update_ledger: tracker.last_update_ledger,
+                });
+            }
+        }
+        if version < 3 {
+            // Version 3 holds swap fees outside the reserves. Fees earned before it were
+            // compounded into the reserves, so nobody can claim them separately any more
+            let tracker = Self::get_fee_tracker(&e);
+            e.storage().instance().set(&DataKey::FeeBaseline, &(tracker.fees_per_lp_token_a, tracker.fees_per_lp_token_b));
+        }
+        if version < SCHEMA_VERSION {
+            e.storage().instance().set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+            e.events().publish((symbol_short!("migrate"),), (version, SCHEMA_VERSION));
+        }
+        SCHEMA_VERSION
     }
 
     pub fn add_liquidity(e: Env, caller: Address, amount_a: i128, amount_b: i128) -> i128 {
@@ -308,14 +415,14 @@ impl LiquidityPool {
                 // Transfer XLM from caller to contract
                 Self::transfer_native_xlm_from_user(e, caller, amount_a);
                 // Transfer contract token from caller to pool
-                token_b_client.transfer_from(&caller, &e.current_contract_address(), &e.current_contract_address(), &amount_b);
+                token_b_client.transfer_from(caller, &e.current_contract_address(), &e.current_contract_address(), &amount_b);
             },
             Some(1) => {
                 // Token A is contract token, Token B is XLM
                 let token_a_client = token::Client::new(e, &pool_info.token_a);
                 
                 // Transfer contract token from caller to pool
-                token_a_client.transfer_from(&caller, &e.current_contract_address(), &e.current_contract_address(), &amount_a);
+                token_a_client.transfer_from(caller, &e.current_contract_address(), &e.current_contract_address(), &amount_a);
                 // Transfer XLM from caller to contract
                 Self::transfer_native_xlm_from_user(e, caller, amount_b);
             },
@@ -387,7 +494,8 @@ impl LiquidityPool {
         assert!(amount_b <= pool_info.reserve_b, "Cannot remove more token B than available");
     
         
-        // Burn LP tokens from caller
+        // Pay out the fees the burnt LP tokens earned, then burn them
+        Self::pay_fees(&e, &caller, &pool_info);
         Self::burn_lp_tokens(&e, &caller, liquidity);
         
     
@@ -507,10 +615,11 @@ impl LiquidityPool {
 
         // Calculate and track fees
         let fee_amount = checked_sub(amount_in, amount_in_with_fee);
-        Self::update_fees(&e, fee_amount);
+        let held_fee = Self::update_fees(&e, is_token_a_in, fee_amount);
 
         // Track volume
         Self::update_volume(&e, amount_in);
+        Self::update_token_volumes(&e, is_token_a_in, amount_in, amount_out);
 
         // Handle output token transfer based on whether it's XLM
         if pool_info.is_xlm_pool && (is_native_xlm(&token_in) || is_native_xlm(&token_out)) {
@@ -521,12 +630,13 @@ impl LiquidityPool {
             token_out_client.transfer(&e.current_contract_address(), &caller, &amount_out);
         }
 
-        // Update reserves with overflow protection
+        // Update reserves with overflow protection. The LP fee stays outside them until claimed
+        let reserve_in_added = checked_sub(amount_in, held_fee);
         if is_token_a_in {
-            pool_info.reserve_a = checked_add(pool_info.reserve_a, amount_in);
+            pool_info.reserve_a = checked_add(pool_info.reserve_a, reserve_in_added);
             pool_info.reserve_b = checked_sub(pool_info.reserve_b, amount_out);
         } else {
-            pool_info.reserve_b = checked_add(pool_info.reserve_b, amount_in);
+            pool_info.reserve_b = checked_add(pool_info.reserve_b, reserve_in_added);
             pool_info.reserve_a = checked_sub(pool_info.reserve_a, amount_out);
         }
         e.storage().instance().set(&symbol_short!("pool"), &pool_info);
@@ -542,7 +652,7 @@ impl LiquidityPool {
         } else {
             // Input is contract token
             let token_in_client = token::Client::new(e, token_in);
-            token_in_client.transfer_from(&caller, &e.current_contract_address(), &e.current_contract_address(), &amount_in);
+            token_in_client.transfer_from(caller, &e.current_contract_address(), &e.current_contract_address(), &amount_in);
         }
     }
 
@@ -554,11 +664,20 @@ impl LiquidityPool {
         } else {
             // Output is contract token
             let token_out_client = token::Client::new(e, token_out);
-            token_out_client.transfer(&e.current_contract_address(), &caller, &amount_out);
+            token_out_client.transfer(&e.current_contract_address(), caller, &amount_out);
         }
     }
 
     // View functions
+    pub fn get_factory(e: Env) -> Address {
+        e.storage().instance().get(&DataKey::Factory).unwrap()
+    }
+
+    /// Storage layout version, 1 for pools created before versioning
+    pub fn schema_version(e: Env) -> u32 {
+        e.storage().instance().get(&DataKey::SchemaVersion).unwrap_or(1)
+    }
+
     pub fn get_token_a(e: Env) -> Address {
         let pool_info: PoolInfo = e.storage().instance().get(&symbol_short!("pool")).unwrap();
         pool_info.token_a
@@ -603,75 +722,61 @@ impl LiquidityPool {
         tracker.fees_per_lp_token
     }
 
+    /// Unclaimed fees of a user, token A and token B amounts added together
     pub fn get_user_unclaimed_fees(e: Env, user: Address) -> i128 {
+        let (fees_a, fees_b) = Self::calculate_user_unclaimed_fees(&e, &user);
+        checked_add(fees_a, fees_b)
+    }
+
+    /// Unclaimed (token A, token B) fees of a user
+    pub fn get_user_unclaimed_fees_by_token(e: Env, user: Address) -> (i128, i128) {
         Self::calculate_user_unclaimed_fees(&e, &user)
     }
 
+    /// Pay out the caller's share of the swap fees earned since their last claim. The fees are
+    /// held outside the reserves. Returns the token A and token B amounts added together
     pub fn claim_fees(e: Env, caller: Address) -> i128 {
         caller.require_auth();
-        
-        let unclaimed_fees = Self::calculate_user_unclaimed_fees(&e, &caller);
-        if unclaimed_fees <= 0 {
-            return 0;
-        }
-        
-        // Get pool info
         let pool_info: PoolInfo = e.storage().instance().get(&symbol_short!("pool")).unwrap();
-        let total_supply = Self::total_supply(e.clone());
-        let user_balance = Self::balance_of(e.clone(), caller.clone());
-        
-        // Calculate user's share of the pool
-        let user_share = if total_supply > 0 {
-            checked_div(checked_mul(user_balance, 10000), total_supply) // In basis points
-        } else {
-            0
-        };
-        
-        // Calculate fee amounts for each token based on user's share
-        let fee_tracker = Self::get_fee_tracker(&e);
-        let total_fees_earned = fee_tracker.total_fees_earned;
-        
-        // Calculate user's share of fees for each token
-        let user_fee_share_a = if total_supply > 0 {
-            checked_div(checked_mul(pool_info.reserve_a, user_share), 10000)
-        } else {
-            0
-        };
-        
-        let user_fee_share_b = if total_supply > 0 {
-            checked_div(checked_mul(pool_info.reserve_b, user_share), 10000)
-        } else {
-            0
-        };
+        let (user_fee_share_a, user_fee_share_b) = Self::pay_fees(&e, &caller, &pool_info);
+        // Return the total fee amount claimed
+        checked_add(user_fee_share_a, user_fee_share_b)
+    }
+
+    // Transfer a user's unclaimed fees and move their checkpoint up. Runs before any burn,
+    // since fees are only held for LP tokens that still exist
+    fn pay_fees(e: &Env, user: &Address, pool_info: &PoolInfo) -> (i128, i128) {
+        let (user_fee_share_a, user_fee_share_b) = Self::calculate_user_unclaimed_fees(e, user);
+        if user_fee_share_a <= 0 && user_fee_share_b <= 0 {
+            return (0, 0);
+        }
         
         // Update claimed fees
-        let current_claimed = Self::get_user_fees_claimed(&e, &caller);
-        let new_claimed = checked_add(current_claimed, unclaimed_fees);
-        Self::set_user_fees_claimed(&e, &caller, new_claimed);
+        let current_claimed = Self::get_user_fees_claimed(e, user);
+        let new_claimed = checked_add(current_claimed, checked_add(user_fee_share_a, user_fee_share_b));
+        Self::set_user_fees_claimed(e, user, new_claimed);
         
-        // Update user's last claimed fees_per_lp_token
-        let tracker = Self::get_fee_tracker(&e);
-        Self::set_user_last_fees_per_lp_token(&e, &caller, tracker.fees_per_lp_token);
+        // Update user's fee checkpoint
+        let tracker = Self::get_fee_tracker(e);
+        Self::set_user_fee_checkpoint(e, user, (tracker.fees_per_lp_token_a, tracker.fees_per_lp_token_b));
         
         // Transfer fee tokens to user based on whether it's an XLM pool
         if pool_info.is_xlm_pool {
-            Self::handle_xlm_fee_transfer(&e, &caller, &pool_info, user_fee_share_a, user_fee_share_b);
+            Self::handle_xlm_fee_transfer(e, user, pool_info, user_fee_share_a, user_fee_share_b);
         } else {
             // Standard token transfer for non-XLM pools
-            let token_a_client = token::Client::new(&e, &pool_info.token_a);
-            let token_b_client = token::Client::new(&e, &pool_info.token_b);
+            let token_a_client = token::Client::new(e, &pool_info.token_a);
+            let token_b_client = token::Client::new(e, &pool_info.token_b);
             
             // Only transfer if amounts are greater than 0
             if user_fee_share_a > 0 {
-                token_a_client.transfer(&e.current_contract_address(), &caller, &user_fee_share_a);
+                token_a_client.transfer(&e.current_contract_address(), user, &user_fee_share_a);
             }
             if user_fee_share_b > 0 {
-                token_b_client.transfer(&e.current_contract_address(), &caller, &user_fee_share_b);
+                token_b_client.transfer(&e.current_contract_address(), user, &user_fee_share_b);
             }
         }
-        
-        // Return the total fee amount claimed
-        checked_add(user_fee_share_a, user_fee_share_b)
+        (user_fee_share_a, user_fee_share_b)
     }
 
     // Handle XLM fee transfer
@@ -728,6 +833,11 @@ impl LiquidityPool {
         tracker.total_volume_all_time
     }
 
+    /// All-time swap volume of (token A, token B), each counting what was swapped in and out in that token
+    pub fn get_token_volumes(e: Env) -> (i128, i128) {
+        e.storage().instance().get(&DataKey::TokenVolumes).unwrap_or((0, 0))
+    }
+
     // Enhanced liquidity position methods
     pub fn get_user_liquidity_position(e: Env, user: Address) -> (i128, i128, i128) {
         let user_balance = Self::balance_of(e.clone(), user.clone());
@@ -738,9 +848,6 @@ impl LiquidityPool {
             return (0, 0, 0);
         }
         
-        // Calculate user's share of the pool
-        let user_share = checked_div(checked_mul(user_balance, 10000), total_supply); // In basis points
-        
         // Calculate user's token amounts
         let user_token_a = checked_div(checked_mul(pool_info.reserve_a, user_balance), total_supply);
         let user_token_b = checked_div(checked_mul(pool_info.reserve_b, user_balance), total_supply);
@@ -759,6 +866,10 @@ impl LiquidityPool {
     }
 
     fn mint_lp_tokens(e: Env, to: Address, amount: i128) {
+        // Newly minted LP tokens only earn fees from here on
+        let tracker = Self::get_fee_tracker(&e);
+        Self::merge_fee_checkpoint(&e, &to, amount, (tracker.fees_per_lp_token_a, tracker.fees_per_lp_token_b));
+
         let total_supply = Self::total_supply(e.clone());
         let new_total_supply = checked_add(total_supply, amount);
         e.storage().instance().set(&DataKey::TotalSupply, &new_total_supply);
@@ -785,6 +896,7 @@ impl LiquidityPool {
     }
 }
 
+#[contractimpl]
 impl token::Interface for LiquidityPool {
     fn allowance(e: Env, from: Address, spender: Address) -> i128 {
         e.storage().instance().get(&DataKey::Allowance(from, spender)).unwrap_or(0)
@@ -807,6 +919,9 @@ impl token::Interface for LiquidityPool {
         let balance = Self::balance(e.clone(), from.clone());
         assert!(balance >= amount, "Insufficient balance");
         
+        let from_checkpoint = Self::get_effective_fee_checkpoint(&e, &from);
+        Self::merge_fee_checkpoint(&e, &to, amount, from_checkpoint);
+
         let new_from_balance = checked_sub(balance, amount);
         let to_balance = Self::balance(e.clone(), to.clone());
         let new_to_balance = checked_add(to_balance, amount);
@@ -826,6 +941,9 @@ impl token::Interface for LiquidityPool {
         assert!(balance >= amount, "Insufficient balance");
         
         let new_allowance = checked_sub(allowance, amount);
+        let from_checkpoint = Self::get_effective_fee_checkpoint(&e, &from);
+        Self::merge_fee_checkpoint(&e, &to, amount, from_checkpoint);
+
         let new_from_balance = checked_sub(balance, amount);
         let to_balance = Self::balance(e.clone(), to.clone());
         let new_to_balance = checked_add(to_balance, amount);
@@ -841,6 +959,8 @@ impl token::Interface for LiquidityPool {
         
         let balance = Self::balance(e.clone(), from.clone());
         assert!(balance >= amount, "Insufficient balance");
+        let pool_info: PoolInfo = e.storage().instance().get(&symbol_short!("pool")).unwrap();
+        Self::pay_fees(&e, &from, &pool_info);
         
         let new_balance = checked_sub(balance, amount);
         let total_supply = Self::total_supply(e.clone());
@@ -859,6 +979,8 @@ impl token::Interface for LiquidityPool {
         
         let balance = Self::balance(e.clone(), from.clone());
         assert!(balance >= amount, "Insufficient balance");
+        let pool_info: PoolInfo = e.storage().instance().get(&symbol_short!("pool")).unwrap();
+        Self::pay_fees(&e, &from, &pool_info);
         
         let new_allowance = checked_sub(allowance, amount);
         let new_balance = checked_sub(balance, amount);
@@ -892,8 +1014,13 @@ enum DataKey {
     NativeXlmBalance, // Track native XLM balance in the contract
     FeeTracker, // Track total fees earned and fees per LP token
     VolumeTracker, // Track total volume and last swap ledger
+    TokenVolumes, // All-time swap volume of (token A, token B)
     UserFeesClaimed(Address), // Track user's total claimed fees (legacy, not used)
-    UserLastFeesPerLpToken(Address), // Track user's last claimed fees_per_lp_token
+    UserLastFeesPerLpToken(Address), // Track user's last claimed fees_per_lp_token (legacy, not used)
+    UserFeeCheckpoint(Address), // User's last claimed (token A, token B) fees per LP token
+    FeeBaseline, // (token A, token B) fees per LP token already compounded into the reserves
+    Factory, // Factory allowed to upgrade the pool
+    SchemaVersion, // Storage layout version, see SCHEMA_VERSION
 }
 
 fn sqrt(x: i128) -> i128 {
@@ -6,6 +6,7 @@ use soroban_sdk::{
 };
 use crate::LiquidityPool;
 use crate::LiquidityPoolClient;
+use crate::{DataKey, FeeTracker, FeeTrackerV1};
 use ::token::Token;
 use ::token::TokenClient;
 
@@ -31,6 +32,7 @@ fn deploy_pool<'a>(e: &Env, token_a: &TokenClient<'a>, token_b: &TokenClient<'a>
             &token_b.address,
             String::from_val(e, &"LPToken"),
             String::from_val(e, &"LP"),
+            Address::generate(e),
         ),
     );
     LiquidityPoolClient::new(e, &contract_id)
@@ -40,7 +42,7 @@ fn deploy_pool<'a>(e: &Env, token_a: &TokenClient<'a>, token_b: &TokenClient<'a>
 fn create_native_xlm_address(e: &Env) -> Address {
     // Create a mock XLM token by registering a dummy token contract
     // This simulates XLM in the test environment
-    let xlm_token = e.register(
+    e.register(
         Token,
         (
             &Address::generate(e), // admin
@@ -48,9 +50,7 @@ fn create_native_xlm_address(e: &Env) -> Address {
             String::from_val(e, &"Stellar Lumens"),
             String::from_val(e, &"XLM"),
         ),
-    );
-    
-    xlm_token
+    )
 }
 
 #[test]
@@ -342,10 +342,13 @@ fn test_swap_a_for_b() {
     assert!(amount_out > 0);
     assert!(amount_out < 1_000_000_000); // Should be less due to fee
     
-    // Verify reserves updated
+    // Verify reserves updated; the 0.3% LP fee is held outside them
     let (reserve_a, reserve_b) = pool.get_reserves();
-    assert_eq!(reserve_a, amount_a + swap_amount);
+    assert_eq!(reserve_a, amount_a + swap_amount - swap_amount * 3 / 1000);
     assert_eq!(reserve_b, amount_b - amount_out);
+
+    // Each token's volume counts it in its own units
+    assert_eq!(pool.get_token_volumes(), (swap_amount, amount_out));
 }
 
 #[test]
@@ -403,6 +406,7 @@ fn test_xlm_pool_detection() {
             &xlm_token,
             String::from_val(&env, &"LPToken"),
             String::from_val(&env, &"LP"),
+            Address::generate(&env),
         ),
     );
     let pool = LiquidityPoolClient::new(&env, &contract_id);
@@ -412,7 +416,7 @@ fn test_xlm_pool_detection() {
     assert_eq!(pool.get_token_b(), xlm_token);
     
     // Test that the pool correctly identifies as non-XLM pool (since we're using a mock token, not real "native")
-    assert_eq!(pool.is_xlm_pool(), false);
+    assert!(!pool.is_xlm_pool());
     
     // Test XLM token index
     let xlm_index = pool.get_xlm_token_index();
@@ -442,12 +446,13 @@ fn test_xlm_pool_with_xlm_as_token_a() {
             &token_b.address,
             String::from_val(&env, &"LPToken"),
             String::from_val(&env, &"LP"),
+            Address::generate(&env),
         ),
     );
     let pool = LiquidityPoolClient::new(&env, &contract_id);
     
     // Verify XLM pool detection (should be false since we're using a mock token)
-    assert_eq!(pool.is_xlm_pool(), false);
+    assert!(!pool.is_xlm_pool());
     
     // Test XLM token index
     let xlm_index = pool.get_xlm_token_index();
@@ -468,7 +473,7 @@ fn test_non_xlm_pool_detection() {
     let pool = deploy_pool(&env, &token_a, &token_b);
     
     // Verify that the pool correctly identifies as non-XLM pool
-    assert_eq!(pool.is_xlm_pool(), false);
+    assert!(!pool.is_xlm_pool());
     
     // Test XLM token index
     let xlm_index = pool.get_xlm_token_index();
@@ -650,6 +655,7 @@ fn test_xlm_pool_with_real_native_detection() {
             &native_xlm,
             String::from_val(&env, &"LPToken"),
             String::from_val(&env, &"LP"),
+            Address::generate(&env),
         ),
     );
     let pool = LiquidityPoolClient::new(&env, &contract_id);
@@ -659,7 +665,7 @@ fn test_xlm_pool_with_real_native_detection() {
     assert_eq!(pool.get_token_b(), native_xlm);
     
     // Test that the pool correctly identifies as non-XLM pool (since we're using a generated address, not "native")
-    assert_eq!(pool.is_xlm_pool(), false);
+    assert!(!pool.is_xlm_pool());
     
     // Test XLM token index
     let xlm_index = pool.get_xlm_token_index();
@@ -725,14 +731,14 @@ fn test_fee_tracking_and_claiming() {
     let initial_amount_b = 20_000_000_000;
     token_a.approve(&user1, &pool.address, &initial_amount_a, &1000);
     token_b.approve(&user1, &pool.address, &initial_amount_b, &1000);
-    let liquidity1 = pool.add_liquidity(&user1, &initial_amount_a, &initial_amount_b);
+    let _liquidity1 = pool.add_liquidity(&user1, &initial_amount_a, &initial_amount_b);
     
     // Add liquidity from user2
     let amount_a2 = 10_000_000_000;
     let amount_b2 = 10_000_000_000;
     token_a.approve(&user2, &pool.address, &amount_a2, &1000);
     token_b.approve(&user2, &pool.address, &amount_b2, &1000);
-    let liquidity2 = pool.add_liquidity(&user2, &amount_a2, &amount_b2);
+    let _liquidity2 = pool.add_liquidity(&user2, &amount_a2, &amount_b2);
     
     // Record initial balances
     let initial_balance_a1 = token_a.balance(&user1);
@@ -743,11 +749,11 @@ fn test_fee_tracking_and_claiming() {
     // Perform swaps to generate fees
     let swap_amount = 5_000_000_000;
     token_a.approve(&user1, &pool.address, &swap_amount, &1000);
-    let amount_out1 = pool.swap(&user1, &token_a.address, &swap_amount);
+    let _amount_out1 = pool.swap(&user1, &token_a.address, &swap_amount);
     
     // Perform another swap
     token_b.approve(&user1, &pool.address, &swap_amount, &1000);
-    let amount_out2 = pool.swap(&user1, &token_b.address, &swap_amount);
+    let _amount_out2 = pool.swap(&user1, &token_b.address, &swap_amount);
     
     // Check that fees were tracked
     let total_fees_earned = pool.get_total_fees_earned();
@@ -819,13 +825,13 @@ fn test_fee_claiming_with_xlm_pool() {
     token_b.approve(&user, &pool.address, &token_b_amount, &1000);
     pool.add_liquidity(&user, &xlm_amount, &token_b_amount);
     
-    // Record initial balance
-    let initial_balance_b = token_b.balance(&user);
-    
     // Perform swap to generate fees
     let swap_amount = 5_000_000_000;
     token_b.approve(&user, &pool.address, &swap_amount, &1000);
-    let amount_out = pool.swap(&user, &token_b.address, &swap_amount);
+    let _amount_out = pool.swap(&user, &token_b.address, &swap_amount);
+    
+    // Record balance before claiming
+    let initial_balance_b = token_b.balance(&user);
     
     // Check that fees were tracked
     let total_fees_earned = pool.get_total_fees_earned();
@@ -928,6 +934,206 @@ fn test_fee_claiming_after_removing_liquidity() {
     assert_eq!(claimed_amount, 0, "Should claim 0 fees after removing all liquidity");
 }
 
+#[test]
+fn test_lp_transfer_moves_pending_fees() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+
+    let token_a = create_token(&env, &user1);
+    let token_b = create_token(&env, &user1);
+    token_a.mint(&user1, &40_000_000_000);
+    token_b.mint(&user1, &40_000_000_000);
+    let pool = deploy_pool(&env, &token_a, &token_b);
+
+    token_a.approve(&user1, &pool.address, &20_000_000_000, &1000);
+    token_b.approve(&user1, &pool.address, &20_000_000_000, &1000);
+    let liquidity = pool.add_liquidity(&user1, &20_000_000_000, &20_000_000_000);
+
+    token_a.approve(&user1, &pool.address, &5_000_000_000, &1000);
+    pool.swap(&user1, &token_a.address, &5_000_000_000);
+    let pending = pool.get_user_unclaimed_fees(&user1);
+    assert!(pending > 0);
+
+    // Half of the LP tokens carry half of the pending fees to the receiver
+    pool.transfer(&user1, &user2, &(liquidity / 2));
+    assert_eq!(pool.balance(&user2), liquidity / 2);
+    let pending1 = pool.get_user_unclaimed_fees(&user1);
+    let pending2 = pool.get_user_unclaimed_fees(&user2);
+    assert!(pending1 > 0 && pending2 > 0);
+    assert!(pending - (pending1 + pending2) <= 1);
+}
+
+#[test]
+fn test_new_liquidity_does_not_earn_past_fees() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+
+    let token_a = create_token(&env, &user1);
+    let token_b = create_token(&env, &user1);
+    token_a.mint(&user1, &40_000_000_000);
+    token_b.mint(&user1, &40_000_000_000);
+    token_a.mint(&user2, &40_000_000_000);
+    token_b.mint(&user2, &40_000_000_000);
+    let pool = deploy_pool(&env, &token_a, &token_b);
+
+    token_a.approve(&user1, &pool.address, &20_000_000_000, &1000);
+    token_b.approve(&user1, &pool.address, &20_000_000_000, &1000);
+    pool.add_liquidity(&user1, &20_000_000_000, &20_000_000_000);
+    token_a.approve(&user1, &pool.address, &5_000_000_000, &1000);
+    pool.swap(&user1, &token_a.address, &5_000_000_000);
+
+    // Join at the current ratio after the swap
+    let (reserve_a, reserve_b) = pool.get_reserves();
+    let amount_a = 5_000_000_000;
+    let amount_b = amount_a * reserve_b / reserve_a;
+    token_a.approve(&user2, &pool.address, &amount_a, &1000);
+    token_b.approve(&user2, &pool.address, &amount_b, &1000);
+    pool.add_liquidity(&user2, &amount_a, &amount_b);
+
+    assert_eq!(pool.get_user_unclaimed_fees(&user2), 0);
+    assert_eq!(pool.claim_fees(&user2), 0);
+}
+
+#[test]
+fn test_claim_fees_pays_only_earned_fees() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+
+    let token_a = create_token(&env, &user1);
+    let token_b = create_token(&env, &user1);
+    token_a.mint(&user1, &40_000_000_000);
+    token_b.mint(&user1, &40_000_000_000);
+    token_a.mint(&user2, &20_000_000_000);
+    token_b.mint(&user2, &20_000_000_000);
+    let pool = deploy_pool(&env, &token_a, &token_b);
+
+    token_a.approve(&user1, &pool.address, &10_000_000_000, &1000);
+    token_b.approve(&user1, &pool.address, &10_000_000_000, &1000);
+    pool.add_liquidity(&user1, &10_000_000_000, &10_000_000_000);
+    token_a.approve(&user2, &pool.address, &10_000_000_000, &1000);
+    token_b.approve(&user2, &pool.address, &10_000_000_000, &1000);
+    pool.add_liquidity(&user2, &10_000_000_000, &10_000_000_000);
+
+    // 0.3% of each swap goes to the LPs, in the input token
+    token_a.approve(&user1, &pool.address, &2_000_000_000, &1000);
+    pool.swap(&user1, &token_a.address, &2_000_000_000);
+    token_b.approve(&user1, &pool.address, &1_000_000_000, &1000);
+    pool.swap(&user1, &token_b.address, &1_000_000_000);
+    assert_eq!(pool.get_user_unclaimed_fees_by_token(&user2), (3_000_000, 1_500_000));
+
+    let (reserve_a, reserve_b) = pool.get_reserves();
+    let balance_a = token_a.balance(&user2);
+    let balance_b = token_b.balance(&user2);
+    assert_eq!(pool.claim_fees(&user2), 4_500_000);
+    assert_eq!(token_a.balance(&user2), balance_a + 3_000_000);
+    assert_eq!(token_b.balance(&user2), balance_b + 1_500_000);
+    assert_eq!(pool.get_reserves(), (reserve_a, reserve_b));
+    assert_eq!(pool.get_user_unclaimed_fees_by_token(&user2), (0, 0));
+    assert_eq!(pool.get_user_unclaimed_fees_by_token(&user1), (3_000_000, 1_500_000));
+}
+
+#[test]
+fn test_claiming_lp_does_not_take_from_other_lps() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    let trader = Address::generate(&env);
+
+    let token_a = create_token(&env, &user1);
+    let token_b = create_token(&env, &user1);
+    for user in [&user1, &user2, &trader] {
+        token_a.mint(user, &20_000_000_000);
+        token_b.mint(user, &20_000_000_000);
+    }
+    let pool = deploy_pool(&env, &token_a, &token_b);
+
+    for user in [&user1, &user2] {
+        token_a.approve(user, &pool.address, &10_000_000_000, &1000);
+        token_b.approve(user, &pool.address, &10_000_000_000, &1000);
+        pool.add_liquidity(user, &10_000_000_000, &10_000_000_000);
+    }
+    token_a.approve(&trader, &pool.address, &2_000_000_000, &1000);
+    pool.swap(&trader, &token_a.address, &2_000_000_000);
+    token_b.approve(&trader, &pool.address, &1_000_000_000, &1000);
+    pool.swap(&trader, &token_b.address, &1_000_000_000);
+
+    // Only user1 claims before both withdraw everything
+    pool.claim_fees(&user1);
+    pool.remove_liquidity(&user1, &pool.balance(&user1));
+    pool.remove_liquidity(&user2, &pool.balance(&user2));
+
+    // Equal positions end with equal value, within rounding
+    assert!((token_a.balance(&user1) - token_a.balance(&user2)).abs() <= 1);
+    assert!((token_b.balance(&user1) - token_b.balance(&user2)).abs() <= 1);
+    assert_eq!(pool.get_reserves(), (0, 0));
+    assert_eq!(pool.get_user_unclaimed_fees(&user2), 0);
+}
+
+#[test]
+fn test_migrate_fee_tracker_from_v1() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user);
+    let token_b = create_token(&env, &user);
+    let pool = deploy_pool(&env, &token_a, &token_b);
+
+    // Storage as written by schema version 1
+    env.as_contract(&pool.address, || {
+        let tracker = FeeTrackerV1 { total_fees_earned: 500, fees_per_lp_token: 7, last_update_ledger: 3 };
+        env.storage().instance().set(&DataKey::FeeTracker, &tracker);
+        env.storage().instance().set(&DataKey::SchemaVersion, &1_u32);
+    });
+
+    assert_eq!(pool.migrate(), 3);
+    assert_eq!(pool.schema_version(), 3);
+    assert_eq!(pool.get_total_fees_earned(), 500);
+    assert_eq!(pool.get_fees_per_lp_token(), 7);
+    assert_eq!(pool.get_user_unclaimed_fees_by_token(&user), (0, 0));
+}
+
+#[test]
+fn test_migrate_compounds_v2_fees() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user);
+    let token_b = create_token(&env, &user);
+    token_a.mint(&user, &20_000_000_000);
+    token_b.mint(&user, &20_000_000_000);
+    let pool = deploy_pool(&env, &token_a, &token_b);
+    token_a.approve(&user, &pool.address, &10_000_000_000, &1000);
+    token_b.approve(&user, &pool.address, &10_000_000_000, &1000);
+    pool.add_liquidity(&user, &10_000_000_000, &10_000_000_000);
+
+    // Fees tracked by schema version 2 were left in the reserves
+    env.as_contract(&pool.address, || {
+        let tracker = FeeTracker {
+            total_fees_earned: 10_000_000,
+            fees_per_lp_token: 1_000_000_000_000_000,
+            fees_per_lp_token_a: 1_000_000_000_000_000,
+            fees_per_lp_token_b: 0,
+            last_update_ledger: 0,
+        };
+        env.storage().instance().set(&DataKey::FeeTracker, &tracker);
+        env.storage().instance().set(&DataKey::SchemaVersion, &2_u32);
+    });
+    assert!(pool.get_user_unclaimed_fees(&user) > 0);
+
+    assert_eq!(pool.migrate(), 3);
+    assert_eq!(pool.get_user_unclaimed_fees_by_token(&user), (0, 0));
+    assert_eq!(pool.claim_fees(&user), 0);
+}
+
 #[test]
 fn test_volume_tracking() {
     let env = Env::default();
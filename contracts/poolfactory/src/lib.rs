@@ -1,16 +1,161 @@
 #![no_std]
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short, vec, Address, BytesN, Env, IntoVal, Map, String, Val, Vec
+    contract, contractclient, contractimpl, contracttype, symbol_short, vec, xdr::ToXdr, Address, BytesN, Env, String, Vec, U256,
+    token::{self},
 };
+use storagettl::{read_persistent, write_persistent};
+
+// Generated LP token names are "CDX-LP " followed by the token symbols joined with '/'
+const LP_NAME_PREFIX: &[u8] = b"CDX-LP ";
+const LP_SYMBOL: &str = "CDX-LP";
+const MAX_LP_NAME_LEN: usize = 64;
+
+// Pools kept in the top-by-volume list of each token
+const MAX_TOP_POOLS: u32 = 10;
+
+// Overflow protection functions
+fn checked_add(a: i128, b: i128) -> i128 {
+    a.checked_add(b).expect("Overflow in addition")
+}
+
+fn checked_sub(a: i128, b: i128) -> i128 {
+    a.checked_sub(b).expect("Underflow in subtraction")
+}
+
+// Swap fees of the pool types with a fixed fee, as set in their contracts
+const CONSTANT_PRODUCT_FEE_BPS: u32 = 30;
+const STABLE_FEE_BPS: u32 = 4;
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PoolType {
+    ConstantProduct, // x*y=k LiquidityPool
+    Stable,          // StableSwap pool for pegged assets
+    Concentrated,    // Concentrated liquidity pool with ranged positions
+    Weighted,        // Weighted product pool with two or more tokens
+}
+
+// Roles the admin can hand out. A role nobody was given is held by the admin
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Role {
+    Upgrader,   // Sets the pool Wasm hashes and upgrades deployed pools
+    FeeManager, // Manages pool fee parameters, such as stable pool amplification ramps
+    Pauser,     // Pauses and resumes pool creation
+}
+
+/// SEP-41 metadata of a pool token, read when the first pool with it is created
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenInfo {
+    pub decimals: u32,
+    pub name: String,
+    pub symbol: String,
+}
+
+/// Reserves and volume of a pool, as of the last `sync_pool_stats` that covered it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolStats {
+    pub reserves: Vec<i128>, // In the order of get_pool_tokens
+    pub volumes: Vec<i128>,  // All-time swap volume of each token, in that token's units and order
+    pub last_sync_ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolVolume {
+    pub pool: Address,
+    pub volume: i128,
+}
+
+/// Protocol totals across all pools, as of the last `sync_pool_stats` of each pool
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProtocolStats {
+    pub pool_count: u32,
+    pub last_sync_ledger: u32,
+}
+
+// Data of the event published for every pool the factory deploys
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolCreated {
+    pub pool: Address,
+    pub pool_type: PoolType,
+    pub tokens: Vec<Address>,
+    pub fee_bps: u32,
+    pub index: u32, // Registry index, see get_pool_at
+}
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DataKey {
     Admin,
+    PendingAdmin, // Proposed admin, until it accepts
+    Role(Role), // Holder of each assigned role
+    Paused, // Pool creation is paused
     PoolWasmHash,
-    DeployedPools(Address, Address),
-    AllPools, // Track all deployed pools for global calculations
+    DeployedPools(Address, Address), // Constant product pools, keyed by the sorted token pair, in persistent storage
+    AllPools, // Legacy instance list of all pools, moved into PoolAt by migrate_pool_registry
+    PoolTypeWasmHash(PoolType), // Wasm hash for pool types other than constant product
+    DeployedTypedPools(PoolType, Address, Address), // Pools of other types than constant product, in persistent storage
+    PoolTypeOf(Address), // Pool type of each deployed pool, in persistent storage
+    PoolCount, // Number of pools in the registry
+    PoolAt(u32), // Registry index to pool address, in persistent storage
+    PoolTokens(Address), // Tokens of each pool, set once the pool is in the token index
+    TokenPoolCount(Address), // Number of pools containing a token
+    TokenPoolAt(Address, u32), // Per-token index to pool address, in persistent storage
+    TokenInfo(Address), // SEP-41 metadata of each pool token
+    PoolStats(Address), // Last synced reserves and volume of each pool
+    TokenTvl(Address), // Synced reserves of a token summed over all pools
+    TokenVolume(Address), // Synced swap volume of a token summed over all pools
+    TopPools(Address), // Pools with the largest synced volume of a token, largest first
+    LastSyncLedger,
+}
+
+#[contractclient(name = "StablePoolClient")]
+pub trait StablePoolInterface {
+    fn ramp_amp(e: Env, future_amp: u32, future_ledger: u32);
+    fn stop_ramp_amp(e: Env);
+}
+
+// Token pair of the two-token pool types
+#[contractclient(name = "PairPoolClient")]
+pub trait PairPoolInterface {
+    fn get_token_a(e: Env) -> Address;
+    fn get_token_b(e: Env) -> Address;
+    fn get_reserves(e: Env) -> (i128, i128);
+    fn get_token_volumes(e: Env) -> (i128, i128);
+}
+
+#[contractclient(name = "ConstantProductPoolClient")]
+pub trait ConstantProductPoolInterface {
+    fn add_liquidity(e: Env, caller: Address, amount_a: i128, amount_b: i128) -> i128;
+}
+
+// Code upgrades of the pool types the factory administers
+#[contractclient(name = "UpgradablePoolClient")]
+pub trait UpgradablePoolInterface {
+    fn upgrade(e: Env, new_wasm_hash: BytesN<32>);
+    fn migrate(e: Env) -> u32;
+}
+
+#[contractclient(name = "WeightedPoolClient")]
+pub trait WeightedPoolInterface {
+    fn get_tokens(e: Env) -> Vec<Address>;
+    fn get_balances(e: Env) -> Vec<i128>;
+    fn get_token_volumes(e: Env) -> Vec<i128>;
+}
+
+// Order a token pair so (A, B) and (B, A) map to the same storage key
+fn sort_tokens(token_a: Address, token_b: Address) -> (Address, Address) {
+    if token_a < token_b {
+        (token_a, token_b)
+    } else {
+        (token_b, token_a)
+    }
 }
 
 #[contract]
@@ -22,37 +167,163 @@ impl PoolFactory {
         env.storage().instance().set(&DataKey::Admin, &admin);
     }
 
-    /// Set the pool contract Wasm hash (admin only)
-    pub fn update_pool_wasm_hash(env: Env, admin_addr: Address, new_hash: BytesN<32>) {
+    fn require_admin(env: &Env, admin_addr: &Address) {
         let admin = env.storage().instance().get::<_, Address>(&DataKey::Admin).expect("not set");
-        assert!(admin == admin_addr, "Unauthorized");
+        assert!(admin == *admin_addr, "Unauthorized");
         admin.require_auth();
-        env.storage().instance().set(&DataKey::PoolWasmHash, &new_hash);
+    }
+
+    fn require_role(env: &Env, role: Role, caller: &Address) {
+        let holder = Self::get_role(env.clone(), role);
+        assert!(holder == *caller, "Unauthorized");
+        caller.require_auth();
+    }
+
+    fn require_not_paused(env: &Env) {
+        assert!(!Self::is_paused(env.clone()), "Pool creation paused");
+    }
+
+    /// Propose a new admin (admin only). The transfer completes once the new admin accepts it
+    pub fn propose_admin(env: Env, admin_addr: Address, new_admin: Address) {
+        Self::require_admin(&env, &admin_addr);
+        env.storage().instance().set(&DataKey::PendingAdmin, &new_admin);
+        env.events().publish((symbol_short!("adm_prop"), admin_addr), new_admin);
+    }
+
+    /// Become admin after being proposed with `propose_admin`
+    pub fn accept_admin(env: Env, new_admin: Address) {
+        let pending: Address = env.storage().instance().get(&DataKey::PendingAdmin).expect("No pending admin");
+        assert!(pending == new_admin, "Unauthorized");
+        new_admin.require_auth();
+
+        let old_admin = Self::get_admin(env.clone());
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+        env.events().publish((symbol_short!("admin"), old_admin), new_admin);
+    }
+
+    /// Give a role to `holder`, or back to the admin with None (admin only)
+    pub fn set_role(env: Env, admin_addr: Address, role: Role, holder: Option<Address>) {
+        Self::require_admin(&env, &admin_addr);
+        match &holder {
+            Some(holder) => env.storage().instance().set(&DataKey::Role(role), holder),
+            None => env.storage().instance().remove(&DataKey::Role(role)),
+        }
+        env.events().publish((symbol_short!("role"), role), holder);
+    }
+
+    /// Pause or resume the creation of new pools (pauser only)
+    pub fn set_paused(env: Env, caller: Address, paused: bool) {
+        Self::require_role(&env, Role::Pauser, &caller);
+        env.storage().instance().set(&DataKey::Paused, &paused);
+        env.events().publish((symbol_short!("paused"), caller), paused);
+    }
+
+    // Read a token's SEP-41 metadata, panicking if the address isn't a token contract
+    fn load_token_info(env: &Env, token: &Address) -> TokenInfo {
+        let token_client = token::Client::new(env, token);
+        match (token_client.try_decimals(), token_client.try_name(), token_client.try_symbol()) {
+            (Ok(Ok(decimals)), Ok(Ok(name)), Ok(Ok(symbol))) => TokenInfo { decimals, name, symbol },
+            _ => panic!("Invalid token"),
+        }
+    }
+
+    // Check every token of a new pool and record its metadata
+    fn validate_tokens(env: &Env, tokens: &Vec<Address>) -> Vec<TokenInfo> {
+        let mut token_info = vec![env];
+        for token in tokens.iter() {
+            let info = Self::load_token_info(env, &token);
+            write_persistent(env, &DataKey::TokenInfo(token), &info);
+            token_info.push_back(info);
+        }
+        token_info
+    }
+
+    // The LP token name and symbol given by the caller, or "CDX-LP SYM_A/SYM_B" and "CDX-LP"
+    fn lp_token_names(env: &Env, token_info: &Vec<TokenInfo>, name: Option<String>, symbol: Option<String>) -> (String, String) {
+        let name = name.unwrap_or_else(|| {
+            let mut buf = [0u8; MAX_LP_NAME_LEN];
+            buf[..LP_NAME_PREFIX.len()].copy_from_slice(LP_NAME_PREFIX);
+            let mut len = LP_NAME_PREFIX.len();
+            for (i, info) in token_info.iter().enumerate() {
+                let symbol_len = info.symbol.len() as usize;
+                assert!(len + symbol_len < MAX_LP_NAME_LEN, "Token symbols too long for an LP name");
+                if i > 0 {
+                    buf[len] = b'/';
+                    len += 1;
+                }
+                info.symbol.copy_into_slice(&mut buf[len..len + symbol_len]);
+                len += symbol_len;
+            }
+            String::from_bytes(env, &buf[..len])
+        });
+        let symbol = symbol.unwrap_or_else(|| String::from_str(env, LP_SYMBOL));
+        (name, symbol)
+    }
+
+    // Storage key of a two-token pool, independent of the order the tokens are given in
+    fn pair_key(pool_type: PoolType, token_a: Address, token_b: Address) -> DataKey {
+        let (token_0, token_1) = sort_tokens(token_a, token_b);
+        Self::ordered_pair_key(pool_type, token_0, token_1)
+    }
+
+    // Storage key of a two-token pool for the tokens in the given order
+    fn ordered_pair_key(pool_type: PoolType, token_0: Address, token_1: Address) -> DataKey {
+        match pool_type {
+            PoolType::ConstantProduct => DataKey::DeployedPools(token_0, token_1),
+            _ => DataKey::DeployedTypedPools(pool_type, token_0, token_1),
+        }
+    }
+
+    // Deployment salt of a two-token pool: the pool type and the sorted pair, so each pool address
+    // follows from the pair alone
+    fn pool_salt(env: &Env, pool_type: PoolType, token_a: Address, token_b: Address) -> BytesN<32> {
+        let (token_0, token_1) = sort_tokens(token_a, token_b);
+        env.crypto().sha256(&(pool_type, token_0, token_1).to_xdr(env)).to_bytes()
+    }
+
+    fn wasm_hash_key(pool_type: PoolType) -> DataKey {
+        match pool_type {
+            PoolType::ConstantProduct => DataKey::PoolWasmHash,
+            _ => DataKey::PoolTypeWasmHash(pool_type),
+        }
+    }
+
+    /// Set the pool contract Wasm hash (upgrader only)
+    pub fn update_pool_wasm_hash(env: Env, caller: Address, new_hash: BytesN<32>) {
+        Self::update_pool_type_wasm_hash(env, caller, PoolType::ConstantProduct, new_hash);
     }
 
     /// Get the pool contract Wasm hash
     pub fn get_pool_wasm_hash(env: Env) -> BytesN<32> {
-        env.storage().instance().get(&DataKey::PoolWasmHash).expect("not set")
+        Self::get_pool_type_wasm_hash(env, PoolType::ConstantProduct)
+    }
+
+    /// Set the Wasm hash deployed for a pool type (upgrader only)
+    pub fn update_pool_type_wasm_hash(env: Env, caller: Address, pool_type: PoolType, new_hash: BytesN<32>) {
+        Self::require_role(&env, Role::Upgrader, &caller);
+        env.storage().instance().set(&Self::wasm_hash_key(pool_type), &new_hash);
+        env.events().publish((symbol_short!("wasm"), pool_type), new_hash);
     }
 
-    /// Deploy a new pool for a token pair, revert if already exists
+    /// Get the Wasm hash deployed for a pool type
+    pub fn get_pool_type_wasm_hash(env: Env, pool_type: PoolType) -> BytesN<32> {
+        env.storage().instance().get(&Self::wasm_hash_key(pool_type)).expect("not set")
+    }
+
+    /// Deploy a new pool for a token pair, revert if already exists. The pool address is
+    /// derived from the pair, see `predict_pool_address`
     pub fn create_pool(
         env: Env,
         token_a: Address,
         token_b: Address,
-        lp_token_name: String,
-        lp_token_symbol: String,
-        salt: BytesN<32>,
+        lp_token_name: Option<String>,
+        lp_token_symbol: Option<String>,
     ) -> Address {
+        Self::require_not_paused(&env);
         assert!(token_a != token_b, "Tokens must be different");
-        // Sort addresses for uniqueness
-        // let (token_0, token_1) = if token_a < token_b {
-        //     (token_a.clone(), token_b.clone())
-        // } else {
-        //     (token_b.clone(), token_a.clone())
-        // };
-        let key = DataKey::DeployedPools(token_a.clone(), token_b.clone());
-        if let Some(addr) = env.storage().instance().get::<_, Address>(&key) {
+        let key = Self::pair_key(PoolType::ConstantProduct, token_a.clone(), token_b.clone());
+        if env.storage().persistent().has(&key) {
             panic!("Pool already exists for pair");
         }
         let wasm_hash = env
@@ -61,6 +332,10 @@ impl PoolFactory {
             .get::<_, BytesN<32>>(&DataKey::PoolWasmHash)
             .expect("Wasm hash not set");
         // // Deploy contract
+        let tokens = vec![&env, token_a.clone(), token_b.clone()];
+        let token_info = Self::validate_tokens(&env, &tokens);
+        let (lp_token_name, lp_token_symbol) = Self::lp_token_names(&env, &token_info, lp_token_name, lp_token_symbol);
+        let salt = Self::pool_salt(&env, PoolType::ConstantProduct, token_a.clone(), token_b.clone());
         let pool_addr = env
             .deployer()
             .with_address(env.current_contract_address(), salt)
@@ -69,39 +344,610 @@ impl PoolFactory {
                 token_b,
                 lp_token_name,
                 lp_token_symbol,
+                env.current_contract_address(),
             ));
         // // Store mapping
-        env.storage().instance().set(&key, &pool_addr);
-        
-        // Track pool in global list
-        let mut all_pools = env.storage().instance().get::<_, Vec<Address>>(&DataKey::AllPools).unwrap_or(vec![&env]);
-        all_pools.push_back(pool_addr.clone());
-        env.storage().instance().set(&DataKey::AllPools, &all_pools);
-        
+        write_persistent(&env, &key, &pool_addr);
+        Self::register_pool(&env, &pool_addr, PoolType::ConstantProduct, &tokens, CONSTANT_PRODUCT_FEE_BPS);
+
         pool_addr
         // token_a
     }
 
-    /// Get the pool address for a token pair, or None if not exists
+    /// Deploy a constant product pool and make its first deposit from `creator` in one call, so the
+    /// opening price is the creator's `amount_a`/`amount_b`. The LP token name and symbol are generated
+    /// from the tokens' symbols. Returns the pool and the LP tokens minted to the creator
+    pub fn create_pool_with_liquidity(
+        env: Env,
+        creator: Address,
+        token_a: Address,
+        token_b: Address,
+        amount_a: i128,
+        amount_b: i128,
+    ) -> (Address, i128) {
+        creator.require_auth();
+        assert!(amount_a > 0 && amount_b > 0, "Amounts must be > 0");
+        let pool_addr = Self::create_pool(env.clone(), token_a.clone(), token_b.clone(), None, None);
+
+        // The factory deposits on the creator's behalf and hands over the LP tokens
+        let current = env.current_contract_address();
+        let expiration_ledger = env.ledger().sequence();
+        let token_a_client = token::Client::new(&env, &token_a);
+        let token_b_client = token::Client::new(&env, &token_b);
+        token_a_client.transfer(&creator, &current, &amount_a);
+        token_b_client.transfer(&creator, &current, &amount_b);
+        token_a_client.approve(&current, &pool_addr, &amount_a, &expiration_ledger);
+        token_b_client.approve(&current, &pool_addr, &amount_b, &expiration_ledger);
+
+        let lp_amount = ConstantProductPoolClient::new(&env, &pool_addr).add_liquidity(&current, &amount_a, &amount_b);
+        token::Client::new(&env, &pool_addr).transfer(&current, &creator, &lp_amount);
+
+        env.events().publish(
+            (symbol_short!("seeded"), pool_addr.clone()),
+            (creator, amount_a, amount_b, lp_amount),
+        );
+        (pool_addr, lp_amount)
+    }
+
+    /// Deploy a new StableSwap pool for a pegged token pair, revert if already exists
+    pub fn create_stable_pool(
+        env: Env,
+        token_a: Address,
+        token_b: Address,
+        lp_token_name: Option<String>,
+        lp_token_symbol: Option<String>,
+        amp: u32,
+    ) -> Address {
+        Self::require_not_paused(&env);
+        assert!(token_a != token_b, "Tokens must be different");
+        let key = Self::pair_key(PoolType::Stable, token_a.clone(), token_b.clone());
+        if env.storage().persistent().has(&key) {
+            panic!("Pool already exists for pair");
+        }
+        let wasm_hash = env
+            .storage()
+            .instance()
+            .get::<_, BytesN<32>>(&DataKey::PoolTypeWasmHash(PoolType::Stable))
+            .expect("Wasm hash not set");
+        // The factory administers the pool so amp ramps follow the factory admin
+        let tokens = vec![&env, token_a.clone(), token_b.clone()];
+        let token_info = Self::validate_tokens(&env, &tokens);
+        let (lp_token_name, lp_token_symbol) = Self::lp_token_names(&env, &token_info, lp_token_name, lp_token_symbol);
+        let salt = Self::pool_salt(&env, PoolType::Stable, token_a.clone(), token_b.clone());
+        let pool_addr = env
+            .deployer()
+            .with_address(env.current_contract_address(), salt)
+            .deploy_v2(wasm_hash, (
+                token_a,
+                token_b,
+                lp_token_name,
+                lp_token_symbol,
+                env.current_contract_address(),
+                amp,
+            ));
+        write_persistent(&env, &key, &pool_addr);
+        Self::register_pool(&env, &pool_addr, PoolType::Stable, &tokens, STABLE_FEE_BPS);
+
+        pool_addr
+    }
+
+    /// Deploy a new concentrated liquidity pool for a token pair, revert if already exists.
+    /// `sqrt_price` is the starting sqrt(token_b per token_a) as a Q64.64 number
+    pub fn create_concentrated_pool(
+        env: Env,
+        token_a: Address,
+        token_b: Address,
+        fee_bps: u32,
+        tick_spacing: u32,
+        sqrt_price: U256,
+    ) -> Address {
+        Self::require_not_paused(&env);
+        assert!(token_a != token_b, "Tokens must be different");
+        let key = Self::pair_key(PoolType::Concentrated, token_a.clone(), token_b.clone());
+        if env.storage().persistent().has(&key) {
+            panic!("Pool already exists for pair");
+        }
+        let wasm_hash = env
+            .storage()
+            .instance()
+            .get::<_, BytesN<32>>(&DataKey::PoolTypeWasmHash(PoolType::Concentrated))
+            .expect("Wasm hash not set");
+        let tokens = vec![&env, token_a.clone(), token_b.clone()];
+        Self::validate_tokens(&env, &tokens);
+        let salt = Self::pool_salt(&env, PoolType::Concentrated, token_a.clone(), token_b.clone());
+        let pool_addr = env
+            .deployer()
+            .with_address(env.current_contract_address(), salt)
+            .deploy_v2(wasm_hash, (
+                token_a,
+                token_b,
+                fee_bps,
+                tick_spacing,
+                sqrt_price,
+                env.current_contract_address(),
+            ));
+        write_persistent(&env, &key, &pool_addr);
+        Self::register_pool(&env, &pool_addr, PoolType::Concentrated, &tokens, fee_bps);
+
+        pool_addr
+    }
+
+    /// Deploy a new weighted pool. Several weighted pools may share tokens, since their weights can differ;
+    /// they are tracked in the global pool list with their type
+    pub fn create_weighted_pool(
+        env: Env,
+        tokens: Vec<Address>,
+        weights: Vec<u32>,
+        fee_bps: u32,
+        lp_token_name: Option<String>,
+        lp_token_symbol: Option<String>,
+        salt: BytesN<32>,
+    ) -> Address {
+        Self::require_not_paused(&env);
+        let wasm_hash = env
+            .storage()
+            .instance()
+            .get::<_, BytesN<32>>(&DataKey::PoolTypeWasmHash(PoolType::Weighted))
+            .expect("Wasm hash not set");
+        let token_info = Self::validate_tokens(&env, &tokens);
+        let (lp_token_name, lp_token_symbol) = Self::lp_token_names(&env, &token_info, lp_token_name, lp_token_symbol);
+        let pool_addr = env
+            .deployer()
+            .with_address(env.current_contract_address(), salt)
+            .deploy_v2(wasm_hash, (
+                tokens.clone(),
+                weights,
+                fee_bps,
+                lp_token_name,
+                lp_token_symbol,
+                env.current_contract_address(),
+            ));
+        Self::register_pool(&env, &pool_addr, PoolType::Weighted, &tokens, fee_bps);
+
+        pool_addr
+    }
+
+    /// Start ramping a stable pool's amplification coefficient (fee manager only)
+    pub fn ramp_stable_pool_amp(env: Env, caller: Address, pool: Address, future_amp: u32, future_ledger: u32) {
+        Self::require_role(&env, Role::FeeManager, &caller);
+        assert!(Self::get_pool_type(env.clone(), pool.clone()) == Some(PoolType::Stable), "Not a stable pool");
+        StablePoolClient::new(&env, &pool).ramp_amp(&future_amp, &future_ledger);
+        env.events().publish((symbol_short!("ramp_amp"), pool), (future_amp, future_ledger));
+    }
+
+    /// Stop a running amplification ramp on a stable pool (fee manager only)
+    pub fn stop_stable_pool_amp_ramp(env: Env, caller: Address, pool: Address) {
+        Self::require_role(&env, Role::FeeManager, &caller);
+        assert!(Self::get_pool_type(env.clone(), pool.clone()) == Some(PoolType::Stable), "Not a stable pool");
+        StablePoolClient::new(&env, &pool).stop_ramp_amp();
+        env.events().publish((symbol_short!("stop_ramp"), pool), ());
+    }
+
+    // Move a pool to the Wasm hash currently set for its type and migrate its storage. Returns None and
+    // leaves the pool as it is if its type isn't upgradable or its code can't be replaced, as for pools
+    // deployed before pools had an `upgrade` entrypoint and a recorded factory
+    fn upgrade_pool_code(env: &Env, pool: &Address) -> Option<u32> {
+        let pool_type = Self::get_pool_type(env.clone(), pool.clone()).expect("Pool not deployed by this factory");
+        let wasm_hash = Self::get_pool_type_wasm_hash(env.clone(), pool_type);
+        let pool_client = UpgradablePoolClient::new(env, pool);
+        if !matches!(pool_client.try_upgrade(&wasm_hash), Ok(Ok(()))) {
+            return None;
+        }
+        let schema_version = pool_client.migrate();
+        env.events().publish((symbol_short!("upgraded"), pool.clone()), (wasm_hash, schema_version));
+        Some(schema_version)
+    }
+
+    /// Upgrade a pool of any type to the Wasm hash set for its type (upgrader only).
+    /// Returns the pool's storage schema version after migration
+    pub fn upgrade_pool(env: Env, caller: Address, pool: Address) -> u32 {
+        Self::require_role(&env, Role::Upgrader, &caller);
+        Self::upgrade_pool_code(&env, &pool).expect("Pool not upgradable")
+    }
+
+    /// Upgrade a batch of pools, see `upgrade_pool` (upgrader only). Pools that can't be upgraded are
+    /// skipped; returns them and reports them in a `skipped` event
+    pub fn upgrade_pools(env: Env, caller: Address, pools: Vec<Address>) -> Vec<Address> {
+        Self::require_role(&env, Role::Upgrader, &caller);
+        let mut skipped = vec![&env];
+        for pool in pools.iter() {
+            if Self::upgrade_pool_code(&env, &pool).is_none() {
+                skipped.push_back(pool);
+            }
+        }
+        if !skipped.is_empty() {
+            env.events().publish((symbol_short!("skipped"),), skipped.clone());
+        }
+        skipped
+    }
+
+    fn read_pool_at(env: &Env, index: u32) -> Address {
+        read_persistent(env, &DataKey::PoolAt(index)).expect("Pool index out of range")
+    }
+
+    // Append a pool to the registry, returns its index
+    fn push_pool(env: &Env, pool_addr: &Address) -> u32 {
+        let index = Self::get_pool_count(env.clone());
+        write_persistent(env, &DataKey::PoolAt(index), pool_addr);
+        env.storage().instance().set(&DataKey::PoolCount, &(index + 1));
+        index
+    }
+
+    // Add a pool to the index of each of its tokens
+    fn index_pool_tokens(env: &Env, pool_addr: &Address, tokens: &Vec<Address>) {
+        for token in tokens.iter() {
+            let count = Self::get_token_pool_count(env.clone(), token.clone());
+            write_persistent(env, &DataKey::TokenPoolAt(token.clone(), count), pool_addr);
+            write_persistent(env, &DataKey::TokenPoolCount(token), &(count + 1));
+        }
+        write_persistent(env, &DataKey::PoolTokens(pool_addr.clone()), tokens);
+    }
+
+    // Track pool in the global registry and the token index, and announce it
+    fn register_pool(env: &Env, pool_addr: &Address, pool_type: PoolType, tokens: &Vec<Address>, fee_bps: u32) {
+        let index = Self::push_pool(env, pool_addr);
+        write_persistent(env, &DataKey::PoolTypeOf(pool_addr.clone()), &pool_type);
+        Self::index_pool_tokens(env, pool_addr, tokens);
+
+        env.events().publish(
+            (symbol_short!("created"), pool_addr.clone()),
+            PoolCreated {
+                pool: pool_addr.clone(),
+                pool_type,
+                tokens: tokens.clone(),
+                fee_bps,
+                index,
+            },
+        );
+    }
+
+    /// Replace the factory code (upgrader only). Run the `migrate_*` entrypoints of the new code
+    /// afterwards to convert the storage of older factories. Factories deployed before this entrypoint
+    /// existed can't be upgraded in place
+    pub fn upgrade(env: Env, caller: Address, new_wasm_hash: BytesN<32>) {
+        Self::require_role(&env, Role::Upgrader, &caller);
+        env.deployer().update_current_contract_wasm(new_wasm_hash.clone());
+        env.events().publish((symbol_short!("upgrade"), caller), new_wasm_hash);
+    }
+
+    /// Move the pool list kept in instance storage by older factories into the indexed registry
+    /// (admin only), along with the pool types and the pair keys of pools in the token index.
+    /// Pools without a recorded type are recorded as constant product pools. Returns how many
+    /// pools were moved into the registry
+    pub fn migrate_pool_registry(env: Env, admin_addr: Address) -> u32 {
+        Self::require_admin(&env, &admin_addr);
+        let legacy_pools: Vec<Address> = env.storage().instance().get(&DataKey::AllPools).unwrap_or(vec![&env]);
+        for pool in legacy_pools.iter() {
+            Self::push_pool(&env, &pool);
+        }
+        env.storage().instance().remove(&DataKey::AllPools);
+        // Older factories kept one type and pair key per pool in instance storage, so their
+        // registries are small enough to walk in one call
+        for i in 0..Self::get_pool_count(env.clone()) {
+            let pool = Self::read_pool_at(&env, i);
+            let type_key = DataKey::PoolTypeOf(pool.clone());
+            if let Some(pool_type) = env.storage().instance().get::<_, PoolType>(&type_key) {
+                env.storage().instance().remove(&type_key);
+                write_persistent(&env, &type_key, &pool_type);
+            }
+            let pool_type = Self::pool_type_or_legacy(&env, &pool);
+            if pool_type == PoolType::Weighted {
+                continue;
+            }
+            // Pools missing from the token index get their pair key moved by `migrate_pair_keys`
+            if let Some(tokens) = read_persistent::<_, Vec<Address>>(&env, &DataKey::PoolTokens(pool.clone())) {
+                Self::move_pair_key(&env, pool_type, tokens.get(0).unwrap(), tokens.get(1).unwrap(), &pool);
+            }
+        }
+        env.events().publish((symbol_short!("migrate"), symbol_short!("registry")), legacy_pools.len());
+        legacy_pools.len()
+    }
+
+    // Read a pool's tokens from the pool itself and add it to the token index
+    fn index_unindexed_pool(env: &Env, pool: &Address, pool_type: PoolType) -> Vec<Address> {
+        let tokens = match pool_type {
+            PoolType::Weighted => WeightedPoolClient::new(env, pool).get_tokens(),
+            _ => {
+                let pool_client = PairPoolClient::new(env, pool);
+                vec![env, pool_client.get_token_a(), pool_client.get_token_b()]
+            }
+        };
+        Self::index_pool_tokens(env, pool, &tokens);
+        tokens
+    }
+
+    /// Add pools deployed before the token index existed to it (admin only). Walks `limit` pools
+    /// of the registry from `start` and returns how many pools were indexed. Pools without a
+    /// recorded type are recorded as constant product pools
+    pub fn migrate_token_index(env: Env, admin_addr: Address, start: u32, limit: u32) -> u32 {
+        Self::require_admin(&env, &admin_addr);
+        let end = start.saturating_add(limit).min(Self::get_pool_count(env.clone()));
+        let mut migrated = 0;
+        for i in start..end {
+            let pool = Self::read_pool_at(&env, i);
+            if env.storage().persistent().has(&DataKey::PoolTokens(pool.clone())) {
+                continue;
+            }
+            let pool_type = Self::pool_type_or_legacy(&env, &pool);
+            Self::index_unindexed_pool(&env, &pool, pool_type);
+            migrated += 1;
+        }
+        env.events().publish((symbol_short!("migrate"), symbol_short!("tokens")), migrated);
+        migrated
+    }
+
+    // Type of a registry pool. Pools deployed before pool types were recorded have none and are all
+    // constant product pools, so they get recorded as such
+    fn pool_type_or_legacy(env: &Env, pool: &Address) -> PoolType {
+        Self::get_pool_type(env.clone(), pool.clone()).unwrap_or_else(|| {
+            write_persistent(env, &DataKey::PoolTypeOf(pool.clone()), &PoolType::ConstantProduct);
+            PoolType::ConstantProduct
+        })
+    }
+
+    // Move the pair keys older factories kept in instance storage, in either token order, to the
+    // canonical key in persistent storage, or register `pool` there if it has none. If a pair was
+    // deployed twice in both orders, the pool already behind the canonical key wins. Returns
+    // whether the canonical key was added
+    fn move_pair_key(env: &Env, pool_type: PoolType, token_a: Address, token_b: Address, pool: &Address) -> bool {
+        let (token_0, token_1) = sort_tokens(token_a, token_b);
+        let key = Self::ordered_pair_key(pool_type, token_0.clone(), token_1.clone());
+        let mut pair_pool = None;
+        for legacy_key in [key.clone(), Self::ordered_pair_key(pool_type, token_1, token_0)] {
+            if let Some(legacy_pool) = env.storage().instance().get::<_, Address>(&legacy_key) {
+                env.storage().instance().remove(&legacy_key);
+                pair_pool = pair_pool.or(Some(legacy_pool));
+            }
+        }
+        if env.storage().persistent().has(&key) {
+            return false;
+        }
+        write_persistent(env, &key, &pair_pool.unwrap_or(pool.clone()));
+        true
+    }
+
+    /// Register the canonical pair key of pools deployed before pairs were sorted (admin only).
+    /// Walks `limit` pools of the global list from `start` and returns how many keys were added.
+    /// Pools without a recorded type are recorded as constant product pools
+    pub fn migrate_pair_keys(env: Env, admin_addr: Address, start: u32, limit: u32) -> u32 {
+        Self::require_admin(&env, &admin_addr);
+        let end = start.saturating_add(limit).min(Self::get_pool_count(env.clone()));
+        let mut migrated = 0;
+        for i in start..end {
+            let pool = Self::read_pool_at(&env, i);
+            let pool_type = Self::pool_type_or_legacy(&env, &pool);
+            if pool_type == PoolType::Weighted {
+                continue;
+            }
+            let pool_client = PairPoolClient::new(&env, &pool);
+            if Self::move_pair_key(&env, pool_type, pool_client.get_token_a(), pool_client.get_token_b(), &pool) {
+                migrated += 1;
+            }
+        }
+        env.events().publish((symbol_short!("migrate"), symbol_short!("pairs")), migrated);
+        migrated
+    }
+
+    /// Get the pool address for a token pair in either order, or None if not exists
     pub fn get_pool(env: Env, token_a: Address, token_b: Address) -> Option<Address> {
-        // let (token_0, token_1) = if token_a < token_b {
-        //     (token_a, token_b)
-        // } else {
-        //     (token_b, token_a)
-        // };
-        let key = DataKey::DeployedPools(token_a, token_b);
-        env.storage().instance().get(&key)
+        read_persistent(&env, &Self::pair_key(PoolType::ConstantProduct, token_a, token_b))
+    }
+
+    /// Get the pool address of a given type for a token pair in either order, or None if not exists.
+    /// Weighted pools aren't unique per pair and are never returned here
+    pub fn get_pool_by_type(env: Env, pool_type: PoolType, token_a: Address, token_b: Address) -> Option<Address> {
+        match pool_type {
+            PoolType::Weighted => None,
+            _ => read_persistent(&env, &Self::pair_key(pool_type, token_a, token_b)),
+        }
+    }
+
+    /// Address the constant product pool for a token pair has or will have once created, in either order
+    pub fn predict_pool_address(env: Env, token_a: Address, token_b: Address) -> Address {
+        Self::predict_pool_address_by_type(env, PoolType::ConstantProduct, token_a, token_b)
+    }
+
+    /// Address the pool of a given type for a token pair has or will have once created, in either order.
+    /// Weighted pools are deployed with a caller-chosen salt and can't be predicted from a pair
+    pub fn predict_pool_address_by_type(env: Env, pool_type: PoolType, token_a: Address, token_b: Address) -> Address {
+        assert!(pool_type != PoolType::Weighted, "Weighted pools have no pair address");
+        assert!(token_a != token_b, "Tokens must be different");
+        let salt = Self::pool_salt(&env, pool_type, token_a, token_b);
+        env.deployer().with_address(env.current_contract_address(), salt).deployed_address()
+    }
+
+    pub fn get_admin(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Admin).expect("not set")
+    }
+
+    pub fn get_pending_admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::PendingAdmin)
+    }
+
+    /// Get the holder of a role, the admin when the role wasn't given to anyone
+    pub fn get_role(env: Env, role: Role) -> Address {
+        env.storage().instance().get(&DataKey::Role(role)).unwrap_or_else(|| Self::get_admin(env))
     }
 
-    /// Get all deployed pools
+    pub fn is_paused(env: Env) -> bool {
+        env.storage().instance().get(&DataKey::Paused).unwrap_or(false)
+    }
+
+    /// Get the type of a deployed pool, or None if it isn't registered
+    pub fn get_pool_type(env: Env, pool: Address) -> Option<PoolType> {
+        read_persistent(&env, &DataKey::PoolTypeOf(pool))
+    }
+
+    /// Get all deployed pools. Reads the whole registry, prefer `get_pools` for large registries
     pub fn get_all_pools(env: Env) -> Vec<Address> {
-        env.storage().instance().get(&DataKey::AllPools).unwrap_or(vec![&env])
+        let count = Self::get_pool_count(env.clone());
+        Self::get_pools(env, 0, count)
+    }
+
+    /// Get up to `limit` pools of the registry, starting at index `offset`
+    pub fn get_pools(env: Env, offset: u32, limit: u32) -> Vec<Address> {
+        let end = offset.saturating_add(limit).min(Self::get_pool_count(env.clone()));
+        let mut pools = vec![&env];
+        for i in offset..end {
+            pools.push_back(Self::read_pool_at(&env, i));
+        }
+        pools
+    }
+
+    /// Get the pool at a registry index
+    pub fn get_pool_at(env: Env, index: u32) -> Address {
+        Self::read_pool_at(&env, index)
+    }
+
+    // Current reserves and all-time volume of each token of a pool, in token order. Pools deployed
+    // before pools tracked volume report none
+    fn read_pool_stats(env: &Env, pool: &Address, pool_type: PoolType) -> (Vec<i128>, Vec<i128>) {
+        match pool_type {
+            PoolType::Weighted => {
+                let pool_client = WeightedPoolClient::new(env, pool);
+                (pool_client.get_balances(), pool_client.get_token_volumes())
+            }
+            _ => {
+                let pool_client = PairPoolClient::new(env, pool);
+                let (reserve_a, reserve_b) = pool_client.get_reserves();
+                let (volume_a, volume_b) = match pool_client.try_get_token_volumes() {
+                    Ok(Ok(volumes)) => volumes,
+                    _ => (0, 0),
+                };
+                (vec![env, reserve_a, reserve_b], vec![env, volume_a, volume_b])
+            }
+        }
+    }
+
+    // Put a pool at its place in the top-by-volume list of a token
+    fn update_top_pools(env: &Env, token: &Address, pool: &Address, volume: i128) {
+        let key = DataKey::TopPools(token.clone());
+        let mut top_pools: Vec<PoolVolume> = read_persistent(env, &key).unwrap_or(vec![env]);
+        if let Some(i) = top_pools.iter().position(|entry| entry.pool == *pool) {
+            top_pools.remove(i as u32);
+        }
+        let position = top_pools.iter().position(|entry| entry.volume < volume).map_or(top_pools.len(), |i| i as u32);
+        if position < MAX_TOP_POOLS && volume > 0 {
+            top_pools.insert(position, PoolVolume { pool: pool.clone(), volume });
+        }
+        while top_pools.len() > MAX_TOP_POOLS {
+            top_pools.pop_back();
+        }
+        write_persistent(env, &key, &top_pools);
+    }
+
+    // Add the change since the last sync to a per-token total
+    fn add_token_change(env: &Env, key: DataKey, change: i128) {
+        if change != 0 {
+            let total: i128 = read_persistent(env, &key).unwrap_or(0);
+            write_persistent(env, &key, &checked_add(total, change));
+        }
+    }
+
+    /// Read the reserves and volume of `limit` pools of the registry from `start` and fold the
+    /// changes since their last sync into the per-token totals. Pools missing from the token index
+    /// are indexed on the way. Anyone can call it; returns how many pools were synced
+    pub fn sync_pool_stats(env: Env, start: u32, limit: u32) -> u32 {
+        let end = start.saturating_add(limit).min(Self::get_pool_count(env.clone()));
+        let ledger = env.ledger().sequence();
+        let mut synced = 0;
+        for i in start..end {
+            let pool = Self::read_pool_at(&env, i);
+            let pool_type = Self::pool_type_or_legacy(&env, &pool);
+            let mut tokens = Self::get_pool_tokens(env.clone(), pool.clone());
+            if tokens.is_empty() {
+                // Not in the token index yet, index it now rather than leaving it out of the totals
+                tokens = Self::index_unindexed_pool(&env, &pool, pool_type);
+            }
+            let (reserves, volumes) = Self::read_pool_stats(&env, &pool, pool_type);
+            let previous = Self::get_pool_stats(env.clone(), pool.clone());
+
+            for (j, token) in tokens.iter().enumerate() {
+                let j = j as u32;
+                let (old_reserve, old_volume) = previous.as_ref().map_or((0, 0), |stats| {
+                    (stats.reserves.get(j).unwrap_or(0), stats.volumes.get(j).unwrap_or(0))
+                });
+                let volume = volumes.get(j).unwrap_or(0);
+                Self::add_token_change(&env, DataKey::TokenTvl(token.clone()), checked_sub(reserves.get(j).unwrap_or(0), old_reserve));
+                Self::add_token_change(&env, DataKey::TokenVolume(token.clone()), checked_sub(volume, old_volume));
+                if volume != old_volume {
+                    Self::update_top_pools(&env, &token, &pool, volume);
+                }
+            }
+
+            write_persistent(&env, &DataKey::PoolStats(pool), &PoolStats { reserves, volumes, last_sync_ledger: ledger });
+            synced += 1;
+        }
+        env.storage().instance().set(&DataKey::LastSyncLedger, &ledger);
+        env.events().publish((symbol_short!("synced"),), (start, synced));
+        synced
+    }
+
+    /// Get the reserves and volume of a pool as of its last sync, or None if never synced
+    pub fn get_pool_stats(env: Env, pool: Address) -> Option<PoolStats> {
+        read_persistent(&env, &DataKey::PoolStats(pool))
+    }
+
+    /// Get the amount of a token held by all synced pools
+    pub fn get_token_tvl(env: Env, token: Address) -> i128 {
+        read_persistent(&env, &DataKey::TokenTvl(token)).unwrap_or(0)
+    }
+
+    /// Get the swap volume of a token over all synced pools, in the token's units
+    pub fn get_token_volume(env: Env, token: Address) -> i128 {
+        read_persistent(&env, &DataKey::TokenVolume(token)).unwrap_or(0)
+    }
+
+    /// Get the synced pools with the largest volume of a token, largest first
+    pub fn get_top_pools_by_volume(env: Env, token: Address) -> Vec<PoolVolume> {
+        read_persistent(&env, &DataKey::TopPools(token)).unwrap_or(vec![&env])
+    }
+
+    /// Get the pool count and last sync ledger of the whole protocol in one call
+    pub fn get_protocol_stats(env: Env) -> ProtocolStats {
+        ProtocolStats {
+            pool_count: Self::get_pool_count(env.clone()),
+            last_sync_ledger: env.storage().instance().get(&DataKey::LastSyncLedger).unwrap_or(0),
+        }
     }
 
     /// Get total number of pools
     pub fn get_pool_count(env: Env) -> u32 {
-        let all_pools = Self::get_all_pools(env);
-        all_pools.len() as u32
+        env.storage().instance().get(&DataKey::PoolCount).unwrap_or(0)
+    }
+
+    /// Get the SEP-41 metadata recorded for a token, or None if no pool with it was created
+    pub fn get_token_info(env: Env, token: Address) -> Option<TokenInfo> {
+        read_persistent(&env, &DataKey::TokenInfo(token))
+    }
+
+    /// Get the metadata of each token of a pool, in the pool's token order
+    pub fn get_pool_token_info(env: Env, pool: Address) -> Vec<TokenInfo> {
+        let mut token_info = vec![&env];
+        for token in Self::get_pool_tokens(env.clone(), pool).iter() {
+            token_info.push_back(Self::get_token_info(env.clone(), token).expect("Token info not recorded"));
+        }
+        token_info
+    }
+
+    /// Get the tokens of a pool, or an empty list if it isn't in the token index
+    pub fn get_pool_tokens(env: Env, pool: Address) -> Vec<Address> {
+        read_persistent(&env, &DataKey::PoolTokens(pool)).unwrap_or(vec![&env])
+    }
+
+    /// Get up to `limit` pools containing `token`, of any type, starting at index `offset`
+    pub fn get_pools_for_token(env: Env, token: Address, offset: u32, limit: u32) -> Vec<Address> {
+        let end = offset.saturating_add(limit).min(Self::get_token_pool_count(env.clone(), token.clone()));
+        let mut pools = vec![&env];
+        for i in offset..end {
+            pools.push_back(read_persistent(&env, &DataKey::TokenPoolAt(token.clone(), i)).unwrap());
+        }
+        pools
+    }
+
+    /// Get the number of pools containing `token`
+    pub fn get_token_pool_count(env: Env, token: Address) -> u32 {
+        read_persistent(&env, &DataKey::TokenPoolCount(token)).unwrap_or(0)
     }
 }
 
@@ -6,15 +6,15 @@ extern crate std;
 use super::*;
 use soroban_sdk::{
     // token::{self, TokenClient},
-    vec, Env, String, Address, Symbol, FromVal, IntoVal,
-    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
-    BytesN,
+    Env, String, Address, FromVal,
+    testutils::{Address as _, Events, Ledger},
+    IntoVal, symbol_short,
+    BytesN, U256,
 };
 
 use crate::PoolFactory;
 use crate::PoolFactoryClient;
 
-use pool::LiquidityPool;
 use pool::LiquidityPoolClient;
 
 use ::token::Token;
@@ -24,6 +24,22 @@ mod contract {
     soroban_sdk::contractimport!(file = "../../target/wasm32v1-none/release/pool.wasm");
 }
 
+mod stable_contract {
+    soroban_sdk::contractimport!(file = "../../target/wasm32v1-none/release/stablepool.wasm");
+}
+
+mod weighted_contract {
+    soroban_sdk::contractimport!(file = "../../target/wasm32v1-none/release/weightedpool.wasm");
+}
+
+mod factory_contract {
+    soroban_sdk::contractimport!(file = "../../target/wasm32v1-none/release/poolfactory.wasm");
+}
+
+mod concentrated_contract {
+    soroban_sdk::contractimport!(file = "../../target/wasm32v1-none/release/concentratedpool.wasm");
+}
+
 
 // Helper function to create a test token
 fn create_token<'a>(e: &Env, admin: &Address) -> TokenClient<'a> {
@@ -39,19 +55,6 @@ fn create_token<'a>(e: &Env, admin: &Address) -> TokenClient<'a> {
     TokenClient::new(e, &token_contract)
 }
 
-fn deploy_pool<'a>(e: &Env, token_a: &TokenClient<'a>, token_b: &TokenClient<'a>) -> LiquidityPoolClient<'a> {
-    let contract_id = e.register(
-        LiquidityPool,
-        (
-            &token_a.address,
-            &token_b.address,
-            String::from_val(e, &"LPToken"),
-            String::from_val(e, &"LP"),
-        ),
-    );
-    LiquidityPoolClient::new(e, &contract_id)
-}
-
 fn deploy_poolfactory<'a>(e: &Env, admin: &Address) -> PoolFactoryClient<'a> {
     let contract_id = e.register(PoolFactory, (admin,));
     PoolFactoryClient::new(e, &contract_id)
@@ -69,16 +72,14 @@ fn test_pool_factory() {
 
     let token_a = create_token(&env, &user);
     let token_b = create_token(&env, &user);
-    let salt = BytesN::from_array(&env, &[0; 32]);
     let wasm_hash = env.deployer().upload_contract_wasm(contract::WASM);
     poolfactory.update_pool_wasm_hash(&user, &wasm_hash);
 
     poolfactory.create_pool(
         &token_a.address,
         &token_b.address,
-        &String::from_val(&env, &"LPToken"),
-        &String::from_val(&env, &"LP"),
-        &salt,
+        &Some(String::from_val(&env, &"LPToken")),
+        &Some(String::from_val(&env, &"LP")),
     );
     let pool_addr = poolfactory.get_pool(&token_a.address, &token_b.address);
     assert!(pool_addr.is_some());
@@ -88,3 +89,868 @@ fn test_pool_factory() {
       assert_eq!(client.get_token_a(), token_a.address);
       assert_eq!(client.get_token_b(), token_b.address);
 }
+
+fn create_lp_pool(e: &Env, poolfactory: &PoolFactoryClient, token_a: &Address, token_b: &Address) -> Address {
+    poolfactory.create_pool(
+        token_a,
+        token_b,
+        &Some(String::from_val(e, &"LPToken")),
+        &Some(String::from_val(e, &"LP")),
+    )
+}
+
+#[test]
+fn test_get_pool_either_order() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    poolfactory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+
+    let pool_addr = create_lp_pool(&env, &poolfactory, &token_b.address, &token_a.address);
+    assert_eq!(poolfactory.get_pool(&token_a.address, &token_b.address), Some(pool_addr.clone()));
+    assert_eq!(poolfactory.get_pool(&token_b.address, &token_a.address), Some(pool_addr.clone()));
+    assert_eq!(poolfactory.get_pool_by_type(&PoolType::ConstantProduct, &token_a.address, &token_b.address), Some(pool_addr));
+}
+
+#[test]
+fn test_predict_pool_address() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    poolfactory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+
+    let predicted = poolfactory.predict_pool_address(&token_a.address, &token_b.address);
+    assert_eq!(poolfactory.predict_pool_address(&token_b.address, &token_a.address), predicted);
+    assert!(poolfactory.predict_pool_address_by_type(&PoolType::Stable, &token_a.address, &token_b.address) != predicted);
+
+    let pool_addr = create_lp_pool(&env, &poolfactory, &token_b.address, &token_a.address);
+    assert_eq!(pool_addr, predicted);
+}
+
+#[test]
+#[should_panic(expected = "Pool already exists for pair")]
+fn test_create_pool_reversed_pair_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    poolfactory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+
+    create_lp_pool(&env, &poolfactory, &token_a.address, &token_b.address);
+    create_lp_pool(&env, &poolfactory, &token_b.address, &token_a.address);
+}
+
+// The constant product pool's storage key for its factory, which pools deployed by older factories lack
+#[contracttype]
+enum LegacyPoolDataKey {
+    Factory,
+}
+
+// Deploy a pool outside the factory and record it the way older factories did: in the registry, under
+// an unsorted pair key and without a pool type
+fn register_legacy_pool(e: &Env, poolfactory: &PoolFactoryClient, token_a: &Address, token_b: &Address) -> Address {
+    let pool_addr = e.register(
+        contract::WASM,
+        (
+            token_a,
+            token_b,
+            String::from_val(e, &"LPToken"),
+            String::from_val(e, &"LP"),
+            Address::generate(e),
+        ),
+    );
+    let (token_0, token_1) = sort_tokens(token_a.clone(), token_b.clone());
+    e.as_contract(&poolfactory.address, || {
+        let index: u32 = e.storage().instance().get(&DataKey::PoolCount).unwrap_or(0);
+        e.storage().persistent().set(&DataKey::PoolAt(index), &pool_addr);
+        e.storage().instance().set(&DataKey::PoolCount, &(index + 1));
+        e.storage().instance().set(&DataKey::DeployedPools(token_1, token_0), &pool_addr);
+    });
+    pool_addr
+}
+
+#[test]
+fn test_migrate_pair_keys() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    let pool_addr = register_legacy_pool(&env, &poolfactory, &token_a.address, &token_b.address);
+    assert_eq!(poolfactory.get_pool_type(&pool_addr), None);
+    assert_eq!(poolfactory.get_pool(&token_a.address, &token_b.address), None);
+
+    assert_eq!(poolfactory.migrate_pair_keys(&admin, &0, &10), 1);
+    assert_eq!(poolfactory.get_pool(&token_a.address, &token_b.address), Some(pool_addr.clone()));
+    assert_eq!(poolfactory.get_pool(&token_b.address, &token_a.address), Some(pool_addr.clone()));
+    assert_eq!(poolfactory.get_pool_type(&pool_addr), Some(PoolType::ConstantProduct));
+    env.as_contract(&poolfactory.address, || {
+        let (token_0, token_1) = sort_tokens(token_a.address.clone(), token_b.address.clone());
+        assert!(!env.storage().instance().has(&DataKey::DeployedPools(token_1, token_0)));
+    });
+
+    // Running it again has nothing left to do
+    assert_eq!(poolfactory.migrate_pair_keys(&admin, &0, &10), 0);
+}
+
+#[test]
+fn test_create_stable_pool() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.min_persistent_entry_ttl = 500_000;
+        li.max_entry_ttl = 1_000_000;
+    });
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+
+    let pool_hash = env.deployer().upload_contract_wasm(contract::WASM);
+    let stable_hash = env.deployer().upload_contract_wasm(stable_contract::WASM);
+    poolfactory.update_pool_wasm_hash(&admin, &pool_hash);
+    poolfactory.update_pool_type_wasm_hash(&admin, &PoolType::Stable, &stable_hash);
+    assert_eq!(poolfactory.get_pool_type_wasm_hash(&PoolType::Stable), stable_hash);
+    assert_eq!(poolfactory.get_pool_type_wasm_hash(&PoolType::ConstantProduct), pool_hash);
+
+    // A stable pool and a constant product pool can coexist for the same pair
+    let stable_addr = poolfactory.create_stable_pool(
+        &token_a.address,
+        &token_b.address,
+        &Some(String::from_val(&env, &"StableLPToken")),
+        &Some(String::from_val(&env, &"SLP")),
+        &100,
+    );
+    let pool_addr = poolfactory.create_pool(
+        &token_a.address,
+        &token_b.address,
+        &Some(String::from_val(&env, &"LPToken")),
+        &Some(String::from_val(&env, &"LP")),
+    );
+
+    assert_eq!(poolfactory.get_pool_by_type(&PoolType::Stable, &token_a.address, &token_b.address), Some(stable_addr.clone()));
+    assert_eq!(poolfactory.get_pool_by_type(&PoolType::ConstantProduct, &token_a.address, &token_b.address), Some(pool_addr.clone()));
+    assert_eq!(poolfactory.predict_pool_address_by_type(&PoolType::Stable, &token_b.address, &token_a.address), stable_addr);
+    assert_eq!(poolfactory.get_pool_type(&stable_addr), Some(PoolType::Stable));
+    assert_eq!(poolfactory.get_pool_type(&pool_addr), Some(PoolType::ConstantProduct));
+    assert_eq!(poolfactory.get_pool_count(), 2);
+
+    let stable_pool = stable_contract::Client::new(&env, &stable_addr);
+    assert_eq!(stable_pool.get_token_a(), token_a.address);
+    assert_eq!(stable_pool.get_admin(), poolfactory.address);
+    assert_eq!(stable_pool.get_amp(), 100);
+
+    // Amp ramps go through the factory admin
+    let start = env.ledger().sequence() + 20_000;
+    env.ledger().set_sequence_number(start);
+    poolfactory.ramp_stable_pool_amp(&admin, &stable_addr, &200, &(start + 20_000));
+    env.ledger().set_sequence_number(start + 20_000);
+    assert_eq!(stable_pool.get_amp(), 200);
+}
+
+#[test]
+fn test_create_concentrated_pool() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+
+    let concentrated_hash = env.deployer().upload_contract_wasm(concentrated_contract::WASM);
+    poolfactory.update_pool_type_wasm_hash(&admin, &PoolType::Concentrated, &concentrated_hash);
+
+    let sqrt_price = U256::from_u32(&env, 1).shl(64);
+    let pool_addr = poolfactory.create_concentrated_pool(
+        &token_a.address,
+        &token_b.address,
+        &30,
+        &60,
+        &sqrt_price,
+    );
+
+    assert_eq!(poolfactory.get_pool_by_type(&PoolType::Concentrated, &token_a.address, &token_b.address), Some(pool_addr.clone()));
+    assert_eq!(poolfactory.get_pool_type(&pool_addr), Some(PoolType::Concentrated));
+    assert_eq!(poolfactory.get_all_pools(), vec![&env, pool_addr.clone()]);
+
+    let pool = concentrated_contract::Client::new(&env, &pool_addr);
+    assert_eq!(pool.get_token_a(), token_a.address);
+    assert_eq!(pool.get_fee_bps(), 30);
+    assert_eq!(pool.get_tick_spacing(), 60);
+    assert_eq!(pool.get_sqrt_price(), sqrt_price);
+    assert_eq!(pool.get_tick(), 0);
+}
+
+#[test]
+fn test_create_weighted_pool() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    let token_c = create_token(&env, &admin);
+
+    let weighted_hash = env.deployer().upload_contract_wasm(weighted_contract::WASM);
+    poolfactory.update_pool_type_wasm_hash(&admin, &PoolType::Weighted, &weighted_hash);
+
+    let tokens = vec![&env, token_a.address.clone(), token_b.address.clone(), token_c.address.clone()];
+    let pool_addr = poolfactory.create_weighted_pool(
+        &tokens,
+        &vec![&env, 6000, 2000, 2000],
+        &30,
+        &Some(String::from_val(&env, &"WeightedLPToken")),
+        &Some(String::from_val(&env, &"WLP")),
+        &BytesN::from_array(&env, &[4; 32]),
+    );
+
+    assert_eq!(poolfactory.get_pool_type(&pool_addr), Some(PoolType::Weighted));
+    assert_eq!(poolfactory.get_pool_count(), 1);
+
+    let pool = weighted_contract::Client::new(&env, &pool_addr);
+    assert_eq!(pool.get_tokens(), tokens);
+    assert_eq!(pool.get_weights(), vec![&env, 6000, 2000, 2000]);
+    assert_eq!(pool.get_fee_bps(), 30);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized")]
+fn test_ramp_stable_pool_amp_non_admin_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let attacker = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    poolfactory.ramp_stable_pool_amp(&attacker, &Address::generate(&env), &200, &100_000);
+}
+
+#[test]
+fn test_get_pools_paginated() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    let token_c = create_token(&env, &admin);
+    poolfactory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+
+    let pool_ab = create_lp_pool(&env, &poolfactory, &token_a.address, &token_b.address);
+    let pool_ac = create_lp_pool(&env, &poolfactory, &token_a.address, &token_c.address);
+    let pool_bc = create_lp_pool(&env, &poolfactory, &token_b.address, &token_c.address);
+
+    assert_eq!(poolfactory.get_pool_count(), 3);
+    assert_eq!(poolfactory.get_pool_at(&1), pool_ac.clone());
+    assert_eq!(poolfactory.get_pools(&0, &2), vec![&env, pool_ab.clone(), pool_ac.clone()]);
+    assert_eq!(poolfactory.get_pools(&1, &10), vec![&env, pool_ac.clone(), pool_bc.clone()]);
+    assert_eq!(poolfactory.get_pools(&3, &10), vec![&env]);
+    assert_eq!(poolfactory.get_all_pools(), vec![&env, pool_ab, pool_ac, pool_bc]);
+}
+
+#[test]
+fn test_migrate_pool_registry() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let legacy_pools = vec![&env, Address::generate(&env), Address::generate(&env)];
+    env.as_contract(&poolfactory.address, || {
+        env.storage().instance().set(&DataKey::AllPools, &legacy_pools);
+    });
+    assert_eq!(poolfactory.get_pool_count(), 0);
+
+    assert_eq!(poolfactory.migrate_pool_registry(&admin), 2);
+    assert_eq!(poolfactory.get_pool_count(), 2);
+    assert_eq!(poolfactory.get_all_pools(), legacy_pools);
+    // Older factories only deployed constant product pools and didn't record a type
+    assert_eq!(poolfactory.get_pool_type(&legacy_pools.get(1).unwrap()), Some(PoolType::ConstantProduct));
+    assert_eq!(poolfactory.migrate_pool_registry(&admin), 0);
+}
+
+#[test]
+fn test_migrate_pool_registry_moves_instance_keys() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    poolfactory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+    let pool_addr = create_lp_pool(&env, &poolfactory, &token_a.address, &token_b.address);
+
+    // Storage as written by factories that kept pool types and pair keys in instance storage
+    let (token_0, token_1) = sort_tokens(token_a.address.clone(), token_b.address.clone());
+    let pair_key = DataKey::DeployedPools(token_0, token_1);
+    let type_key = DataKey::PoolTypeOf(pool_addr.clone());
+    env.as_contract(&poolfactory.address, || {
+        env.storage().persistent().remove(&pair_key);
+        env.storage().persistent().remove(&type_key);
+        env.storage().instance().set(&pair_key, &pool_addr);
+        env.storage().instance().set(&type_key, &PoolType::ConstantProduct);
+    });
+    assert_eq!(poolfactory.get_pool(&token_a.address, &token_b.address), None);
+
+    assert_eq!(poolfactory.migrate_pool_registry(&admin), 0);
+    assert_eq!(poolfactory.get_pool(&token_a.address, &token_b.address), Some(pool_addr.clone()));
+    assert_eq!(poolfactory.get_pool_type(&pool_addr), Some(PoolType::ConstantProduct));
+    env.as_contract(&poolfactory.address, || {
+        assert!(!env.storage().instance().has(&pair_key));
+        assert!(!env.storage().instance().has(&type_key));
+        assert!(env.storage().persistent().has(&pair_key));
+    });
+}
+
+#[test]
+fn test_upgrade_factory() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let upgrader = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    poolfactory.set_role(&admin, &Role::Upgrader, &Some(upgrader.clone()));
+    let new_wasm_hash = env.deployer().upload_contract_wasm(factory_contract::WASM);
+
+    poolfactory.upgrade(&upgrader, &new_wasm_hash);
+    let (contract, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(contract, poolfactory.address);
+    assert_eq!(topics, (symbol_short!("upgrade"), upgrader.clone()).into_val(&env));
+
+    // The new code runs on the existing storage
+    assert_eq!(poolfactory.get_admin(), admin);
+    assert_eq!(poolfactory.get_role(&Role::Upgrader), upgrader);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized")]
+fn test_upgrade_factory_by_non_upgrader_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    poolfactory.set_role(&admin, &Role::Upgrader, &Some(Address::generate(&env)));
+    poolfactory.upgrade(&admin, &env.deployer().upload_contract_wasm(factory_contract::WASM));
+}
+
+#[test]
+fn test_get_pools_for_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    let token_c = create_token(&env, &admin);
+    poolfactory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+    poolfactory.update_pool_type_wasm_hash(&admin, &PoolType::Weighted, &env.deployer().upload_contract_wasm(weighted_contract::WASM));
+
+    let pool_ab = create_lp_pool(&env, &poolfactory, &token_a.address, &token_b.address);
+    let pool_bc = create_lp_pool(&env, &poolfactory, &token_b.address, &token_c.address);
+    let tokens = vec![&env, token_a.address.clone(), token_b.address.clone(), token_c.address.clone()];
+    let weighted = poolfactory.create_weighted_pool(
+        &tokens,
+        &vec![&env, 6000, 2000, 2000],
+        &30,
+        &Some(String::from_val(&env, &"WeightedLPToken")),
+        &Some(String::from_val(&env, &"WLP")),
+        &BytesN::from_array(&env, &[4; 32]),
+    );
+
+    assert_eq!(poolfactory.get_token_pool_count(&token_a.address), 2);
+    assert_eq!(poolfactory.get_pools_for_token(&token_a.address, &0, &10), vec![&env, pool_ab.clone(), weighted.clone()]);
+    assert_eq!(poolfactory.get_pools_for_token(&token_b.address, &1, &1), vec![&env, pool_bc.clone()]);
+    assert_eq!(poolfactory.get_pools_for_token(&token_c.address, &0, &10), vec![&env, pool_bc.clone(), weighted.clone()]);
+    assert_eq!(poolfactory.get_pool_tokens(&weighted), tokens);
+    assert_eq!(poolfactory.get_pool_tokens(&pool_bc), vec![&env, token_b.address.clone(), token_c.address.clone()]);
+}
+
+#[test]
+fn test_migrate_token_index() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    let pool_addr = register_legacy_pool(&env, &poolfactory, &token_a.address, &token_b.address);
+    assert_eq!(poolfactory.get_token_pool_count(&token_a.address), 0);
+
+    assert_eq!(poolfactory.migrate_token_index(&admin, &0, &10), 1);
+    assert_eq!(poolfactory.get_pools_for_token(&token_a.address, &0, &10), vec![&env, pool_addr.clone()]);
+    assert_eq!(poolfactory.get_pools_for_token(&token_b.address, &0, &10), vec![&env, pool_addr.clone()]);
+    assert_eq!(poolfactory.get_pool_type(&pool_addr), Some(PoolType::ConstantProduct));
+    assert_eq!(poolfactory.migrate_token_index(&admin, &0, &10), 0);
+}
+
+#[test]
+fn test_create_pool_emits_event() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    let wasm_hash = env.deployer().upload_contract_wasm(contract::WASM);
+    poolfactory.update_pool_wasm_hash(&admin, &wasm_hash);
+
+    let (contract, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(contract, poolfactory.address);
+    assert_eq!(topics, (symbol_short!("wasm"), PoolType::ConstantProduct).into_val(&env));
+    assert_eq!(BytesN::<32>::from_val(&env, &data), wasm_hash);
+
+    let pool_addr = create_lp_pool(&env, &poolfactory, &token_a.address, &token_b.address);
+    let (contract, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(contract, poolfactory.address);
+    assert_eq!(topics, (symbol_short!("created"), pool_addr.clone()).into_val(&env));
+    assert_eq!(
+        PoolCreated::from_val(&env, &data),
+        PoolCreated {
+            pool: pool_addr,
+            pool_type: PoolType::ConstantProduct,
+            tokens: vec![&env, token_a.address.clone(), token_b.address.clone()],
+            fee_bps: 30,
+            index: 0,
+        }
+    );
+}
+
+#[test]
+fn test_two_step_admin_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let new_admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    poolfactory.propose_admin(&admin, &new_admin);
+    assert_eq!(poolfactory.get_admin(), admin);
+    assert_eq!(poolfactory.get_pending_admin(), Some(new_admin.clone()));
+
+    poolfactory.accept_admin(&new_admin);
+    let (_, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("admin"), admin.clone()).into_val(&env));
+    assert_eq!(poolfactory.get_admin(), new_admin);
+    assert_eq!(poolfactory.get_pending_admin(), None);
+    assert_eq!(poolfactory.get_role(&Role::Upgrader), new_admin);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized")]
+fn test_accept_admin_by_other_address_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    poolfactory.propose_admin(&admin, &Address::generate(&env));
+    poolfactory.accept_admin(&Address::generate(&env));
+}
+
+#[test]
+fn test_roles() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let upgrader = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    poolfactory.set_role(&admin, &Role::Upgrader, &Some(upgrader.clone()));
+    assert_eq!(poolfactory.get_role(&Role::Upgrader), upgrader);
+    assert_eq!(poolfactory.get_role(&Role::Pauser), admin);
+
+    let wasm_hash = env.deployer().upload_contract_wasm(contract::WASM);
+    poolfactory.update_pool_wasm_hash(&upgrader, &wasm_hash);
+    assert_eq!(poolfactory.get_pool_wasm_hash(), wasm_hash);
+
+    // Handing the role back to the admin
+    poolfactory.set_role(&admin, &Role::Upgrader, &None);
+    assert_eq!(poolfactory.get_role(&Role::Upgrader), admin);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized")]
+fn test_admin_without_role_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    poolfactory.set_role(&admin, &Role::Upgrader, &Some(Address::generate(&env)));
+    poolfactory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+}
+
+#[test]
+#[should_panic(expected = "Pool creation paused")]
+fn test_create_pool_while_paused_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let guardian = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    poolfactory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+    poolfactory.set_role(&admin, &Role::Pauser, &Some(guardian.clone()));
+
+    poolfactory.set_paused(&guardian, &true);
+    assert!(poolfactory.is_paused());
+    create_lp_pool(&env, &poolfactory, &token_a.address, &token_b.address);
+}
+
+#[test]
+fn test_upgrade_pools() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.min_persistent_entry_ttl = 500_000;
+        li.max_entry_ttl = 1_000_000;
+    });
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    poolfactory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+    poolfactory.update_pool_type_wasm_hash(&admin, &PoolType::Stable, &env.deployer().upload_contract_wasm(stable_contract::WASM));
+
+    let pool_addr = create_lp_pool(&env, &poolfactory, &token_a.address, &token_b.address);
+    let stable_addr = poolfactory.create_stable_pool(
+        &token_a.address,
+        &token_b.address,
+        &Some(String::from_val(&env, &"StableLPToken")),
+        &Some(String::from_val(&env, &"SLP")),
+        &100,
+    );
+    let pool = LiquidityPoolClient::new(&env, &pool_addr);
+    assert_eq!(pool.get_factory(), poolfactory.address);
+    assert_eq!(pool.schema_version(), 3);
+
+    assert_eq!(poolfactory.upgrade_pool(&admin, &pool_addr), 3);
+    let (contract, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(contract, poolfactory.address);
+    assert_eq!(topics, (symbol_short!("upgraded"), pool_addr.clone()).into_val(&env));
+
+    // A pool without a recorded factory can't be upgraded and is skipped, not trapped on
+    let token_c = create_token(&env, &admin);
+    let legacy_addr = register_legacy_pool(&env, &poolfactory, &token_a.address, &token_c.address);
+    poolfactory.migrate_pair_keys(&admin, &0, &10);
+    env.as_contract(&legacy_addr, || {
+        env.storage().instance().remove(&LegacyPoolDataKey::Factory);
+    });
+
+    let skipped = poolfactory.upgrade_pools(&admin, &vec![&env, pool_addr.clone(), legacy_addr.clone(), stable_addr.clone()]);
+    assert_eq!(skipped, vec![&env, legacy_addr.clone()]);
+    assert_eq!(stable_contract::Client::new(&env, &stable_addr).schema_version(), 1);
+    assert_eq!(pool.get_token_a(), token_a.address);
+    assert_eq!(LiquidityPoolClient::new(&env, &legacy_addr).get_token_b(), token_c.address);
+}
+
+#[test]
+fn test_upgrade_concentrated_and_weighted_pools() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    poolfactory.update_pool_type_wasm_hash(&admin, &PoolType::Concentrated, &env.deployer().upload_contract_wasm(concentrated_contract::WASM));
+    poolfactory.update_pool_type_wasm_hash(&admin, &PoolType::Weighted, &env.deployer().upload_contract_wasm(weighted_contract::WASM));
+
+    let concentrated_addr = poolfactory.create_concentrated_pool(
+        &token_a.address,
+        &token_b.address,
+        &30,
+        &60,
+        &U256::from_u32(&env, 1).shl(64),
+    );
+    let weighted_addr = poolfactory.create_weighted_pool(
+        &vec![&env, token_a.address.clone(), token_b.address.clone()],
+        &vec![&env, 8000, 2000],
+        &30,
+        &None,
+        &None,
+        &BytesN::from_array(&env, &[4; 32]),
+    );
+    let concentrated = concentrated_contract::Client::new(&env, &concentrated_addr);
+    let weighted = weighted_contract::Client::new(&env, &weighted_addr);
+    assert_eq!(concentrated.get_factory(), poolfactory.address);
+    assert_eq!(weighted.get_factory(), poolfactory.address);
+
+    assert_eq!(poolfactory.upgrade_pool(&admin, &concentrated_addr), 1);
+    let (_, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("upgraded"), concentrated_addr.clone()).into_val(&env));
+
+    let skipped = poolfactory.upgrade_pools(&admin, &vec![&env, concentrated_addr.clone(), weighted_addr.clone()]);
+    assert!(skipped.is_empty());
+    assert_eq!(concentrated.schema_version(), 1);
+    assert_eq!(weighted.schema_version(), 1);
+    assert_eq!(weighted.get_tokens(), vec![&env, token_a.address.clone(), token_b.address.clone()]);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized")]
+fn test_upgrade_pool_by_non_upgrader_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    poolfactory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+    let pool_addr = create_lp_pool(&env, &poolfactory, &token_a.address, &token_b.address);
+    poolfactory.upgrade_pool(&Address::generate(&env), &pool_addr);
+}
+
+#[test]
+fn test_create_pool_with_liquidity() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    token_a.mint(&creator, &10_000_000_000);
+    token_b.mint(&creator, &40_000_000_000);
+    poolfactory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+
+    let (pool_addr, lp_amount) = poolfactory.create_pool_with_liquidity(
+        &creator,
+        &token_a.address,
+        &token_b.address,
+        &10_000_000_000,
+        &40_000_000_000,
+    );
+    assert_eq!(poolfactory.get_pool(&token_b.address, &token_a.address), Some(pool_addr.clone()));
+
+    let pool = LiquidityPoolClient::new(&env, &pool_addr);
+    assert_eq!(pool.symbol(), String::from_str(&env, "CDX-LP"));
+    assert_eq!(pool.get_reserves(), (10_000_000_000, 40_000_000_000));
+    assert!(lp_amount > 0);
+    assert_eq!(pool.balance(&creator), lp_amount);
+    assert_eq!(pool.balance(&poolfactory.address), 0);
+    assert_eq!(token_a.balance(&creator), 0);
+    assert_eq!(token_b.balance(&poolfactory.address), 0);
+}
+
+#[test]
+#[should_panic(expected = "Amounts must be > 0")]
+fn test_create_pool_with_no_liquidity_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    poolfactory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+    poolfactory.create_pool_with_liquidity(
+        &admin,
+        &token_a.address,
+        &token_b.address,
+        &10_000_000_000,
+        &0,
+    );
+}
+
+#[test]
+fn test_token_info_and_generated_lp_names() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let usdc = TokenClient::new(&env, &env.register(Token, (&admin, 6_u32, String::from_str(&env, "USD Coin"), String::from_str(&env, "USDC"))));
+    let xlm = TokenClient::new(&env, &env.register(Token, (&admin, 7_u32, String::from_str(&env, "Lumens"), String::from_str(&env, "XLM"))));
+    poolfactory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+
+    let pool_addr = poolfactory.create_pool(&usdc.address, &xlm.address, &None, &None);
+    let pool = LiquidityPoolClient::new(&env, &pool_addr);
+    assert_eq!(pool.name(), String::from_str(&env, "CDX-LP USDC/XLM"));
+    assert_eq!(pool.symbol(), String::from_str(&env, "CDX-LP"));
+
+    let usdc_info = TokenInfo { decimals: 6, name: String::from_str(&env, "USD Coin"), symbol: String::from_str(&env, "USDC") };
+    assert_eq!(poolfactory.get_token_info(&usdc.address), Some(usdc_info.clone()));
+    let token_info = poolfactory.get_pool_token_info(&pool_addr);
+    assert_eq!(token_info.len(), 2);
+    assert_eq!(token_info.get(0).unwrap(), usdc_info);
+    assert_eq!(token_info.get(1).unwrap().decimals, 7);
+}
+
+#[test]
+#[should_panic(expected = "Invalid token")]
+fn test_create_pool_with_non_token_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    poolfactory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+    poolfactory.create_pool(&token_a.address, &Address::generate(&env), &None, &None);
+}
+
+fn swap(e: &Env, pool_addr: &Address, trader: &Address, token_in: &TokenClient, amount_in: i128) {
+    token_in.approve(trader, pool_addr, &amount_in, &(e.ledger().sequence() + 1000));
+    LiquidityPoolClient::new(e, pool_addr).swap(trader, &token_in.address, &amount_in);
+}
+
+// Deploys and seeds a constant product pool, then swaps `amount_in` of token_a through it
+fn seeded_pool_with_volume(
+    e: &Env,
+    poolfactory: &PoolFactoryClient,
+    token_a: &TokenClient,
+    token_b: &TokenClient,
+    amount_in: i128,
+) -> Address {
+    let creator = Address::generate(e);
+    token_a.mint(&creator, &(10_000_000_000 + amount_in));
+    token_b.mint(&creator, &10_000_000_000);
+    let (pool_addr, _) = poolfactory.create_pool_with_liquidity(
+        &creator,
+        &token_a.address,
+        &token_b.address,
+        &10_000_000_000,
+        &10_000_000_000,
+    );
+    if amount_in > 0 {
+        swap(e, &pool_addr, &creator, token_a, amount_in);
+    }
+    pool_addr
+}
+
+#[test]
+fn test_sync_pool_stats() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    let token_c = create_token(&env, &admin);
+    poolfactory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+
+    let quiet_pool = seeded_pool_with_volume(&env, &poolfactory, &token_a, &token_b, 0);
+    let busy_pool = seeded_pool_with_volume(&env, &poolfactory, &token_a, &token_c, 1_000_000_000);
+
+    env.ledger().set_sequence_number(100);
+    assert_eq!(poolfactory.sync_pool_stats(&0, &10), 2);
+
+    let busy_reserves = LiquidityPoolClient::new(&env, &busy_pool).get_reserves();
+    let busy_out = 10_000_000_000 - busy_reserves.1;
+    let stats = poolfactory.get_pool_stats(&busy_pool).unwrap();
+    assert_eq!(stats.volumes, vec![&env, 1_000_000_000, busy_out]);
+    assert_eq!(stats.last_sync_ledger, 100);
+    assert_eq!(poolfactory.get_token_tvl(&token_a.address), 10_000_000_000 + busy_reserves.0);
+    assert_eq!(poolfactory.get_token_tvl(&token_c.address), busy_reserves.1);
+
+    // Volume is kept per token, each in its own units
+    assert_eq!(poolfactory.get_token_volume(&token_a.address), 1_000_000_000);
+    assert_eq!(poolfactory.get_token_volume(&token_c.address), busy_out);
+    assert_eq!(poolfactory.get_token_volume(&token_b.address), 0);
+
+    // Pools without volume stay out of the ranking
+    let top_pools = poolfactory.get_top_pools_by_volume(&token_a.address);
+    assert_eq!(top_pools, vec![&env, PoolVolume { pool: busy_pool.clone(), volume: 1_000_000_000 }]);
+    assert!(poolfactory.get_top_pools_by_volume(&token_b.address).is_empty());
+
+    // More volume on the quiet pool moves it to the top, and a second sync doesn't count anything twice
+    let trader = Address::generate(&env);
+    token_b.mint(&trader, &2_000_000_000);
+    swap(&env, &quiet_pool, &trader, &token_b, 2_000_000_000);
+    poolfactory.sync_pool_stats(&0, &1);
+    poolfactory.sync_pool_stats(&0, &2);
+
+    let quiet_reserves = LiquidityPoolClient::new(&env, &quiet_pool).get_reserves();
+    let quiet_out = 10_000_000_000 - quiet_reserves.0;
+    assert_eq!(poolfactory.get_token_tvl(&token_a.address), quiet_reserves.0 + busy_reserves.0);
+    assert_eq!(poolfactory.get_token_tvl(&token_b.address), quiet_reserves.1);
+    assert_eq!(poolfactory.get_token_volume(&token_a.address), 1_000_000_000 + quiet_out);
+    assert_eq!(poolfactory.get_token_volume(&token_b.address), 2_000_000_000);
+    let top_pools = poolfactory.get_top_pools_by_volume(&token_a.address);
+    assert_eq!(top_pools.len(), 2);
+    assert_eq!(top_pools.get(0).unwrap(), PoolVolume { pool: quiet_pool.clone(), volume: quiet_out });
+    assert_eq!(top_pools.get(1).unwrap().pool, busy_pool);
+
+    let protocol = poolfactory.get_protocol_stats();
+    assert_eq!(protocol, ProtocolStats { pool_count: 2, last_sync_ledger: 100 });
+}
+
+#[test]
+fn test_sync_stable_pool_volume() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    poolfactory.update_pool_type_wasm_hash(&admin, &PoolType::Stable, &env.deployer().upload_contract_wasm(stable_contract::WASM));
+    let stable_addr = poolfactory.create_stable_pool(&token_a.address, &token_b.address, &None, &None, &100);
+    let stable = stable_contract::Client::new(&env, &stable_addr);
+
+    token_a.mint(&user, &11_000_000_000);
+    token_b.mint(&user, &10_000_000_000);
+    token_a.approve(&user, &stable_addr, &11_000_000_000, &1000);
+    token_b.approve(&user, &stable_addr, &10_000_000_000, &1000);
+    stable.add_liquidity(&user, &10_000_000_000, &10_000_000_000);
+    let amount_out = stable.swap(&user, &token_a.address, &1_000_000_000);
+
+    poolfactory.sync_pool_stats(&0, &10);
+    assert_eq!(poolfactory.get_token_volume(&token_a.address), 1_000_000_000);
+    assert_eq!(poolfactory.get_token_volume(&token_b.address), amount_out);
+    assert_eq!(poolfactory.get_top_pools_by_volume(&token_b.address).get(0).unwrap().pool, stable_addr);
+}
+
+#[test]
+fn test_sync_pool_stats_indexes_legacy_pools() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let provider = Address::generate(&env);
+
+    let poolfactory = deploy_poolfactory(&env, &admin);
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    let pool_addr = register_legacy_pool(&env, &poolfactory, &token_a.address, &token_b.address);
+    token_a.mint(&provider, &10_000_000_000);
+    token_b.mint(&provider, &10_000_000_000);
+    token_a.approve(&provider, &pool_addr, &10_000_000_000, &1000);
+    token_b.approve(&provider, &pool_addr, &10_000_000_000, &1000);
+    LiquidityPoolClient::new(&env, &pool_addr).add_liquidity(&provider, &10_000_000_000, &10_000_000_000);
+
+    assert_eq!(poolfactory.sync_pool_stats(&0, &10), 1);
+    assert_eq!(poolfactory.get_pool_tokens(&pool_addr), vec![&env, token_a.address.clone(), token_b.address.clone()]);
+    assert_eq!(poolfactory.get_token_tvl(&token_a.address), 10_000_000_000);
+    assert_eq!(poolfactory.get_token_tvl(&token_b.address), 10_000_000_000);
+}
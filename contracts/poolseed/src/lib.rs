@@ -0,0 +1,180 @@
+//! Seeding a constant product pool at a chosen price, shared by the launchpad, the presale and the LBP.
+//!
+//! Anyone can create the factory's pool for a pair and add liquidity at any price before a sale
+//! moves its funds there. Instead of refusing such a pool, the seeding contract first swaps it
+//! to the target price and then deposits at the pool's ratio, so a griefer gives the difference
+//! to the sale instead of choosing the price or blocking it.
+#![no_std]
+
+use soroban_sdk::{contractclient, token, Address, Env, String, U256};
+
+// Swap fee of the constant product pool, taken from the input amount
+const FEE_BPS: i128 = 30;
+const BPS_DENOMINATOR: i128 = 10000;
+
+#[contractclient(name = "FactoryClient")]
+pub trait FactoryInterface {
+    fn create_pool(
+        env: Env,
+        token_a: Address,
+        token_b: Address,
+        lp_token_name: Option<String>,
+        lp_token_symbol: Option<String>,
+    ) -> Address;
+    fn get_pool(env: Env, token_a: Address, token_b: Address) -> Option<Address>;
+}
+
+#[contractclient(name = "PoolClient")]
+pub trait PoolInterface {
+    fn get_token_a(e: Env) -> Address;
+    fn get_token_b(e: Env) -> Address;
+    fn get_reserves(e: Env) -> (i128, i128);
+    fn add_liquidity(e: Env, caller: Address, amount_a: i128, amount_b: i128) -> i128;
+    fn swap(e: Env, caller: Address, input_token: Address, amount_in: i128) -> i128;
+}
+
+fn to_u256(e: &Env, value: i128) -> U256 {
+    if value < 0 {
+        panic!("negative amount is not allowed: {}", value)
+    }
+    U256::from_u128(e, value as u128)
+}
+
+fn to_i128(value: &U256) -> i128 {
+    let value = value.to_u128().expect("Overflow in conversion");
+    i128::try_from(value).expect("Overflow in conversion")
+}
+
+// a * b / c rounded down, without intermediate overflow
+fn mul_div(e: &Env, a: i128, b: i128, c: i128) -> i128 {
+    if c == 0 {
+        panic!("Division by zero");
+    }
+    to_i128(&to_u256(e, a).mul(&to_u256(e, b)).div(&to_u256(e, c)))
+}
+
+// Square root of a * b rounded down. Newton's method from the larger factor, which is at least the root
+fn geometric_mean(e: &Env, a: i128, b: i128) -> i128 {
+    let n = to_u256(e, a).mul(&to_u256(e, b));
+    let two = U256::from_u32(e, 2);
+    let mut x = to_u256(e, a.max(b));
+    if x == U256::from_u32(e, 0) {
+        return 0;
+    }
+    loop {
+        let y = x.add(&n.div(&x)).div(&two);
+        if y >= x {
+            return to_i128(&x);
+        }
+        x = y;
+    }
+}
+
+// Pool reserves as (token, other)
+fn reserves(pool: &PoolClient, token_is_a: bool) -> (i128, i128) {
+    let (reserve_a, reserve_b) = pool.get_reserves();
+    if token_is_a { (reserve_a, reserve_b) } else { (reserve_b, reserve_a) }
+}
+
+// Input that adds `reserve_increase` to the input reserve once the pool takes its fee
+fn swap_amount_in(reserve_increase: i128) -> i128 {
+    let net = BPS_DENOMINATOR - FEE_BPS;
+    (reserve_increase * BPS_DENOMINATOR + net - 1) / net
+}
+
+// Output the pool pays for `amount_in`, as the pool computes it
+fn swap_amount_out(reserve_in: i128, reserve_out: i128, amount_in: i128) -> i128 {
+    let amount_in_with_fee = amount_in * (BPS_DENOMINATOR - FEE_BPS) / BPS_DENOMINATOR;
+    reserve_out * amount_in_with_fee / (reserve_in + amount_in_with_fee)
+}
+
+/// The factory's constant product pool for the pair, created if there is none yet.
+/// `None` when the factory can't provide one, for example while it is paused
+pub fn get_or_create_pool(
+    e: &Env,
+    factory: &Address,
+    token_a: &Address,
+    token_b: &Address,
+    lp_token_name: String,
+    lp_token_symbol: String,
+) -> Option<Address> {
+    let factory = FactoryClient::new(e, factory);
+    match factory.try_get_pool(token_a, token_b) {
+        Ok(Ok(Some(pool))) => Some(pool),
+        Ok(Ok(None)) => match factory.try_create_pool(token_a, token_b, &Some(lp_token_name), &Some(lp_token_symbol)) {
+            Ok(Ok(pool)) => Some(pool),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Deposit up to `token_amount` of `token` and `other_amount` of the pool's other token from this
+/// contract at `price`, a (other, token) ratio. A pool that already holds liquidity at another price
+/// is swapped to `price` first, as far as the amounts allow. Returns the net (token, other) amounts
+/// that left this contract; the swap can make one of them negative. The LP tokens stay here
+pub fn seed_pool(e: &Env, pool: &Address, token: &Address, token_amount: i128, other_amount: i128, price: (i128, i128)) -> (i128, i128) {
+    let pool_client = PoolClient::new(e, pool);
+    let token_is_a = pool_client.get_token_a() == *token;
+    let other_token = if token_is_a { pool_client.get_token_b() } else { pool_client.get_token_a() };
+    let current = e.current_contract_address();
+    let token_client = token::Client::new(e, token);
+    let other_client = token::Client::new(e, &other_token);
+    let token_before = token_client.balance(&current);
+    let other_before = other_client.balance(&current);
+    let (price_other, price_token) = price;
+    let expiration_ledger = e.ledger().sequence();
+
+    let mut token_available = token_amount;
+    let mut other_available = other_amount;
+    let (mut reserve_token, mut reserve_other) = reserves(&pool_client, token_is_a);
+    if reserve_token > 0 && reserve_other > 0 {
+        // The token reserve that keeps the invariant at the target price is the geometric mean of
+        // the current one and the one the other reserve would need at that price
+        let target_token = geometric_mean(e, reserve_token, mul_div(e, reserve_other, price_token, price_other));
+        let target_other = geometric_mean(e, reserve_other, mul_div(e, reserve_token, price_other, price_token));
+        if target_token > reserve_token {
+            let amount_in = swap_amount_in(target_token - reserve_token).min(token_available);
+            if swap_amount_out(reserve_token, reserve_other, amount_in) > 0 {
+                token_client.approve(&current, pool, &amount_in, &expiration_ledger);
+                let amount_out = pool_client.swap(&current, token, &amount_in);
+                token_available -= amount_in;
+                other_available += amount_out;
+            }
+        } else if target_other > reserve_other {
+            let amount_in = swap_amount_in(target_other - reserve_other).min(other_available);
+            if swap_amount_out(reserve_other, reserve_token, amount_in) > 0 {
+                other_client.approve(&current, pool, &amount_in, &expiration_ledger);
+                let amount_out = pool_client.swap(&current, &other_token, &amount_in);
+                other_available -= amount_in;
+                token_available += amount_out;
+            }
+        }
+        (reserve_token, reserve_other) = reserves(&pool_client, token_is_a);
+    }
+
+    // Deposit as much as fits the pool's ratio, or the target price in an empty pool
+    let (ratio_other, ratio_token) = if reserve_token > 0 && reserve_other > 0 {
+        (reserve_other, reserve_token)
+    } else {
+        (price_other, price_token)
+    };
+    let mut deposit_token = token_available;
+    let mut deposit_other = mul_div(e, deposit_token, ratio_other, ratio_token);
+    if deposit_other > other_available {
+        deposit_other = other_available;
+        deposit_token = mul_div(e, deposit_other, ratio_token, ratio_other);
+    }
+    token_client.approve(&current, pool, &deposit_token, &expiration_ledger);
+    other_client.approve(&current, pool, &deposit_other, &expiration_ledger);
+    if token_is_a {
+        pool_client.add_liquidity(&current, &deposit_token, &deposit_other);
+    } else {
+        pool_client.add_liquidity(&current, &deposit_other, &deposit_token);
+    }
+
+    (
+        token_before - token_client.balance(&current),
+        other_before - other_client.balance(&current),
+    )
+}
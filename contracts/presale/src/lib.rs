@@ -0,0 +1,383 @@
+#![no_std]
+
+use soroban_sdk::{
+    contract, contractclient, contractimpl, contracttype, Address, BytesN, Env, String, Vec,
+    token::{self},
+};
+
+use merkleproof::verify;
+use poolseed::{get_or_create_pool, seed_pool};
+use storagettl::{read_persistent, write_persistent};
+
+use crate::merkle::leaf_hash;
+
+// Constants
+const BPS_DENOMINATOR: u32 = 10000;
+
+// Overflow protection functions
+fn checked_add(a: i128, b: i128) -> i128 {
+    a.checked_add(b).expect("Overflow in addition")
+}
+
+fn checked_sub(a: i128, b: i128) -> i128 {
+    a.checked_sub(b).expect("Underflow in subtraction")
+}
+
+fn checked_mul(a: i128, b: i128) -> i128 {
+    a.checked_mul(b).expect("Overflow in multiplication")
+}
+
+fn checked_div(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        panic!("Division by zero");
+    }
+    a.checked_div(b).expect("Overflow in division")
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct SaleConfig {
+    pub start_ledger: u32,
+    pub public_start_ledger: u32,          // Before it, only allowlisted wallets can contribute
+    pub end_ledger: u32,
+    pub allowlist_root: Option<BytesN<32>>, // Merkle root of (wallet, cap) leaves for the allowlist round
+    pub soft_cap: i128,                // Minimum raise, refunds are paid below it
+    pub hard_cap: i128,                // Maximum raise, the sale closes once it is reached
+    pub min_contribution: i128,        // Per wallet, over all its contributions
+    pub max_contribution: i128,        // Per wallet, over all its contributions
+    pub tokens_for_sale: i128,         // Shared among contributors in proportion to their contributions
+    pub liquidity_bps: u32,            // Share of the raise paired into a pool on finalize, 0 to skip
+    pub tokens_for_liquidity: i128,    // Most sale tokens that can be paired with that share
+    pub pool_factory: Option<Address>, // Factory creating the pool, required when liquidity_bps > 0
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum SaleStatus {
+    Upcoming,
+    Active,
+    Succeeded, // Ended at or above the soft cap, claims are open
+    Failed,    // Ended below the soft cap, refunds are open
+    Finalized, // Succeeded and the owner has taken the raise
+}
+
+#[contractclient(name = "PoolClient")]
+pub trait PoolInterface {
+    fn balance_of(e: Env, id: Address) -> i128;
+}
+
+#[contract]
+pub struct Presale;
+
+#[contractimpl]
+impl Presale {
+
+    fn check_nonnegative_amount(amount: i128) {
+        if amount < 0 {
+            panic!("negative amount is not allowed: {}", amount)
+        }
+    }
+
+    fn get_config(e: &Env) -> SaleConfig {
+        e.storage().instance().get(&DataKey::Config).unwrap()
+    }
+
+    fn get_total_raised(e: &Env) -> i128 {
+        e.storage().instance().get(&DataKey::TotalRaised).unwrap_or(0)
+    }
+
+    fn get_contribution(e: &Env, contributor: &Address) -> i128 {
+        read_persistent(e, &DataKey::Contribution(contributor.clone())).unwrap_or(0)
+    }
+
+    fn has_claimed(e: &Env, contributor: &Address) -> bool {
+        read_persistent(e, &DataKey::Claimed(contributor.clone())).unwrap_or(false)
+    }
+
+    fn require_owner(e: &Env, owner: &Address) {
+        let stored: Address = e.storage().instance().get(&DataKey::Owner).unwrap();
+        assert!(stored == *owner, "Unauthorized");
+        owner.require_auth();
+    }
+
+    fn status(e: &Env, config: &SaleConfig) -> SaleStatus {
+        let ledger = e.ledger().sequence();
+        let raised = Self::get_total_raised(e);
+        if e.storage().instance().has(&DataKey::Finalized) {
+            SaleStatus::Finalized
+        } else if ledger < config.start_ledger {
+            SaleStatus::Upcoming
+        } else if ledger < config.end_ledger && raised < config.hard_cap {
+            SaleStatus::Active
+        } else if raised >= config.soft_cap {
+            SaleStatus::Succeeded
+        } else {
+            SaleStatus::Failed
+        }
+    }
+
+    fn require_active(e: &Env, config: &SaleConfig) {
+        assert!(e.storage().instance().has(&DataKey::Funded), "Sale not funded");
+        assert!(Self::status(e, config) == SaleStatus::Active, "Sale not active");
+    }
+
+    fn record_contribution(e: &Env, config: &SaleConfig, contributor: &Address, amount: i128, max_contribution: i128) {
+        assert!(amount > 0, "Amount must be > 0");
+        let contribution = checked_add(Self::get_contribution(e, contributor), amount);
+        assert!(contribution >= config.min_contribution, "Below minimum contribution");
+        assert!(contribution <= max_contribution, "Above maximum contribution");
+        let total_raised = checked_add(Self::get_total_raised(e), amount);
+        assert!(total_raised <= config.hard_cap, "Hard cap exceeded");
+
+        let payment_token: Address = e.storage().instance().get(&DataKey::PaymentToken).unwrap();
+        token::Client::new(e, &payment_token).transfer(contributor, &e.current_contract_address(), &amount);
+
+        write_persistent(e, &DataKey::Contribution(contributor.clone()), &contribution);
+        e.storage().instance().set(&DataKey::TotalRaised, &total_raised);
+    }
+
+    // Sale tokens owed to a contributor: their share of the raise applied to the tokens for sale
+    fn calculate_claim(e: &Env, config: &SaleConfig, contribution: i128) -> i128 {
+        checked_div(checked_mul(contribution, config.tokens_for_sale), Self::get_total_raised(e))
+    }
+
+    pub fn __constructor(e: Env, owner: Address, sale_token: Address, payment_token: Address, config: SaleConfig) {
+        assert!(sale_token != payment_token, "Tokens must be different");
+        assert!(
+            config.start_ledger <= config.public_start_ledger && config.public_start_ledger < config.end_ledger,
+            "Invalid ledger range"
+        );
+        assert!(
+            config.allowlist_root.is_some() || config.public_start_ledger == config.start_ledger,
+            "Allowlist round requires a root"
+        );
+        assert!(config.soft_cap > 0 && config.soft_cap <= config.hard_cap, "Invalid caps");
+        assert!(
+            config.min_contribution > 0 && config.min_contribution <= config.max_contribution,
+            "Invalid contribution limits"
+        );
+        assert!(config.tokens_for_sale > 0, "Tokens for sale must be > 0");
+        assert!(config.liquidity_bps <= BPS_DENOMINATOR, "Invalid liquidity share");
+        Self::check_nonnegative_amount(config.tokens_for_liquidity);
+        if config.liquidity_bps > 0 {
+            assert!(
+                config.pool_factory.is_some() && config.tokens_for_liquidity > 0,
+                "Liquidity requires a pool factory and tokens"
+            );
+        }
+
+        e.storage().instance().set(&DataKey::Owner, &owner);
+        e.storage().instance().set(&DataKey::SaleToken, &sale_token);
+        e.storage().instance().set(&DataKey::PaymentToken, &payment_token);
+        e.storage().instance().set(&DataKey::Config, &config);
+    }
+
+    /// Deposit the tokens for sale and for liquidity (owner only, before the sale starts)
+    pub fn fund(e: Env, owner: Address) {
+        Self::require_owner(&e, &owner);
+        let config = Self::get_config(&e);
+        assert!(e.ledger().sequence() < config.start_ledger, "Sale already started");
+        assert!(!e.storage().instance().has(&DataKey::Funded), "Already funded");
+
+        let sale_token: Address = e.storage().instance().get(&DataKey::SaleToken).unwrap();
+        let amount = checked_add(config.tokens_for_sale, config.tokens_for_liquidity);
+        token::Client::new(&e, &sale_token).transfer(&owner, &e.current_contract_address(), &amount);
+        e.storage().instance().set(&DataKey::Funded, &true);
+    }
+
+    /// Contribute payment tokens during the public round, within the per-wallet limits and the hard cap
+    pub fn contribute(e: Env, contributor: Address, amount: i128) {
+        contributor.require_auth();
+        let config = Self::get_config(&e);
+        Self::require_active(&e, &config);
+        assert!(e.ledger().sequence() >= config.public_start_ledger, "Public round not started");
+
+        Self::record_contribution(&e, &config, &contributor, amount, config.max_contribution);
+    }
+
+    /// Contribute payment tokens during the allowlist round, up to the wallet's allowlisted cap.
+    /// `proof` shows that (contributor, cap) is a leaf of the allowlist root
+    pub fn contribute_allowlisted(e: Env, contributor: Address, amount: i128, cap: i128, proof: Vec<BytesN<32>>) {
+        contributor.require_auth();
+        let config = Self::get_config(&e);
+        Self::require_active(&e, &config);
+        assert!(e.ledger().sequence() < config.public_start_ledger, "Allowlist round ended");
+
+        let root = config.allowlist_root.clone().expect("No allowlist");
+        assert!(verify(&e, &root, leaf_hash(&e, &contributor, cap), &proof), "Invalid proof");
+
+        Self::record_contribution(&e, &config, &contributor, amount, cap);
+    }
+
+    /// Claim sale tokens in proportion to the contribution once the sale has succeeded
+    pub fn claim(e: Env, contributor: Address) -> i128 {
+        contributor.require_auth();
+        let config = Self::get_config(&e);
+        let status = Self::status(&e, &config);
+        assert!(status == SaleStatus::Succeeded || status == SaleStatus::Finalized, "Sale not successful");
+
+        let contribution = Self::get_contribution(&e, &contributor);
+        assert!(contribution > 0, "Nothing to claim");
+        assert!(!Self::has_claimed(&e, &contributor), "Already claimed");
+
+        let amount = Self::calculate_claim(&e, &config, contribution);
+        let sale_token: Address = e.storage().instance().get(&DataKey::SaleToken).unwrap();
+        token::Client::new(&e, &sale_token).transfer(&e.current_contract_address(), &contributor, &amount);
+        write_persistent(&e, &DataKey::Claimed(contributor), &true);
+
+        amount
+    }
+
+    /// Get the contribution back once the sale has ended below the soft cap
+    pub fn refund(e: Env, contributor: Address) -> i128 {
+        contributor.require_auth();
+        let config = Self::get_config(&e);
+        assert!(Self::status(&e, &config) == SaleStatus::Failed, "Refunds not available");
+
+        let contribution = Self::get_contribution(&e, &contributor);
+        assert!(contribution > 0, "Nothing to refund");
+
+        let payment_token: Address = e.storage().instance().get(&DataKey::PaymentToken).unwrap();
+        token::Client::new(&e, &payment_token).transfer(&e.current_contract_address(), &contributor, &contribution);
+        e.storage().persistent().remove(&DataKey::Contribution(contributor));
+
+        contribution
+    }
+
+    /// Take the deposited sale tokens back once the sale has failed (owner only)
+    pub fn reclaim_tokens(e: Env, owner: Address) -> i128 {
+        Self::require_owner(&e, &owner);
+        let config = Self::get_config(&e);
+        assert!(Self::status(&e, &config) == SaleStatus::Failed, "Sale not failed");
+        assert!(e.storage().instance().has(&DataKey::Funded), "Nothing to reclaim");
+
+        let sale_token: Address = e.storage().instance().get(&DataKey::SaleToken).unwrap();
+        let amount = checked_add(config.tokens_for_sale, config.tokens_for_liquidity);
+        token::Client::new(&e, &sale_token).transfer(&e.current_contract_address(), &owner, &amount);
+        e.storage().instance().remove(&DataKey::Funded);
+
+        amount
+    }
+
+    /// Close a successful sale (owner only). When `liquidity_bps` is set, that share of the raise is
+    /// paired with sale tokens at the sale price in the factory's pool for the pair, and the LP tokens
+    /// stay locked in this contract. A pool seeded at another price is swapped to the sale price first.
+    /// If the factory can't provide a pool, the sale closes without one. The rest of the raise and any
+    /// unused liquidity tokens go to the owner. Returns the pool, if any
+    pub fn finalize(
+        e: Env,
+        owner: Address,
+        lp_token_name: String,
+        lp_token_symbol: String,
+    ) -> Option<Address> {
+        Self::require_owner(&e, &owner);
+        let config = Self::get_config(&e);
+        assert!(Self::status(&e, &config) == SaleStatus::Succeeded, "Sale not successful");
+
+        let sale_token: Address = e.storage().instance().get(&DataKey::SaleToken).unwrap();
+        let payment_token: Address = e.storage().instance().get(&DataKey::PaymentToken).unwrap();
+        let total_raised = Self::get_total_raised(&e);
+
+        let pool = if config.liquidity_bps > 0 {
+            get_or_create_pool(&e, config.pool_factory.as_ref().unwrap(), &sale_token, &payment_token, lp_token_name, lp_token_symbol)
+        } else {
+            None
+        };
+        let (tokens_used, payment_used) = match &pool {
+            Some(pool_addr) => {
+                let payment_amount = checked_div(checked_mul(total_raised, config.liquidity_bps as i128), BPS_DENOMINATOR as i128);
+                let price = (total_raised, config.tokens_for_sale);
+                let used = seed_pool(&e, pool_addr, &sale_token, config.tokens_for_liquidity, payment_amount, price);
+                e.storage().instance().set(&DataKey::Pool, pool_addr);
+                used
+            }
+            None => (0, 0),
+        };
+
+        let payment_left = checked_sub(total_raised, payment_used);
+        if payment_left > 0 {
+            token::Client::new(&e, &payment_token).transfer(&e.current_contract_address(), &owner, &payment_left);
+        }
+        let tokens_left = checked_sub(config.tokens_for_liquidity, tokens_used);
+        if tokens_left > 0 {
+            token::Client::new(&e, &sale_token).transfer(&e.current_contract_address(), &owner, &tokens_left);
+        }
+        e.storage().instance().set(&DataKey::Finalized, &true);
+
+        pool
+    }
+
+    // View functions
+    pub fn get_owner(e: Env) -> Address {
+        e.storage().instance().get(&DataKey::Owner).unwrap()
+    }
+
+    pub fn get_sale_config(e: Env) -> SaleConfig {
+        Self::get_config(&e)
+    }
+
+    pub fn get_status(e: Env) -> SaleStatus {
+        Self::status(&e, &Self::get_config(&e))
+    }
+
+    /// Whether the sale is in its allowlist round
+    pub fn is_allowlist_round(e: Env) -> bool {
+        let config = Self::get_config(&e);
+        let ledger = e.ledger().sequence();
+        Self::status(&e, &config) == SaleStatus::Active && ledger < config.public_start_ledger
+    }
+
+    pub fn get_raised(e: Env) -> i128 {
+        Self::get_total_raised(&e)
+    }
+
+    pub fn get_contribution_of(e: Env, contributor: Address) -> i128 {
+        Self::get_contribution(&e, &contributor)
+    }
+
+    /// Sale tokens the contributor can claim now
+    pub fn get_claimable(e: Env, contributor: Address) -> i128 {
+        let config = Self::get_config(&e);
+        let status = Self::status(&e, &config);
+        if (status != SaleStatus::Succeeded && status != SaleStatus::Finalized)
+            || Self::has_claimed(&e, &contributor)
+        {
+            return 0;
+        }
+        let contribution = Self::get_contribution(&e, &contributor);
+        if contribution == 0 {
+            return 0;
+        }
+        Self::calculate_claim(&e, &config, contribution)
+    }
+
+    pub fn get_pool(e: Env) -> Option<Address> {
+        e.storage().instance().get(&DataKey::Pool)
+    }
+
+    /// LP tokens locked in this contract
+    pub fn get_locked_liquidity(e: Env) -> i128 {
+        match e.storage().instance().get::<_, Address>(&DataKey::Pool) {
+            Some(pool_addr) => PoolClient::new(&e, &pool_addr).balance_of(&e.current_contract_address()),
+            None => 0,
+        }
+    }
+}
+
+#[derive(Clone)]
+#[contracttype]
+enum DataKey {
+    Owner,
+    SaleToken,
+    PaymentToken,
+    Config,
+    Funded,
+    TotalRaised,
+    Finalized,
+    Pool,                  // Pool seeded on finalize, holding the locked liquidity
+    Contribution(Address), // Payment tokens contributed by a wallet
+    Claimed(Address),
+}
+
+mod merkle;
+mod test;
@@ -0,0 +1,11 @@
+//! Merkle allowlist leaves, verified with the shared `merkleproof` crate.
+//!
+//! Each leaf commits to an address and its allocation cap: sha256(address XDR || cap as 16
+//! big-endian bytes).
+use soroban_sdk::{xdr::ToXdr, Address, BytesN, Env};
+
+pub(crate) fn leaf_hash(e: &Env, account: &Address, cap: i128) -> BytesN<32> {
+    let mut data = account.clone().to_xdr(e);
+    data.extend_from_array(&cap.to_be_bytes());
+    e.crypto().sha256(&data).to_bytes()
+}
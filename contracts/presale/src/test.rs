@@ -0,0 +1,527 @@
+#![cfg(test)]
+extern crate std;
+use soroban_sdk::{
+    Env, String, Address, FromVal,
+    testutils::{Address as _, Ledger},
+};
+use crate::{Presale, PresaleClient, SaleConfig, SaleStatus};
+use crate::merkle::leaf_hash;
+use merkleproof::{verify, MerkleTree};
+use pool::LiquidityPoolClient;
+use poolfactory::PoolFactory;
+use poolfactory::PoolFactoryClient;
+use ::token::Token;
+use ::token::TokenClient;
+
+mod contract {
+    soroban_sdk::contractimport!(file = "../../target/wasm32v1-none/release/pool.wasm");
+}
+
+const START_LEDGER: u32 = 100;
+const PUBLIC_START_LEDGER: u32 = 600;
+const END_LEDGER: u32 = 1100;
+const TOKENS_FOR_SALE: i128 = 100_000_000_000;
+
+// Helper function to create a test token
+fn create_token<'a>(e: &Env, admin: &Address) -> TokenClient<'a> {
+    let token_contract = e.register(
+        Token,
+        (
+            admin,
+            7_u32,
+            String::from_val(e, &"name"),
+            String::from_val(e, &"symbol"),
+        ),
+    );
+    TokenClient::new(e, &token_contract)
+}
+
+fn set_ledger(e: &Env, sequence: u32) {
+    e.ledger().set_sequence_number(sequence);
+}
+
+// Soft cap 100, hard cap 500, 1 to 200 per wallet, 10000 tokens for sale
+fn sale_config(liquidity_bps: u32, tokens_for_liquidity: i128, pool_factory: Option<Address>) -> SaleConfig {
+    SaleConfig {
+        start_ledger: START_LEDGER,
+        public_start_ledger: START_LEDGER,
+        end_ledger: END_LEDGER,
+        allowlist_root: None,
+        soft_cap: 1_000_000_000,
+        hard_cap: 5_000_000_000,
+        min_contribution: 10_000_000,
+        max_contribution: 2_000_000_000,
+        tokens_for_sale: TOKENS_FOR_SALE,
+        liquidity_bps,
+        tokens_for_liquidity,
+        pool_factory,
+    }
+}
+
+fn setup<'a>(e: &Env, owner: &Address, config: SaleConfig) -> (TokenClient<'a>, TokenClient<'a>, PresaleClient<'a>) {
+    let sale_token = create_token(e, owner);
+    let payment_token = create_token(e, owner);
+    sale_token.mint(owner, &(config.tokens_for_sale + config.tokens_for_liquidity));
+
+    let contract_id = e.register(Presale, (owner, &sale_token.address, &payment_token.address, config));
+    let presale = PresaleClient::new(e, &contract_id);
+    presale.fund(owner);
+
+    (sale_token, payment_token, presale)
+}
+
+fn merkle_tree(e: &Env, entries: &[(Address, i128)]) -> MerkleTree {
+    MerkleTree::new(e, entries.iter().map(|(account, cap)| leaf_hash(e, account, *cap)).collect())
+}
+
+// An allowlist round from START_LEDGER to PUBLIC_START_LEDGER over the given (wallet, cap) entries
+fn allowlist_config(e: &Env, entries: &[(Address, i128)]) -> (SaleConfig, MerkleTree) {
+    let tree = merkle_tree(e, entries);
+    let mut config = sale_config(0, 0, None);
+    config.public_start_ledger = PUBLIC_START_LEDGER;
+    config.allowlist_root = Some(tree.root());
+    (config, tree)
+}
+
+fn allowlist_entries(e: &Env) -> std::vec::Vec<(Address, i128)> {
+    (0..5).map(|i| (Address::generate(e), 100_000_000 * (i + 1))).collect()
+}
+
+fn contribute(presale: &PresaleClient, payment_token: &TokenClient, contributor: &Address, amount: i128) {
+    payment_token.mint(contributor, &amount);
+    presale.contribute(contributor, &amount);
+}
+
+fn finalize_without_pool(e: &Env, presale: &PresaleClient, owner: &Address) -> Option<Address> {
+    presale.finalize(
+        owner,
+        &String::from_val(e, &"LPToken"),
+        &String::from_val(e, &"LP"),
+    )
+}
+
+#[test]
+fn test_claims_are_proportional() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+
+    let (sale_token, payment_token, presale) = setup(&env, &owner, sale_config(0, 0, None));
+    assert_eq!(presale.get_status(), SaleStatus::Upcoming);
+
+    set_ledger(&env, START_LEDGER);
+    assert_eq!(presale.get_status(), SaleStatus::Active);
+    contribute(&presale, &payment_token, &user1, 1_500_000_000);
+    contribute(&presale, &payment_token, &user1, 500_000_000);
+    contribute(&presale, &payment_token, &user2, 1_000_000_000);
+    assert_eq!(presale.get_contribution_of(&user1), 2_000_000_000);
+    assert_eq!(presale.get_raised(), 3_000_000_000);
+    assert_eq!(presale.get_claimable(&user1), 0);
+
+    set_ledger(&env, END_LEDGER);
+    assert_eq!(presale.get_status(), SaleStatus::Succeeded);
+    assert_eq!(presale.get_claimable(&user1), TOKENS_FOR_SALE * 2 / 3);
+    assert_eq!(presale.claim(&user1), TOKENS_FOR_SALE * 2 / 3);
+    assert_eq!(presale.claim(&user2), TOKENS_FOR_SALE / 3);
+    assert_eq!(sale_token.balance(&user1), TOKENS_FOR_SALE * 2 / 3);
+    assert_eq!(presale.get_claimable(&user1), 0);
+
+    // Without a liquidity step the whole raise goes to the owner
+    assert_eq!(finalize_without_pool(&env, &presale, &owner), None);
+    assert_eq!(payment_token.balance(&owner), 3_000_000_000);
+    assert_eq!(presale.get_status(), SaleStatus::Finalized);
+}
+
+#[test]
+#[should_panic(expected = "Already claimed")]
+fn test_claim_twice_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    let (_, payment_token, presale) = setup(&env, &owner, sale_config(0, 0, None));
+    set_ledger(&env, START_LEDGER);
+    contribute(&presale, &payment_token, &user, 2_000_000_000);
+
+    set_ledger(&env, END_LEDGER);
+    presale.claim(&user);
+    presale.claim(&user);
+}
+
+#[test]
+#[should_panic(expected = "Sale not active")]
+fn test_contribute_before_start_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    let (_, payment_token, presale) = setup(&env, &owner, sale_config(0, 0, None));
+    contribute(&presale, &payment_token, &user, 100_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Below minimum contribution")]
+fn test_contribute_below_minimum_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    let (_, payment_token, presale) = setup(&env, &owner, sale_config(0, 0, None));
+    set_ledger(&env, START_LEDGER);
+    contribute(&presale, &payment_token, &user, 1_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Above maximum contribution")]
+fn test_contribute_above_wallet_maximum_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    let (_, payment_token, presale) = setup(&env, &owner, sale_config(0, 0, None));
+    set_ledger(&env, START_LEDGER);
+    contribute(&presale, &payment_token, &user, 1_500_000_000);
+    contribute(&presale, &payment_token, &user, 600_000_000);
+}
+
+#[test]
+fn test_hard_cap_closes_sale() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    let user3 = Address::generate(&env);
+
+    let (_, payment_token, presale) = setup(&env, &owner, sale_config(0, 0, None));
+    set_ledger(&env, START_LEDGER);
+    contribute(&presale, &payment_token, &user1, 2_000_000_000);
+    contribute(&presale, &payment_token, &user2, 2_000_000_000);
+    contribute(&presale, &payment_token, &user3, 1_000_000_000);
+
+    // Reaching the hard cap ends the sale before the end ledger
+    assert_eq!(presale.get_status(), SaleStatus::Succeeded);
+    assert_eq!(presale.claim(&user3), TOKENS_FOR_SALE / 5);
+}
+
+#[test]
+#[should_panic(expected = "Hard cap exceeded")]
+fn test_contribute_above_hard_cap_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    let user3 = Address::generate(&env);
+
+    let (_, payment_token, presale) = setup(&env, &owner, sale_config(0, 0, None));
+    set_ledger(&env, START_LEDGER);
+    contribute(&presale, &payment_token, &user1, 2_000_000_000);
+    contribute(&presale, &payment_token, &user2, 2_000_000_000);
+    contribute(&presale, &payment_token, &user3, 1_500_000_000);
+}
+
+#[test]
+fn test_soft_cap_missed_refunds() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    let (sale_token, payment_token, presale) = setup(&env, &owner, sale_config(0, 0, None));
+    set_ledger(&env, START_LEDGER);
+    contribute(&presale, &payment_token, &user, 500_000_000);
+
+    set_ledger(&env, END_LEDGER);
+    assert_eq!(presale.get_status(), SaleStatus::Failed);
+    assert_eq!(presale.get_claimable(&user), 0);
+    assert_eq!(presale.refund(&user), 500_000_000);
+    assert_eq!(payment_token.balance(&user), 500_000_000);
+    assert_eq!(presale.get_contribution_of(&user), 0);
+
+    // The owner takes the unsold tokens back
+    assert_eq!(presale.reclaim_tokens(&owner), TOKENS_FOR_SALE);
+    assert_eq!(sale_token.balance(&owner), TOKENS_FOR_SALE);
+}
+
+#[test]
+#[should_panic(expected = "Sale not successful")]
+fn test_claim_after_failed_sale_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    let (_, payment_token, presale) = setup(&env, &owner, sale_config(0, 0, None));
+    set_ledger(&env, START_LEDGER);
+    contribute(&presale, &payment_token, &user, 500_000_000);
+
+    set_ledger(&env, END_LEDGER);
+    presale.claim(&user);
+}
+
+#[test]
+#[should_panic(expected = "Refunds not available")]
+fn test_refund_after_successful_sale_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    let (_, payment_token, presale) = setup(&env, &owner, sale_config(0, 0, None));
+    set_ledger(&env, START_LEDGER);
+    contribute(&presale, &payment_token, &user, 1_000_000_000);
+
+    set_ledger(&env, END_LEDGER);
+    presale.refund(&user);
+}
+
+#[test]
+fn test_finalize_seeds_pool_with_locked_liquidity() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+
+    let factory = PoolFactoryClient::new(&env, &env.register(PoolFactory, (&admin,)));
+    factory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+
+    // Half of the raise goes into the pool, with up to 6000 tokens
+    let config = sale_config(5000, 60_000_000_000, Some(factory.address.clone()));
+    let (sale_token, payment_token, presale) = setup(&env, &owner, config);
+    set_ledger(&env, START_LEDGER);
+    contribute(&presale, &payment_token, &user1, 2_000_000_000);
+    contribute(&presale, &payment_token, &user2, 2_000_000_000);
+
+    set_ledger(&env, END_LEDGER);
+    let pool_addr = presale.finalize(
+        &owner,
+        &String::from_val(&env, &"LPToken"),
+        &String::from_val(&env, &"LP"),
+    ).unwrap();
+    assert_eq!(factory.get_pool(&sale_token.address, &payment_token.address), Some(pool_addr.clone()));
+    assert_eq!(presale.get_pool(), Some(pool_addr.clone()));
+
+    // 200 payment tokens paired at the sale price of 0.04 use 5000 of the 6000 liquidity tokens
+    let pool = LiquidityPoolClient::new(&env, &pool_addr);
+    assert_eq!(pool.get_reserves(), (50_000_000_000, 2_000_000_000));
+    assert_eq!(payment_token.balance(&owner), 2_000_000_000);
+    assert_eq!(sale_token.balance(&owner), 10_000_000_000);
+    assert!(presale.get_locked_liquidity() > 0);
+    assert_eq!(pool.balance_of(&presale.address), presale.get_locked_liquidity());
+
+    // Contributors still claim the full tokens for sale
+    assert_eq!(presale.claim(&user1), TOKENS_FOR_SALE / 2);
+    assert_eq!(presale.claim(&user2), TOKENS_FOR_SALE / 2);
+    assert_eq!(sale_token.balance(&presale.address), 0);
+}
+
+#[test]
+fn test_finalize_into_seeded_pool_restores_sale_price() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let user1 = Address::generate(&env);
+    let attacker = Address::generate(&env);
+
+    let factory = PoolFactoryClient::new(&env, &env.register(PoolFactory, (&admin,)));
+    factory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+
+    let config = sale_config(5000, 60_000_000_000, Some(factory.address.clone()));
+    let (sale_token, payment_token, presale) = setup(&env, &owner, config);
+    set_ledger(&env, START_LEDGER);
+    contribute(&presale, &payment_token, &user1, 2_000_000_000);
+
+    // Someone seeds the pool at 1 payment token per sale token before the sale is finalized
+    let pool_addr = factory.create_pool(&sale_token.address, &payment_token.address, &None, &None);
+    let pool = LiquidityPoolClient::new(&env, &pool_addr);
+    sale_token.mint(&attacker, &1_000_000);
+    payment_token.mint(&attacker, &1_000_000);
+    sale_token.approve(&attacker, &pool_addr, &1_000_000, &1000);
+    payment_token.approve(&attacker, &pool_addr, &1_000_000, &1000);
+    pool.add_liquidity(&attacker, &1_000_000, &1_000_000);
+
+    set_ledger(&env, END_LEDGER);
+    let finalized_pool = presale.finalize(
+        &owner,
+        &String::from_val(&env, &"LPToken"),
+        &String::from_val(&env, &"LP"),
+    );
+    assert_eq!(finalized_pool, Some(pool_addr.clone()));
+
+    // The pool is back at the sale price of 0.02, within rounding
+    let (reserve_sale, reserve_payment) = pool.get_reserves();
+    assert!((reserve_payment * 100_000 / reserve_sale - 2_000).abs() <= 2);
+    assert!(presale.get_locked_liquidity() > 0);
+
+    // The contributor still claims the full tokens for sale and nothing else is left behind
+    assert_eq!(presale.claim(&user1), TOKENS_FOR_SALE);
+    assert_eq!(sale_token.balance(&presale.address), 0);
+    assert_eq!(payment_token.balance(&presale.address), 0);
+}
+
+#[test]
+fn test_finalize_without_available_pool_releases_raise() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let user1 = Address::generate(&env);
+
+    let factory = PoolFactoryClient::new(&env, &env.register(PoolFactory, (&admin,)));
+    factory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+
+    let config = sale_config(5000, 60_000_000_000, Some(factory.address.clone()));
+    let (sale_token, payment_token, presale) = setup(&env, &owner, config);
+    set_ledger(&env, START_LEDGER);
+    contribute(&presale, &payment_token, &user1, 2_000_000_000);
+
+    // A paused factory can't create the pool, so the sale closes without one
+    factory.set_paused(&admin, &true);
+    set_ledger(&env, END_LEDGER);
+    assert_eq!(finalize_without_pool(&env, &presale, &owner), None);
+    assert_eq!(presale.get_status(), SaleStatus::Finalized);
+    assert_eq!(presale.get_locked_liquidity(), 0);
+    assert_eq!(payment_token.balance(&owner), 2_000_000_000);
+    assert_eq!(sale_token.balance(&owner), 60_000_000_000);
+    assert_eq!(presale.claim(&user1), TOKENS_FOR_SALE);
+}
+
+#[test]
+#[should_panic(expected = "Liquidity requires a pool factory and tokens")]
+fn test_liquidity_without_factory_panics() {
+    let env = Env::default();
+    let owner = Address::generate(&env);
+
+    let sale_token = create_token(&env, &owner);
+    let payment_token = create_token(&env, &owner);
+    env.register(Presale, (&owner, &sale_token.address, &payment_token.address, sale_config(5000, 1_000, None)));
+}
+
+#[test]
+fn test_merkle_tree_proofs_verify() {
+    let env = Env::default();
+    let entries = allowlist_entries(&env);
+    let tree = merkle_tree(&env, &entries);
+
+    for (index, (account, cap)) in entries.iter().enumerate() {
+        let proof = tree.proof(&env, index);
+        assert!(verify(&env, &tree.root(), leaf_hash(&env, account, *cap), &proof));
+        assert!(!verify(&env, &tree.root(), leaf_hash(&env, account, cap + 1), &proof));
+    }
+}
+
+#[test]
+fn test_allowlisted_contribution_up_to_cap() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let entries = allowlist_entries(&env);
+    let (config, tree) = allowlist_config(&env, &entries);
+
+    let (_, payment_token, presale) = setup(&env, &owner, config);
+    set_ledger(&env, START_LEDGER);
+    assert!(presale.is_allowlist_round());
+
+    // The last entry is promoted through the tree without a sibling at the first level
+    let (account, cap) = &entries[4];
+    payment_token.mint(account, cap);
+    presale.contribute_allowlisted(account, &300_000_000, cap, &tree.proof(&env, 4));
+    presale.contribute_allowlisted(account, &200_000_000, cap, &tree.proof(&env, 4));
+    assert_eq!(presale.get_contribution_of(account), *cap);
+
+    // The public round is open to everyone, with the regular per-wallet maximum
+    set_ledger(&env, PUBLIC_START_LEDGER);
+    assert!(!presale.is_allowlist_round());
+    let user = Address::generate(&env);
+    contribute(&presale, &payment_token, &user, 2_000_000_000);
+    assert_eq!(presale.get_raised(), 2_500_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Above maximum contribution")]
+fn test_allowlisted_contribution_above_cap_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let entries = allowlist_entries(&env);
+    let (config, tree) = allowlist_config(&env, &entries);
+
+    let (_, payment_token, presale) = setup(&env, &owner, config);
+    set_ledger(&env, START_LEDGER);
+    let (account, cap) = &entries[1];
+    payment_token.mint(account, &(cap + 1));
+    presale.contribute_allowlisted(account, &(cap + 1), cap, &tree.proof(&env, 1));
+}
+
+#[test]
+#[should_panic(expected = "Invalid proof")]
+fn test_allowlisted_contribution_with_inflated_cap_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let entries = allowlist_entries(&env);
+    let (config, tree) = allowlist_config(&env, &entries);
+
+    let (_, payment_token, presale) = setup(&env, &owner, config);
+    set_ledger(&env, START_LEDGER);
+    let (account, _) = &entries[0];
+    payment_token.mint(account, &2_000_000_000);
+    presale.contribute_allowlisted(account, &2_000_000_000, &2_000_000_000, &tree.proof(&env, 0));
+}
+
+#[test]
+#[should_panic(expected = "Invalid proof")]
+fn test_contribution_with_another_wallets_proof_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let entries = allowlist_entries(&env);
+    let (config, tree) = allowlist_config(&env, &entries);
+
+    let (_, payment_token, presale) = setup(&env, &owner, config);
+    set_ledger(&env, START_LEDGER);
+    payment_token.mint(&outsider, &entries[2].1);
+    presale.contribute_allowlisted(&outsider, &entries[2].1, &entries[2].1, &tree.proof(&env, 2));
+}
+
+#[test]
+#[should_panic(expected = "Public round not started")]
+fn test_public_contribution_during_allowlist_round_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let user = Address::generate(&env);
+    let entries = allowlist_entries(&env);
+    let (config, _) = allowlist_config(&env, &entries);
+
+    let (_, payment_token, presale) = setup(&env, &owner, config);
+    set_ledger(&env, START_LEDGER);
+    contribute(&presale, &payment_token, &user, 100_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Allowlist round ended")]
+fn test_allowlisted_contribution_after_round_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let owner = Address::generate(&env);
+    let entries = allowlist_entries(&env);
+    let (config, tree) = allowlist_config(&env, &entries);
+
+    let (_, payment_token, presale) = setup(&env, &owner, config);
+    set_ledger(&env, PUBLIC_START_LEDGER);
+    let (account, cap) = &entries[0];
+    payment_token.mint(account, cap);
+    presale.contribute_allowlisted(account, cap, cap, &tree.proof(&env, 0));
+}
@@ -0,0 +1,474 @@
+#![no_std]
+
+use soroban_sdk::{
+    contract, contractclient, contractimpl, contracttype, token, vec, Address, Env, Map, Vec,
+};
+
+// Constants (mirrors the constant product math in the pool contract)
+const FEE_BPS: i128 = 30; // 0.3%
+const BPS_DENOMINATOR: i128 = 10000;
+const MAX_SPLIT_PARTS: u32 = 20; // Upper bound on the chunks `get_best_split` distributes
+const ROUTE_PAGE_SIZE: u32 = 50; // Pools read from the factory's token index per call
+const PAIR_POOL_TYPES: [PoolType; 3] = [PoolType::ConstantProduct, PoolType::Stable, PoolType::Concentrated];
+
+// Overflow protection functions
+fn checked_add(a: i128, b: i128) -> i128 {
+    a.checked_add(b).expect("Overflow in addition")
+}
+
+fn checked_sub(a: i128, b: i128) -> i128 {
+    a.checked_sub(b).expect("Underflow in subtraction")
+}
+
+fn checked_mul(a: i128, b: i128) -> i128 {
+    a.checked_mul(b).expect("Overflow in multiplication")
+}
+
+fn checked_div(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        panic!("Division by zero");
+    }
+    a.checked_div(b).expect("Overflow in division")
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Factory,
+}
+
+/// One leg of a split swap
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Split {
+    pub path: Vec<Address>, // Pool addresses to hop through, in order
+    pub fraction_bps: u32,  // Share of the input amount routed through this path
+}
+
+// Cached view of a pool used to simulate swaps without touching the pool. Constant product pools are
+// simulated on their reserves, other pools are quoted for everything routed through them so far
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolState {
+    pub token_a: Address,
+    pub token_b: Address,
+    pub reserve_a: i128,
+    pub reserve_b: i128,
+    pub quoted: bool,        // Priced by the pool's `get_amount_out` rather than the constant product formula
+    pub quoted_a_in: bool,   // Direction of the amounts routed through a quoted pool
+    pub quoted_in: i128,     // Input routed through a quoted pool so far
+    pub quoted_out: i128,    // Output quoted for `quoted_in`
+}
+
+// Mirrors PoolFactory's PoolType; routes go through the two-token pool types, which share a swap interface
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PoolType {
+    ConstantProduct,
+    Stable,
+    Concentrated,
+    Weighted,
+}
+
+#[contractclient(name = "PoolClient")]
+pub trait PoolInterface {
+    fn get_token_a(e: Env) -> Address;
+    fn get_token_b(e: Env) -> Address;
+    fn get_reserves(e: Env) -> (i128, i128);
+    fn swap(e: Env, caller: Address, input_token: Address, amount_in: i128) -> i128;
+    fn get_amount_out(e: Env, input_token: Address, amount_in: i128) -> i128;
+}
+
+#[contractclient(name = "FactoryClient")]
+pub trait FactoryInterface {
+    fn get_pool_by_type(env: Env, pool_type: PoolType, token_a: Address, token_b: Address) -> Option<Address>;
+    fn get_pool_type(env: Env, pool: Address) -> Option<PoolType>;
+    fn get_pools_for_token(env: Env, token: Address, offset: u32, limit: u32) -> Vec<Address>;
+    fn get_token_pool_count(env: Env, token: Address) -> u32;
+    fn get_pool_tokens(env: Env, pool: Address) -> Vec<Address>;
+}
+
+#[contract]
+pub struct Router;
+
+#[contractimpl]
+impl Router {
+    pub fn __constructor(env: Env, factory: Address) {
+        env.storage().instance().set(&DataKey::Factory, &factory);
+    }
+
+    /// Get the pool factory used for route discovery
+    pub fn get_factory(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Factory).expect("not set")
+    }
+
+    /// Swap `amount_in` of `token_in` across several paths atomically, revert if the
+    /// combined output is below `min_out`
+    pub fn swap_split(
+        env: Env,
+        caller: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        splits: Vec<Split>,
+        min_out: i128,
+    ) -> i128 {
+        caller.require_auth();
+        assert!(amount_in > 0, "Amount in must be > 0");
+        assert!(min_out >= 0, "Minimum output must be >= 0");
+        assert!(token_in != token_out, "Tokens must be different");
+
+        let amounts = Self::split_amounts(&env, amount_in, &splits);
+        let router = env.current_contract_address();
+        token::Client::new(&env, &token_in).transfer(&caller, &router, &amount_in);
+
+        let mut total_out = 0;
+        for (split, amount) in splits.iter().zip(amounts.iter()) {
+            if amount == 0 {
+                continue;
+            }
+            let (path_token_out, amount_out) = Self::execute_path(&env, &token_in, amount, &split.path);
+            assert!(path_token_out == token_out, "Path does not end in output token");
+            total_out = checked_add(total_out, amount_out);
+        }
+
+        assert!(total_out >= min_out, "Insufficient output amount");
+        token::Client::new(&env, &token_out).transfer(&router, &caller, &total_out);
+
+        total_out
+    }
+
+    /// Quote the combined output of a split swap against current reserves
+    pub fn get_split_amount_out(
+        env: Env,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        splits: Vec<Split>,
+    ) -> i128 {
+        assert!(amount_in > 0, "Amount in must be > 0");
+        let amounts = Self::split_amounts(&env, amount_in, &splits);
+
+        // Paths sharing a pool see each other's reserve changes, as they would on execution
+        let factory = FactoryClient::new(&env, &Self::get_factory(env.clone()));
+        let mut states = Map::new(&env);
+        let mut total_out = 0;
+        for (split, amount) in splits.iter().zip(amounts.iter()) {
+            if amount == 0 {
+                continue;
+            }
+            let (path_token_out, amount_out) =
+                Self::simulate_path(&env, &factory, &mut states, &token_in, amount, &split.path);
+            assert!(path_token_out == token_out, "Path does not end in output token");
+            total_out = checked_add(total_out, amount_out);
+        }
+        total_out
+    }
+
+    /// Compute the split of `amount_in` across the factory's direct and two-hop routes
+    /// that maximises output, by greedily assigning `parts` equal chunks
+    pub fn get_best_split(
+        env: Env,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        parts: u32,
+    ) -> (Vec<Split>, i128) {
+        assert!(amount_in > 0, "Amount in must be > 0");
+        assert!(token_in != token_out, "Tokens must be different");
+        assert!(parts > 0 && parts <= MAX_SPLIT_PARTS, "Invalid number of parts");
+
+        let factory = FactoryClient::new(&env, &Self::get_factory(env.clone()));
+        let mut states = Map::new(&env);
+        let routes = Self::find_routes(&env, &factory, &mut states, &token_in, &token_out);
+        assert!(!routes.is_empty(), "No route between tokens");
+
+        let mut route_amounts: Vec<i128> = Vec::new(&env);
+        for _ in routes.iter() {
+            route_amounts.push_back(0);
+        }
+
+        let chunk = checked_div(amount_in, parts as i128);
+        for part in 0..parts {
+            let part_amount = if part == parts - 1 {
+                checked_sub(amount_in, checked_mul(chunk, (parts - 1) as i128))
+            } else {
+                chunk
+            };
+            if part_amount == 0 {
+                continue;
+            }
+
+            // Send this chunk down whichever route pays the most given the chunks already placed
+            let mut best: Option<(u32, i128, Map<Address, PoolState>)> = None;
+            for (index, route) in routes.iter().enumerate() {
+                let mut trial = states.clone();
+                let (_, amount_out) = Self::simulate_path(&env, &factory, &mut trial, &token_in, part_amount, &route);
+                let is_better = match &best {
+                    Some((_, best_out, _)) => amount_out > *best_out,
+                    None => amount_out > 0,
+                };
+                if is_better {
+                    best = Some((index as u32, amount_out, trial));
+                }
+            }
+
+            let (index, _, trial) = best.expect("Insufficient liquidity for swap");
+            states = trial;
+            let assigned = route_amounts.get(index).unwrap();
+            route_amounts.set(index, checked_add(assigned, part_amount));
+        }
+
+        // Convert the chunk assignment into basis points, rounding dust into the largest leg
+        let mut splits: Vec<Split> = Vec::new(&env);
+        let mut total_bps: u32 = 0;
+        let mut largest: Option<(u32, i128)> = None;
+        for (route, amount) in routes.iter().zip(route_amounts.iter()) {
+            if amount == 0 {
+                continue;
+            }
+            let fraction_bps = checked_div(checked_mul(amount, BPS_DENOMINATOR), amount_in) as u32;
+            if largest.is_none_or(|(_, largest_amount)| amount > largest_amount) {
+                largest = Some((splits.len(), amount));
+            }
+            total_bps += fraction_bps;
+            splits.push_back(Split { path: route, fraction_bps });
+        }
+        let (largest_index, _) = largest.expect("Insufficient liquidity for swap");
+        let mut largest_split = splits.get(largest_index).unwrap();
+        largest_split.fraction_bps += BPS_DENOMINATOR as u32 - total_bps;
+        splits.set(largest_index, largest_split);
+
+        let amount_out = Self::get_split_amount_out(env, token_in, token_out, amount_in, splits.clone());
+        (splits, amount_out)
+    }
+
+    // Validate the splits and work out how much of `amount_in` each one receives
+    fn split_amounts(env: &Env, amount_in: i128, splits: &Vec<Split>) -> Vec<i128> {
+        assert!(!splits.is_empty(), "No splits provided");
+
+        let mut total_bps: u32 = 0;
+        for split in splits.iter() {
+            assert!(!split.path.is_empty(), "Split path is empty");
+            total_bps = total_bps.checked_add(split.fraction_bps).expect("Overflow in addition");
+        }
+        assert!(total_bps == BPS_DENOMINATOR as u32, "Split fractions must sum to 10000 bps");
+
+        // The last split takes the remainder so no input is left in the router
+        let mut amounts = vec![env];
+        let mut allocated = 0;
+        let last = splits.len() - 1;
+        for (index, split) in splits.iter().enumerate() {
+            let amount = if index as u32 == last {
+                checked_sub(amount_in, allocated)
+            } else {
+                checked_div(checked_mul(amount_in, split.fraction_bps as i128), BPS_DENOMINATOR)
+            };
+            allocated = checked_add(allocated, amount);
+            amounts.push_back(amount);
+        }
+        amounts
+    }
+
+    // Swap through each pool of a path using the router's own balance
+    fn execute_path(env: &Env, token_in: &Address, amount_in: i128, path: &Vec<Address>) -> (Address, i128) {
+        let router = env.current_contract_address();
+        let mut current_token = token_in.clone();
+        let mut current_amount = amount_in;
+
+        for pool in path.iter() {
+            let pool_client = PoolClient::new(env, &pool);
+            let token_a = pool_client.get_token_a();
+            let token_b = pool_client.get_token_b();
+            let next_token = if current_token == token_a {
+                token_b
+            } else if current_token == token_b {
+                token_a
+            } else {
+                panic!("Path does not connect");
+            };
+
+            // The pool pulls its input with transfer_from, so approve it for this hop only
+            token::Client::new(env, &current_token).approve(
+                &router,
+                &pool,
+                &current_amount,
+                &env.ledger().sequence(),
+            );
+            current_amount = pool_client.swap(&router, &current_token, &current_amount);
+            current_token = next_token;
+        }
+
+        (current_token, current_amount)
+    }
+
+    // Simulate a path against cached reserves, updating them as the pools would
+    fn simulate_path(
+        env: &Env,
+        factory: &FactoryClient,
+        states: &mut Map<Address, PoolState>,
+        token_in: &Address,
+        amount_in: i128,
+        path: &Vec<Address>,
+    ) -> (Address, i128) {
+        let mut current_token = token_in.clone();
+        let mut current_amount = amount_in;
+
+        for pool in path.iter() {
+            let mut state = Self::load_pool_state(env, factory, states, &pool);
+            let is_token_a_in = current_token == state.token_a;
+            assert!(is_token_a_in || current_token == state.token_b, "Path does not connect");
+
+            let next_token = if is_token_a_in { state.token_b.clone() } else { state.token_a.clone() };
+            let amount_out = if state.quoted {
+                Self::quote_hop(env, &pool, &mut state, &current_token, is_token_a_in, current_amount)
+            } else {
+                let (reserve_in, reserve_out) = if is_token_a_in {
+                    (state.reserve_a, state.reserve_b)
+                } else {
+                    (state.reserve_b, state.reserve_a)
+                };
+                get_amount_out(current_amount, reserve_in, reserve_out)
+            };
+
+            if is_token_a_in {
+                state.reserve_a = checked_add(state.reserve_a, current_amount);
+                state.reserve_b = checked_sub(state.reserve_b, amount_out);
+            } else {
+                state.reserve_b = checked_add(state.reserve_b, current_amount);
+                state.reserve_a = checked_sub(state.reserve_a, amount_out);
+            }
+            states.set(pool.clone(), state);
+
+            current_token = next_token;
+            current_amount = amount_out;
+            if current_amount == 0 {
+                break;
+            }
+        }
+
+        (current_token, current_amount)
+    }
+
+    // Output of a hop through a pool that isn't constant product: the pool's quote for everything routed
+    // through it so far, less the output earlier hops already took. Swaps in one direction compose, so
+    // this matches executing the hops one after another
+    fn quote_hop(
+        env: &Env,
+        pool: &Address,
+        state: &mut PoolState,
+        token_in: &Address,
+        is_token_a_in: bool,
+        amount_in: i128,
+    ) -> i128 {
+        assert!(state.quoted_in == 0 || state.quoted_a_in == is_token_a_in, "Paths cross a pool both ways");
+        let quoted_in = checked_add(state.quoted_in, amount_in);
+        // A pool that can't fill the amount quotes nothing more
+        let quoted_out = match PoolClient::new(env, pool).try_get_amount_out(token_in, &quoted_in) {
+            Ok(Ok(amount_out)) => amount_out.max(state.quoted_out),
+            _ => state.quoted_out,
+        };
+        let amount_out = checked_sub(quoted_out, state.quoted_out);
+        state.quoted_a_in = is_token_a_in;
+        state.quoted_in = quoted_in;
+        state.quoted_out = quoted_out;
+        amount_out
+    }
+
+    // Load a pool's tokens and reserves once per call, taking the tokens from the factory index when
+    // it has them so only the reserves need a call into the pool
+    fn load_pool_state(
+        env: &Env,
+        factory: &FactoryClient,
+        states: &mut Map<Address, PoolState>,
+        pool: &Address,
+    ) -> PoolState {
+        if let Some(state) = states.get(pool.clone()) {
+            return state;
+        }
+        let pool_client = PoolClient::new(env, pool);
+        let tokens = factory.get_pool_tokens(pool);
+        let (token_a, token_b) = if tokens.len() == 2 {
+            (tokens.get(0).unwrap(), tokens.get(1).unwrap())
+        } else {
+            (pool_client.get_token_a(), pool_client.get_token_b())
+        };
+        let (reserve_a, reserve_b) = pool_client.get_reserves();
+        // Pools the factory doesn't know are priced as constant product pools
+        let pool_type = factory.get_pool_type(pool).unwrap_or(PoolType::ConstantProduct);
+        let state = PoolState {
+            token_a,
+            token_b,
+            reserve_a,
+            reserve_b,
+            quoted: pool_type != PoolType::ConstantProduct,
+            quoted_a_in: false,
+            quoted_in: 0,
+            quoted_out: 0,
+        };
+        states.set(pool.clone(), state.clone());
+        state
+    }
+
+    // Every direct two-token pool for the pair plus every two-hop path through an intermediate token,
+    // looked up through the factory's token index and pair keys. Weighted pools swap through another
+    // interface and are left out
+    fn find_routes(
+        env: &Env,
+        factory: &FactoryClient,
+        states: &mut Map<Address, PoolState>,
+        token_in: &Address,
+        token_out: &Address,
+    ) -> Vec<Vec<Address>> {
+        let mut routes = Vec::new(env);
+        let token_pool_count = factory.get_token_pool_count(token_in);
+        let mut offset = 0;
+        while offset < token_pool_count {
+            for first in factory.get_pools_for_token(token_in, &offset, &ROUTE_PAGE_SIZE).iter() {
+                if !factory.get_pool_type(&first).is_some_and(|pool_type| PAIR_POOL_TYPES.contains(&pool_type)) {
+                    continue;
+                }
+                let first_state = Self::load_pool_state(env, factory, states, &first);
+                let intermediate = match other_token(&first_state, token_in) {
+                    Some(token) => token,
+                    None => continue,
+                };
+                if intermediate == *token_out {
+                    routes.push_back(vec![env, first]);
+                    continue;
+                }
+                for pool_type in PAIR_POOL_TYPES {
+                    if let Some(second) = factory.get_pool_by_type(&pool_type, &intermediate, token_out) {
+                        Self::load_pool_state(env, factory, states, &second);
+                        routes.push_back(vec![env, first.clone(), second]);
+                    }
+                }
+            }
+            offset += ROUTE_PAGE_SIZE;
+        }
+        routes
+    }
+}
+
+// Constant product output for a single hop, matching the pool's swap formula
+fn get_amount_out(amount_in: i128, reserve_in: i128, reserve_out: i128) -> i128 {
+    if amount_in <= 0 || reserve_in <= 0 || reserve_out <= 0 {
+        return 0;
+    }
+    let amount_in_with_fee = checked_div(checked_mul(amount_in, checked_sub(BPS_DENOMINATOR, FEE_BPS)), BPS_DENOMINATOR);
+    let numerator = checked_mul(reserve_out, amount_in_with_fee);
+    let denominator = checked_add(reserve_in, amount_in_with_fee);
+    checked_div(numerator, denominator)
+}
+
+fn other_token(state: &PoolState, token: &Address) -> Option<Address> {
+    if *token == state.token_a {
+        Some(state.token_b.clone())
+    } else if *token == state.token_b {
+        Some(state.token_a.clone())
+    } else {
+        None
+    }
+}
+
+mod test;
@@ -0,0 +1,428 @@
+#![cfg(test)]
+extern crate std;
+
+use soroban_sdk::{
+    vec, Env, String, Address, BytesN, FromVal,
+    testutils::{Address as _, Ledger},
+};
+
+use crate::{Router, RouterClient, Split};
+
+use pool::LiquidityPool;
+use pool::LiquidityPoolClient;
+use poolfactory::PoolFactory;
+use poolfactory::PoolFactoryClient;
+use poolfactory::PoolType;
+
+use ::token::Token;
+use ::token::TokenClient;
+
+mod contract {
+    soroban_sdk::contractimport!(file = "../../target/wasm32v1-none/release/pool.wasm");
+}
+
+mod stable_contract {
+    soroban_sdk::contractimport!(file = "../../target/wasm32v1-none/release/stablepool.wasm");
+}
+
+mod weighted_contract {
+    soroban_sdk::contractimport!(file = "../../target/wasm32v1-none/release/weightedpool.wasm");
+}
+
+// Helper function to create a test token
+fn create_token<'a>(e: &Env, admin: &Address) -> TokenClient<'a> {
+    let token_contract = e.register(
+        Token,
+        (
+            admin,
+            18_u32,
+            String::from_val(e, &"name"),
+            String::from_val(e, &"symbol"),
+        ),
+    );
+    TokenClient::new(e, &token_contract)
+}
+
+fn deploy_pool<'a>(e: &Env, token_a: &TokenClient<'a>, token_b: &TokenClient<'a>) -> LiquidityPoolClient<'a> {
+    let contract_id = e.register(
+        LiquidityPool,
+        (
+            &token_a.address,
+            &token_b.address,
+            String::from_val(e, &"LPToken"),
+            String::from_val(e, &"LP"),
+            Address::generate(e),
+        ),
+    );
+    LiquidityPoolClient::new(e, &contract_id)
+}
+
+fn deploy_router<'a>(e: &Env, factory: &Address) -> RouterClient<'a> {
+    let contract_id = e.register(Router, (factory,));
+    RouterClient::new(e, &contract_id)
+}
+
+fn seed_pool(pool: &LiquidityPoolClient, token_a: &TokenClient, token_b: &TokenClient, provider: &Address, amount_a: i128, amount_b: i128) {
+    token_a.approve(provider, &pool.address, &amount_a, &1000);
+    token_b.approve(provider, &pool.address, &amount_b, &1000);
+    pool.add_liquidity(provider, &amount_a, &amount_b);
+}
+
+#[test]
+fn test_swap_split_across_parallel_pools() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let provider = Address::generate(&env);
+    let trader = Address::generate(&env);
+
+    let token_a = create_token(&env, &provider);
+    let token_b = create_token(&env, &provider);
+    token_a.mint(&provider, &200_000_000_000);
+    token_b.mint(&provider, &200_000_000_000);
+    token_a.mint(&trader, &10_000_000_000);
+
+    // A deep and a shallow pool for the same pair
+    let deep_pool = deploy_pool(&env, &token_a, &token_b);
+    let shallow_pool = deploy_pool(&env, &token_a, &token_b);
+    seed_pool(&deep_pool, &token_a, &token_b, &provider, 100_000_000_000, 100_000_000_000);
+    seed_pool(&shallow_pool, &token_a, &token_b, &provider, 25_000_000_000, 25_000_000_000);
+
+    let router = deploy_router(&env, &env.register(PoolFactory, (&Address::generate(&env),)));
+    let amount_in = 10_000_000_000;
+    let splits = vec![
+        &env,
+        Split { path: vec![&env, deep_pool.address.clone()], fraction_bps: 8000 },
+        Split { path: vec![&env, shallow_pool.address.clone()], fraction_bps: 2000 },
+    ];
+    let single_route = vec![
+        &env,
+        Split { path: vec![&env, deep_pool.address.clone()], fraction_bps: 10000 },
+    ];
+
+    let quoted = router.get_split_amount_out(&token_a.address, &token_b.address, &amount_in, &splits);
+    let single_quoted = router.get_split_amount_out(&token_a.address, &token_b.address, &amount_in, &single_route);
+    assert!(quoted > single_quoted, "Splitting should beat the deep pool alone");
+
+    let amount_out = router.swap_split(&trader, &token_a.address, &token_b.address, &amount_in, &splits, &quoted);
+    assert_eq!(amount_out, quoted);
+
+    // Trader is settled and the router keeps nothing
+    assert_eq!(token_a.balance(&trader), 0);
+    assert_eq!(token_b.balance(&trader), amount_out);
+    assert_eq!(token_a.balance(&router.address), 0);
+    assert_eq!(token_b.balance(&router.address), 0);
+
+    // Each pool received its share of the input, its LP fee included
+    assert_eq!(token_a.balance(&deep_pool.address), 108_000_000_000);
+    assert_eq!(token_a.balance(&shallow_pool.address), 27_000_000_000);
+}
+
+#[test]
+fn test_swap_split_multi_hop_path() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let provider = Address::generate(&env);
+    let trader = Address::generate(&env);
+
+    let token_a = create_token(&env, &provider);
+    let token_b = create_token(&env, &provider);
+    let token_c = create_token(&env, &provider);
+    token_a.mint(&provider, &100_000_000_000);
+    token_b.mint(&provider, &100_000_000_000);
+    token_c.mint(&provider, &100_000_000_000);
+    token_a.mint(&trader, &1_000_000_000);
+
+    let pool_ab = deploy_pool(&env, &token_a, &token_b);
+    let pool_cb = deploy_pool(&env, &token_c, &token_b);
+    let pool_ac = deploy_pool(&env, &token_a, &token_c);
+    seed_pool(&pool_ab, &token_a, &token_b, &provider, 10_000_000_000, 10_000_000_000);
+    seed_pool(&pool_cb, &token_c, &token_b, &provider, 10_000_000_000, 10_000_000_000);
+    seed_pool(&pool_ac, &token_a, &token_c, &provider, 10_000_000_000, 10_000_000_000);
+
+    let router = deploy_router(&env, &env.register(PoolFactory, (&Address::generate(&env),)));
+    let splits = vec![
+        &env,
+        Split { path: vec![&env, pool_ab.address.clone()], fraction_bps: 5000 },
+        Split { path: vec![&env, pool_ac.address.clone(), pool_cb.address.clone()], fraction_bps: 5000 },
+    ];
+
+    let amount_out = router.swap_split(&trader, &token_a.address, &token_b.address, &1_000_000_000, &splits, &0);
+    assert!(amount_out > 0);
+    assert_eq!(token_b.balance(&trader), amount_out);
+    assert_eq!(token_c.balance(&router.address), 0);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient output amount")]
+fn test_swap_split_below_min_out_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let provider = Address::generate(&env);
+    let trader = Address::generate(&env);
+
+    let token_a = create_token(&env, &provider);
+    let token_b = create_token(&env, &provider);
+    token_a.mint(&provider, &20_000_000_000);
+    token_b.mint(&provider, &20_000_000_000);
+    token_a.mint(&trader, &1_000_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b);
+    seed_pool(&pool, &token_a, &token_b, &provider, 10_000_000_000, 10_000_000_000);
+
+    let router = deploy_router(&env, &env.register(PoolFactory, (&Address::generate(&env),)));
+    let splits = vec![
+        &env,
+        Split { path: vec![&env, pool.address.clone()], fraction_bps: 10000 },
+    ];
+
+    // The fee alone keeps output below a 1:1 rate
+    router.swap_split(&trader, &token_a.address, &token_b.address, &1_000_000_000, &splits, &1_000_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Split fractions must sum to 10000 bps")]
+fn test_swap_split_invalid_fractions_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let trader = Address::generate(&env);
+
+    let token_a = create_token(&env, &trader);
+    let token_b = create_token(&env, &trader);
+    let pool = deploy_pool(&env, &token_a, &token_b);
+
+    let router = deploy_router(&env, &env.register(PoolFactory, (&Address::generate(&env),)));
+    let splits = vec![
+        &env,
+        Split { path: vec![&env, pool.address.clone()], fraction_bps: 6000 },
+    ];
+    router.swap_split(&trader, &token_a.address, &token_b.address, &1_000, &splits, &0);
+}
+
+#[test]
+fn test_get_best_split_across_factory_pools() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let provider = Address::generate(&env);
+    let trader = Address::generate(&env);
+
+    let token_a = create_token(&env, &provider);
+    let token_b = create_token(&env, &provider);
+    let token_c = create_token(&env, &provider);
+    token_a.mint(&provider, &100_000_000_000);
+    token_b.mint(&provider, &100_000_000_000);
+    token_c.mint(&provider, &100_000_000_000);
+    token_a.mint(&trader, &5_000_000_000);
+
+    let factory = PoolFactoryClient::new(&env, &env.register(PoolFactory, (&admin,)));
+    let wasm_hash = env.deployer().upload_contract_wasm(contract::WASM);
+    factory.update_pool_wasm_hash(&admin, &wasm_hash);
+
+    let create = |token_x: &TokenClient, token_y: &TokenClient| {
+        let pool_addr = factory.create_pool(
+            &token_x.address,
+            &token_y.address,
+            &Some(String::from_val(&env, &"LPToken")),
+            &Some(String::from_val(&env, &"LP")),
+        );
+        LiquidityPoolClient::new(&env, &pool_addr)
+    };
+    let pool_ab = create(&token_a, &token_b);
+    let pool_ac = create(&token_a, &token_c);
+    let pool_cb = create(&token_c, &token_b);
+    seed_pool(&pool_ab, &token_a, &token_b, &provider, 20_000_000_000, 20_000_000_000);
+    seed_pool(&pool_ac, &token_a, &token_c, &provider, 20_000_000_000, 20_000_000_000);
+    seed_pool(&pool_cb, &token_c, &token_b, &provider, 20_000_000_000, 20_000_000_000);
+
+    let router = deploy_router(&env, &factory.address);
+    let amount_in = 5_000_000_000;
+    let (splits, expected_out) = router.get_best_split(&token_a.address, &token_b.address, &amount_in, &10);
+
+    // A large trade should use both the direct pool and the route through C
+    assert_eq!(splits.len(), 2);
+    let total_bps: u32 = splits.iter().map(|split| split.fraction_bps).sum();
+    assert_eq!(total_bps, 10000);
+
+    let direct_only = vec![
+        &env,
+        Split { path: vec![&env, pool_ab.address.clone()], fraction_bps: 10000 },
+    ];
+    let direct_out = router.get_split_amount_out(&token_a.address, &token_b.address, &amount_in, &direct_only);
+    assert!(expected_out > direct_out);
+
+    let amount_out = router.swap_split(&trader, &token_a.address, &token_b.address, &amount_in, &splits, &expected_out);
+    assert_eq!(amount_out, expected_out);
+}
+
+#[test]
+fn test_get_best_split_routes_through_stable_pools() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.min_persistent_entry_ttl = 500_000;
+        li.max_entry_ttl = 1_000_000;
+    });
+    let admin = Address::generate(&env);
+    let provider = Address::generate(&env);
+    let trader = Address::generate(&env);
+
+    let token_a = create_token(&env, &provider);
+    let token_b = create_token(&env, &provider);
+    token_a.mint(&provider, &100_000_000_000);
+    token_b.mint(&provider, &100_000_000_000);
+    token_a.mint(&trader, &2_000_000_000);
+
+    let factory = PoolFactoryClient::new(&env, &env.register(PoolFactory, (&admin,)));
+    factory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+    factory.update_pool_type_wasm_hash(&admin, &PoolType::Stable, &env.deployer().upload_contract_wasm(stable_contract::WASM));
+
+    // A stable pool for the same pair is priced by its own quotes
+    let stable_addr = factory.create_stable_pool(
+        &token_a.address,
+        &token_b.address,
+        &Some(String::from_val(&env, &"StableLPToken")),
+        &Some(String::from_val(&env, &"SLP")),
+        &100,
+    );
+    token_a.approve(&provider, &stable_addr, &10_000_000_000, &1000);
+    token_b.approve(&provider, &stable_addr, &10_000_000_000, &1000);
+    stable_contract::Client::new(&env, &stable_addr).add_liquidity(&provider, &10_000_000_000, &10_000_000_000);
+
+    // A weighted pool swaps through another interface and is left out
+    factory.update_pool_type_wasm_hash(&admin, &PoolType::Weighted, &env.deployer().upload_contract_wasm(weighted_contract::WASM));
+    let weighted_addr = factory.create_weighted_pool(
+        &vec![&env, token_a.address.clone(), token_b.address.clone()],
+        &vec![&env, 8000, 2000],
+        &30,
+        &Some(String::from_val(&env, &"WeightedLPToken")),
+        &Some(String::from_val(&env, &"WLP")),
+        &BytesN::from_array(&env, &[3; 32]),
+    );
+
+    let pool_addr = factory.create_pool(
+        &token_a.address,
+        &token_b.address,
+        &Some(String::from_val(&env, &"LPToken")),
+        &Some(String::from_val(&env, &"LP")),
+    );
+    let pool = LiquidityPoolClient::new(&env, &pool_addr);
+    seed_pool(&pool, &token_a, &token_b, &provider, 10_000_000_000, 10_000_000_000);
+
+    let router = deploy_router(&env, &factory.address);
+    let amount_in = 2_000_000_000;
+    let (splits, expected_out) = router.get_best_split(&token_a.address, &token_b.address, &amount_in, &10);
+    assert!(splits.iter().any(|split| split.path == vec![&env, stable_addr.clone()]));
+    assert!(splits.iter().all(|split| !split.path.contains(&weighted_addr)));
+
+    // The quoted output is what the pools pay out
+    let amount_out = router.swap_split(&trader, &token_a.address, &token_b.address, &amount_in, &splits, &expected_out);
+    assert_eq!(amount_out, expected_out);
+}
+
+#[test]
+fn test_get_best_split_second_hop_through_stable_pool() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.min_persistent_entry_ttl = 500_000;
+        li.max_entry_ttl = 1_000_000;
+    });
+    let admin = Address::generate(&env);
+    let provider = Address::generate(&env);
+    let trader = Address::generate(&env);
+
+    let token_a = create_token(&env, &provider);
+    let token_b = create_token(&env, &provider);
+    let token_c = create_token(&env, &provider);
+    token_a.mint(&provider, &100_000_000_000);
+    token_b.mint(&provider, &100_000_000_000);
+    token_c.mint(&provider, &100_000_000_000);
+    token_a.mint(&trader, &1_000_000_000);
+
+    let factory = PoolFactoryClient::new(&env, &env.register(PoolFactory, (&admin,)));
+    factory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+    factory.update_pool_type_wasm_hash(&admin, &PoolType::Stable, &env.deployer().upload_contract_wasm(stable_contract::WASM));
+
+    // A to C only has a constant product pool, C to B only a stable pool
+    let pool_addr = factory.create_pool(
+        &token_a.address,
+        &token_c.address,
+        &Some(String::from_val(&env, &"LPToken")),
+        &Some(String::from_val(&env, &"LP")),
+    );
+    let pool = LiquidityPoolClient::new(&env, &pool_addr);
+    seed_pool(&pool, &token_a, &token_c, &provider, 10_000_000_000, 10_000_000_000);
+    let stable_addr = factory.create_stable_pool(
+        &token_c.address,
+        &token_b.address,
+        &Some(String::from_val(&env, &"StableLPToken")),
+        &Some(String::from_val(&env, &"SLP")),
+        &100,
+    );
+    token_c.approve(&provider, &stable_addr, &10_000_000_000, &1000);
+    token_b.approve(&provider, &stable_addr, &10_000_000_000, &1000);
+    stable_contract::Client::new(&env, &stable_addr).add_liquidity(&provider, &10_000_000_000, &10_000_000_000);
+
+    let router = deploy_router(&env, &factory.address);
+    let amount_in = 1_000_000_000;
+    let (splits, expected_out) = router.get_best_split(&token_a.address, &token_b.address, &amount_in, &4);
+    assert_eq!(splits.len(), 1);
+    assert_eq!(splits.get(0).unwrap().path, vec![&env, pool_addr, stable_addr]);
+
+    let amount_out = router.swap_split(&trader, &token_a.address, &token_b.address, &amount_in, &splits, &expected_out);
+    assert_eq!(amount_out, expected_out);
+    assert_eq!(token_b.balance(&trader), expected_out);
+}
+
+#[test]
+fn test_get_best_split_pages_through_token_index() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+    let admin = Address::generate(&env);
+    let provider = Address::generate(&env);
+
+    let token_a = create_token(&env, &provider);
+    let token_b = create_token(&env, &provider);
+    token_a.mint(&provider, &100_000_000_000);
+    token_b.mint(&provider, &100_000_000_000);
+
+    let factory = PoolFactoryClient::new(&env, &env.register(PoolFactory, (&admin,)));
+    factory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+    let create = |token_x: &Address, token_y: &Address| {
+        factory.create_pool(
+            token_x,
+            token_y,
+            &Some(String::from_val(&env, &"LPToken")),
+            &Some(String::from_val(&env, &"LP")),
+        )
+    };
+
+    // More pools of the input token than fit in one page of the token index come before the route
+    for _ in 0..50 {
+        create(&token_a.address, &create_token(&env, &provider).address);
+    }
+    let pool_addr = create(&token_a.address, &token_b.address);
+    seed_pool(&LiquidityPoolClient::new(&env, &pool_addr), &token_a, &token_b, &provider, 10_000_000_000, 10_000_000_000);
+    assert_eq!(factory.get_token_pool_count(&token_a.address), 51);
+
+    let router = deploy_router(&env, &factory.address);
+    let (splits, _) = router.get_best_split(&token_a.address, &token_b.address, &1_000_000, &4);
+    assert_eq!(splits.len(), 1);
+    assert_eq!(splits.get(0).unwrap().path, vec![&env, pool_addr]);
+}
+
+#[test]
+#[should_panic(expected = "No route between tokens")]
+fn test_get_best_split_without_route_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let token_a = create_token(&env, &admin);
+    let token_b = create_token(&env, &admin);
+    let factory = PoolFactoryClient::new(&env, &env.register(PoolFactory, (&admin,)));
+
+    let router = deploy_router(&env, &factory.address);
+    router.get_best_split(&token_a.address, &token_b.address, &1_000, &4);
+}
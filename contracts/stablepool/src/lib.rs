@@ -0,0 +1,573 @@
+#![no_std]
+
+use soroban_sdk::{
+    contract, contractimpl, contracttype, symbol_short, Address, BytesN, Env, String,
+    token::{self, Interface as _},
+};
+
+use crate::math::{get_d, get_y, mul_div, A_PRECISION};
+
+// Constants
+const FEE_BPS: i128 = 4; // 0.04%, pegged pairs trade close to 1:1
+const IMBALANCE_FEE_BPS: i128 = FEE_BPS / 2; // fee * n / (4 * (n - 1)) for two coins
+const BPS_DENOMINATOR: i128 = 10000;
+const MIN_AMP: u32 = 1;
+const MAX_AMP: u32 = 1_000_000;
+const MAX_AMP_CHANGE: u32 = 10; // A can move at most 10x up or down in one ramp
+const MIN_RAMP_LEDGERS: u32 = 17280; // ~1 day of ledgers
+const SCHEMA_VERSION: u32 = 1; // Storage layout version, bump with every PoolInfo, AmpRamp or DataKey change
+
+// Overflow protection functions
+fn checked_add(a: i128, b: i128) -> i128 {
+    a.checked_add(b).expect("Overflow in addition")
+}
+
+fn checked_sub(a: i128, b: i128) -> i128 {
+    a.checked_sub(b).expect("Underflow in subtraction")
+}
+
+fn checked_mul(a: i128, b: i128) -> i128 {
+    a.checked_mul(b).expect("Overflow in multiplication")
+}
+
+fn checked_div(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        panic!("Division by zero");
+    }
+    a.checked_div(b).expect("Overflow in division")
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PoolInfo {
+    pub token_a: Address,
+    pub token_b: Address,
+    pub reserve_a: i128,
+    pub reserve_b: i128,
+    pub rate_a: i128, // Multiplier bringing token_a to the pair's highest decimals
+    pub rate_b: i128, // Multiplier bringing token_b to the pair's highest decimals
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct AmpRamp {
+    pub initial_amp: u32,    // Amplification at initial_ledger (scaled by A_PRECISION)
+    pub future_amp: u32,     // Amplification reached at future_ledger (scaled by A_PRECISION)
+    pub initial_ledger: u32, // Ledger the current ramp started
+    pub future_ledger: u32,  // Ledger the current ramp ends
+}
+
+#[contract]
+pub struct StableSwapPool;
+
+#[contractimpl]
+impl StableSwapPool {
+
+    fn check_nonnegative_amount(amount: i128) {
+        if amount < 0 {
+            panic!("negative amount is not allowed: {}", amount)
+        }
+    }
+
+    fn check_amp(amp: u32) {
+        assert!((MIN_AMP..=MAX_AMP).contains(&amp), "Amplification out of range");
+    }
+
+    fn get_pool_info(e: &Env) -> PoolInfo {
+        e.storage().instance().get(&symbol_short!("pool")).unwrap()
+    }
+
+    fn get_ramp(e: &Env) -> AmpRamp {
+        e.storage().instance().get(&DataKey::AmpRamp).unwrap()
+    }
+
+    // Amplification (scaled by A_PRECISION), linearly interpolated while a ramp is running
+    fn current_amp(e: &Env) -> u32 {
+        let ramp = Self::get_ramp(e);
+        let now = e.ledger().sequence();
+        if now >= ramp.future_ledger {
+            return ramp.future_amp;
+        }
+
+        let elapsed = (now - ramp.initial_ledger) as u64;
+        let duration = (ramp.future_ledger - ramp.initial_ledger) as u64;
+        let (initial, future) = (ramp.initial_amp as u64, ramp.future_amp as u64);
+        let amp = if future > initial {
+            initial + (future - initial) * elapsed / duration
+        } else {
+            initial - (initial - future) * elapsed / duration
+        };
+        amp as u32
+    }
+
+    fn compute_d(e: &Env, pool_info: &PoolInfo, reserve_a: i128, reserve_b: i128, amp: u32) -> i128 {
+        get_d(
+            e,
+            checked_mul(reserve_a, pool_info.rate_a),
+            checked_mul(reserve_b, pool_info.rate_b),
+            amp,
+        )
+    }
+
+    // Output for a swap against the current reserves, after the swap fee
+    fn calculate_amount_out(e: &Env, pool_info: &PoolInfo, is_token_a_in: bool, amount_in: i128) -> i128 {
+        let (reserve_in, reserve_out, rate_in, rate_out) = if is_token_a_in {
+            (pool_info.reserve_a, pool_info.reserve_b, pool_info.rate_a, pool_info.rate_b)
+        } else {
+            (pool_info.reserve_b, pool_info.reserve_a, pool_info.rate_b, pool_info.rate_a)
+        };
+        if reserve_in == 0 || reserve_out == 0 {
+            return 0;
+        }
+
+        let amp = Self::current_amp(e);
+        let d = Self::compute_d(e, pool_info, pool_info.reserve_a, pool_info.reserve_b, amp);
+        let x = checked_mul(checked_add(reserve_in, amount_in), rate_in);
+        let y = get_y(e, x, d, amp);
+
+        // Round against the trader by one unit
+        let dy = checked_sub(checked_sub(checked_mul(reserve_out, rate_out), y), 1);
+        if dy <= 0 {
+            return 0;
+        }
+        let fee = checked_div(checked_mul(dy, FEE_BPS), BPS_DENOMINATOR);
+        checked_div(checked_sub(dy, fee), rate_out)
+    }
+
+    // Fee on the part of a deposit that moves the pool away from its current ratio
+    fn imbalance_fee(e: &Env, d0: i128, d1: i128, old_reserve: i128, new_reserve: i128) -> i128 {
+        let ideal_reserve = mul_div(e, d1, old_reserve, d0);
+        let difference = if ideal_reserve > new_reserve {
+            checked_sub(ideal_reserve, new_reserve)
+        } else {
+            checked_sub(new_reserve, ideal_reserve)
+        };
+        checked_div(checked_mul(difference, IMBALANCE_FEE_BPS), BPS_DENOMINATOR)
+    }
+
+    pub fn __constructor(
+        e: Env,
+        token_a: Address,
+        token_b: Address,
+        lp_token_name: String,
+        lp_token_symbol: String,
+        admin: Address,
+        amp: u32,
+    ) {
+        assert!(token_a != token_b, "Tokens must be different");
+        Self::check_amp(amp);
+
+        // Normalize both tokens to the larger of the two decimals so the peg is 1:1
+        let decimals_a = token::Client::new(&e, &token_a).decimals();
+        let decimals_b = token::Client::new(&e, &token_b).decimals();
+        let max_decimals = decimals_a.max(decimals_b);
+
+        let pool_info = PoolInfo {
+            token_a,
+            token_b,
+            reserve_a: 0,
+            reserve_b: 0,
+            rate_a: 10i128.pow(max_decimals - decimals_a),
+            rate_b: 10i128.pow(max_decimals - decimals_b),
+        };
+
+        let ledger = e.ledger().sequence();
+        let ramp = AmpRamp {
+            initial_amp: amp * A_PRECISION,
+            future_amp: amp * A_PRECISION,
+            initial_ledger: ledger,
+            future_ledger: ledger,
+        };
+
+        e.storage().instance().set(&symbol_short!("pool"), &pool_info);
+        e.storage().instance().set(&DataKey::Admin, &admin);
+        e.storage().instance().set(&DataKey::AmpRamp, &ramp);
+        e.storage().instance().set(&symbol_short!("name"), &lp_token_name);
+        e.storage().instance().set(&symbol_short!("symbol"), &lp_token_symbol);
+        e.storage().instance().set(&symbol_short!("decimals"), &18u32);
+        e.storage().instance().set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+    }
+
+    /// Replace the pool code (admin only). The admin calls `migrate` right after, with the new code
+    pub fn upgrade(e: Env, new_wasm_hash: BytesN<32>) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        e.deployer().update_current_contract_wasm(new_wasm_hash.clone());
+        e.events().publish((symbol_short!("upgrade"),), new_wasm_hash);
+    }
+
+    /// Convert the storage written by older code to this code's layout (admin only).
+    /// Returns the schema version the storage is at
+    pub fn migrate(e: Env) -> u32 {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        let version = Self::schema_version(e.clone());
+        assert!(version <= SCHEMA_VERSION, "Storage is newer than the code");
+        // A layout change adds its conversion here, reading the previous PoolInfo or AmpRamp
+        // struct when `version` is below the one that introduced the change
+        if version < SCHEMA_VERSION {
+            e.storage().instance().set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+            e.events().publish((symbol_short!("migrate"),), (version, SCHEMA_VERSION));
+        }
+        SCHEMA_VERSION
+    }
+
+    pub fn add_liquidity(e: Env, caller: Address, amount_a: i128, amount_b: i128) -> i128 {
+        caller.require_auth();
+        Self::check_nonnegative_amount(amount_a);
+        Self::check_nonnegative_amount(amount_b);
+
+        let mut pool_info = Self::get_pool_info(&e);
+        let total_supply = Self::total_supply(e.clone());
+        if total_supply == 0 {
+            assert!(amount_a > 0 && amount_b > 0, "Initial deposit requires both tokens");
+        }
+
+        let amp = Self::current_amp(&e);
+        let d0 = Self::compute_d(&e, &pool_info, pool_info.reserve_a, pool_info.reserve_b, amp);
+        let new_reserve_a = checked_add(pool_info.reserve_a, amount_a);
+        let new_reserve_b = checked_add(pool_info.reserve_b, amount_b);
+        let d1 = Self::compute_d(&e, &pool_info, new_reserve_a, new_reserve_b, amp);
+        assert!(d1 > d0, "Insufficient liquidity minted");
+
+        // Unlike the constant product pool, deposits may be imbalanced; the imbalanced
+        // part pays the swap fee so a deposit can't be used as a cheaper swap
+        let liquidity = if total_supply > 0 {
+            let fee_a = Self::imbalance_fee(&e, d0, d1, pool_info.reserve_a, new_reserve_a);
+            let fee_b = Self::imbalance_fee(&e, d0, d1, pool_info.reserve_b, new_reserve_b);
+            let d2 = Self::compute_d(
+                &e,
+                &pool_info,
+                checked_sub(new_reserve_a, fee_a),
+                checked_sub(new_reserve_b, fee_b),
+                amp,
+            );
+            mul_div(&e, total_supply, checked_sub(d2, d0), d0)
+        } else {
+            d1
+        };
+        assert!(liquidity > 0, "Insufficient liquidity minted");
+
+        let token_a_client = token::Client::new(&e, &pool_info.token_a);
+        let token_b_client = token::Client::new(&e, &pool_info.token_b);
+        if amount_a > 0 {
+            token_a_client.transfer_from(&e.current_contract_address(), &caller, &e.current_contract_address(), &amount_a);
+        }
+        if amount_b > 0 {
+            token_b_client.transfer_from(&e.current_contract_address(), &caller, &e.current_contract_address(), &amount_b);
+        }
+
+        Self::mint_lp_tokens(&e, &caller, liquidity);
+
+        // Imbalance fees stay in the reserves and accrue to LPs
+        pool_info.reserve_a = new_reserve_a;
+        pool_info.reserve_b = new_reserve_b;
+        e.storage().instance().set(&symbol_short!("pool"), &pool_info);
+
+        liquidity
+    }
+
+    pub fn remove_liquidity(e: Env, caller: Address, liquidity: i128) -> (i128, i128) {
+        caller.require_auth();
+        Self::check_nonnegative_amount(liquidity);
+
+        let mut pool_info = Self::get_pool_info(&e);
+        let total_supply = Self::total_supply(e.clone());
+
+        assert!(total_supply > 0, "No liquidity in pool");
+        assert!(liquidity > 0, "Liquidity must be > 0");
+
+        let caller_balance = e.storage().instance().get(&DataKey::Balance(caller.clone())).unwrap_or(0);
+        assert!(liquidity <= caller_balance, "Insufficient LP tokens");
+
+        // Withdrawals are always proportional, so they pay no fee
+        let amount_a = mul_div(&e, liquidity, pool_info.reserve_a, total_supply);
+        let amount_b = mul_div(&e, liquidity, pool_info.reserve_b, total_supply);
+
+        Self::burn_lp_tokens(&e, &caller, liquidity);
+
+        let token_a_client = token::Client::new(&e, &pool_info.token_a);
+        let token_b_client = token::Client::new(&e, &pool_info.token_b);
+        if amount_a > 0 {
+            token_a_client.transfer(&e.current_contract_address(), &caller, &amount_a);
+        }
+        if amount_b > 0 {
+            token_b_client.transfer(&e.current_contract_address(), &caller, &amount_b);
+        }
+
+        pool_info.reserve_a = checked_sub(pool_info.reserve_a, amount_a);
+        pool_info.reserve_b = checked_sub(pool_info.reserve_b, amount_b);
+        e.storage().instance().set(&symbol_short!("pool"), &pool_info);
+
+        (amount_a, amount_b)
+    }
+
+    pub fn swap(e: Env, caller: Address, input_token: Address, amount_in: i128) -> i128 {
+        caller.require_auth();
+        Self::check_nonnegative_amount(amount_in);
+
+        let mut pool_info = Self::get_pool_info(&e);
+
+        assert!(amount_in > 0, "Amount in must be > 0");
+        assert!(
+            input_token == pool_info.token_a || input_token == pool_info.token_b,
+            "Invalid token address"
+        );
+
+        let is_token_a_in = input_token == pool_info.token_a;
+        let (token_out, reserve_out) = if is_token_a_in {
+            (pool_info.token_b.clone(), pool_info.reserve_b)
+        } else {
+            (pool_info.token_a.clone(), pool_info.reserve_a)
+        };
+
+        let amount_out = Self::calculate_amount_out(&e, &pool_info, is_token_a_in, amount_in);
+        assert!(amount_out > 0, "Insufficient output amount");
+        assert!(amount_out < reserve_out, "Insufficient pool reserves");
+
+        let token_in_client = token::Client::new(&e, &input_token);
+        token_in_client.transfer_from(&e.current_contract_address(), &caller, &e.current_contract_address(), &amount_in);
+        let token_out_client = token::Client::new(&e, &token_out);
+        token_out_client.transfer(&e.current_contract_address(), &caller, &amount_out);
+
+        // The fee stays in the reserves and accrues to LPs
+        let (volume_a, volume_b) = Self::get_token_volumes(e.clone());
+        let volumes = if is_token_a_in {
+            pool_info.reserve_a = checked_add(pool_info.reserve_a, amount_in);
+            pool_info.reserve_b = checked_sub(pool_info.reserve_b, amount_out);
+            (checked_add(volume_a, amount_in), checked_add(volume_b, amount_out))
+        } else {
+            pool_info.reserve_b = checked_add(pool_info.reserve_b, amount_in);
+            pool_info.reserve_a = checked_sub(pool_info.reserve_a, amount_out);
+            (checked_add(volume_a, amount_out), checked_add(volume_b, amount_in))
+        };
+        e.storage().instance().set(&symbol_short!("pool"), &pool_info);
+        e.storage().instance().set(&DataKey::TokenVolumes, &volumes);
+
+        amount_out
+    }
+
+    /// Start moving the amplification towards `future_amp`, reached at `future_ledger` (admin only)
+    pub fn ramp_amp(e: Env, future_amp: u32, future_ledger: u32) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        Self::check_amp(future_amp);
+
+        let ramp = Self::get_ramp(&e);
+        let now = e.ledger().sequence();
+        assert!(now >= ramp.initial_ledger + MIN_RAMP_LEDGERS, "Ramp started too recently");
+        assert!(future_ledger >= now + MIN_RAMP_LEDGERS, "Ramp duration too short");
+
+        let initial_amp = Self::current_amp(&e);
+        let future_amp = future_amp * A_PRECISION;
+        if future_amp > initial_amp {
+            assert!(future_amp <= initial_amp * MAX_AMP_CHANGE, "Amplification change too large");
+        } else {
+            assert!(future_amp * MAX_AMP_CHANGE >= initial_amp, "Amplification change too large");
+        }
+
+        e.storage().instance().set(&DataKey::AmpRamp, &AmpRamp {
+            initial_amp,
+            future_amp,
+            initial_ledger: now,
+            future_ledger,
+        });
+    }
+
+    /// Freeze the amplification at its current value (admin only)
+    pub fn stop_ramp_amp(e: Env) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        let amp = Self::current_amp(&e);
+        let now = e.ledger().sequence();
+        e.storage().instance().set(&DataKey::AmpRamp, &AmpRamp {
+            initial_amp: amp,
+            future_amp: amp,
+            initial_ledger: now,
+            future_ledger: now,
+        });
+    }
+
+    // View functions
+    pub fn get_token_a(e: Env) -> Address {
+        Self::get_pool_info(&e).token_a
+    }
+
+    pub fn get_token_b(e: Env) -> Address {
+        Self::get_pool_info(&e).token_b
+    }
+
+    pub fn get_reserves(e: Env) -> (i128, i128) {
+        let pool_info = Self::get_pool_info(&e);
+        (pool_info.reserve_a, pool_info.reserve_b)
+    }
+
+    /// All-time swap volume of (token A, token B), each counting what was swapped in and out in that token
+    pub fn get_token_volumes(e: Env) -> (i128, i128) {
+        e.storage().instance().get(&DataKey::TokenVolumes).unwrap_or((0, 0))
+    }
+
+    pub fn get_admin(e: Env) -> Address {
+        e.storage().instance().get(&DataKey::Admin).unwrap()
+    }
+
+    /// Storage layout version, 1 for pools created before versioning
+    pub fn schema_version(e: Env) -> u32 {
+        e.storage().instance().get(&DataKey::SchemaVersion).unwrap_or(1)
+    }
+
+    /// Current amplification coefficient A
+    pub fn get_amp(e: Env) -> u32 {
+        Self::current_amp(&e) / A_PRECISION
+    }
+
+    pub fn get_amp_ramp(e: Env) -> AmpRamp {
+        Self::get_ramp(&e)
+    }
+
+    pub fn get_amount_out(e: Env, input_token: Address, amount_in: i128) -> i128 {
+        Self::check_nonnegative_amount(amount_in);
+        let pool_info = Self::get_pool_info(&e);
+        assert!(
+            input_token == pool_info.token_a || input_token == pool_info.token_b,
+            "Invalid token address"
+        );
+        Self::calculate_amount_out(&e, &pool_info, input_token == pool_info.token_a, amount_in)
+    }
+
+    /// Value of one LP token in normalized pool units, scaled by 1e18
+    pub fn get_virtual_price(e: Env) -> i128 {
+        let total_supply = Self::total_supply(e.clone());
+        if total_supply == 0 {
+            return 0;
+        }
+        let pool_info = Self::get_pool_info(&e);
+        let d = Self::compute_d(&e, &pool_info, pool_info.reserve_a, pool_info.reserve_b, Self::current_amp(&e));
+        mul_div(&e, d, 1_000_000_000_000_000_000, total_supply)
+    }
+
+    pub fn supply(e: Env) -> i128 {
+        Self::total_supply(e)
+    }
+
+    fn total_supply(e: Env) -> i128 {
+        e.storage().instance().get(&DataKey::TotalSupply).unwrap_or(0)
+    }
+
+    fn mint_lp_tokens(e: &Env, to: &Address, amount: i128) {
+        let total_supply = Self::total_supply(e.clone());
+        e.storage().instance().set(&DataKey::TotalSupply, &checked_add(total_supply, amount));
+
+        let balance = e.storage().instance().get(&DataKey::Balance(to.clone())).unwrap_or(0);
+        e.storage().instance().set(&DataKey::Balance(to.clone()), &checked_add(balance, amount));
+    }
+
+    fn burn_lp_tokens(e: &Env, from: &Address, amount: i128) {
+        let total_supply = Self::total_supply(e.clone());
+        e.storage().instance().set(&DataKey::TotalSupply, &checked_sub(total_supply, amount));
+
+        let balance = e.storage().instance().get(&DataKey::Balance(from.clone())).unwrap_or(0);
+        e.storage().instance().set(&DataKey::Balance(from.clone()), &checked_sub(balance, amount));
+    }
+
+    pub fn balance_of(e: Env, id: Address) -> i128 {
+        <StableSwapPool as token::Interface>::balance(e, id)
+    }
+}
+
+#[contractimpl]
+impl token::Interface for StableSwapPool {
+    fn allowance(e: Env, from: Address, spender: Address) -> i128 {
+        e.storage().instance().get(&DataKey::Allowance(from, spender)).unwrap_or(0)
+    }
+
+    fn approve(e: Env, from: Address, spender: Address, amount: i128, _expiration_ledger: u32) {
+        from.require_auth();
+        Self::check_nonnegative_amount(amount);
+        e.storage().instance().set(&DataKey::Allowance(from, spender), &amount);
+    }
+
+    fn balance(e: Env, id: Address) -> i128 {
+        e.storage().instance().get(&DataKey::Balance(id)).unwrap_or(0)
+    }
+
+    fn transfer(e: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        Self::check_nonnegative_amount(amount);
+
+        let balance = Self::balance(e.clone(), from.clone());
+        assert!(balance >= amount, "Insufficient balance");
+
+        let to_balance = Self::balance(e.clone(), to.clone());
+        e.storage().instance().set(&DataKey::Balance(from), &checked_sub(balance, amount));
+        e.storage().instance().set(&DataKey::Balance(to), &checked_add(to_balance, amount));
+    }
+
+    fn transfer_from(e: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        spender.require_auth();
+        Self::check_nonnegative_amount(amount);
+
+        let allowance = Self::allowance(e.clone(), from.clone(), spender.clone());
+        assert!(allowance >= amount, "Insufficient allowance");
+
+        let balance = Self::balance(e.clone(), from.clone());
+        assert!(balance >= amount, "Insufficient balance");
+
+        let to_balance = Self::balance(e.clone(), to.clone());
+        e.storage().instance().set(&DataKey::Allowance(from.clone(), spender), &checked_sub(allowance, amount));
+        e.storage().instance().set(&DataKey::Balance(from), &checked_sub(balance, amount));
+        e.storage().instance().set(&DataKey::Balance(to), &checked_add(to_balance, amount));
+    }
+
+    fn burn(e: Env, from: Address, amount: i128) {
+        from.require_auth();
+        Self::check_nonnegative_amount(amount);
+
+        let balance = Self::balance(e.clone(), from.clone());
+        assert!(balance >= amount, "Insufficient balance");
+
+        Self::burn_lp_tokens(&e, &from, amount);
+    }
+
+    fn burn_from(e: Env, spender: Address, from: Address, amount: i128) {
+        spender.require_auth();
+        Self::check_nonnegative_amount(amount);
+
+        let allowance = Self::allowance(e.clone(), from.clone(), spender.clone());
+        assert!(allowance >= amount, "Insufficient allowance");
+
+        let balance = Self::balance(e.clone(), from.clone());
+        assert!(balance >= amount, "Insufficient balance");
+
+        e.storage().instance().set(&DataKey::Allowance(from.clone(), spender), &checked_sub(allowance, amount));
+        Self::burn_lp_tokens(&e, &from, amount);
+    }
+
+    fn decimals(e: Env) -> u32 {
+        e.storage().instance().get(&symbol_short!("decimals")).unwrap_or(18)
+    }
+
+    fn name(e: Env) -> String {
+        e.storage().instance().get(&symbol_short!("name")).unwrap_or(String::from_str(&e, "Stable Pool Token"))
+    }
+
+    fn symbol(e: Env) -> String {
+        e.storage().instance().get(&symbol_short!("symbol")).unwrap_or(String::from_str(&e, "SLP"))
+    }
+}
+
+#[derive(Clone)]
+#[contracttype]
+enum DataKey {
+    Admin,
+    AmpRamp,
+    Balance(Address),
+    Allowance(Address, Address),
+    TotalSupply,
+    TokenVolumes, // All-time swap volume of (token A, token B)
+    SchemaVersion, // Storage layout version, see SCHEMA_VERSION
+}
+
+mod math;
+mod test;
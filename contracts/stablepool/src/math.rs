@@ -0,0 +1,97 @@
+//! StableSwap invariant for two coins, computed in U256 to keep D^3 terms from overflowing.
+//!
+//! A * n^n * sum(x) + D = A * D * n^n + D^(n+1) / (n^n * prod(x))
+use soroban_sdk::{Env, U256};
+
+// Amplification values are stored multiplied by A_PRECISION so ramps move smoothly
+pub(crate) const A_PRECISION: u32 = 100;
+const N_COINS: u32 = 2;
+const MAX_ITERATIONS: u32 = 255;
+
+fn to_u256(e: &Env, value: i128) -> U256 {
+    if value < 0 {
+        panic!("negative amount is not allowed: {}", value)
+    }
+    U256::from_u128(e, value as u128)
+}
+
+fn to_i128(value: &U256) -> i128 {
+    let value = value.to_u128().expect("Overflow in conversion");
+    i128::try_from(value).expect("Overflow in conversion")
+}
+
+fn converged(a: &U256, b: &U256) -> bool {
+    let diff = if a > b { a.sub(b) } else { b.sub(a) };
+    diff <= U256::from_u32(a.env(), 1)
+}
+
+/// Invariant D for normalized balances `x0`, `x1` and amplification `amp` (scaled by A_PRECISION)
+pub(crate) fn get_d(e: &Env, x0: i128, x1: i128, amp: u32) -> i128 {
+    if x0 == 0 && x1 == 0 {
+        return 0;
+    }
+    assert!(x0 > 0 && x1 > 0, "Pool balances must be > 0");
+
+    let n = U256::from_u32(e, N_COINS);
+    let a_precision = U256::from_u32(e, A_PRECISION);
+    let x0 = to_u256(e, x0);
+    let x1 = to_u256(e, x1);
+    let s = x0.add(&x1);
+    let ann = U256::from_u32(e, amp).mul(&n);
+
+    let mut d = s.clone();
+    for _ in 0..MAX_ITERATIONS {
+        let d_p = d.mul(&d).div(&x0.mul(&n)).mul(&d).div(&x1.mul(&n));
+        let d_prev = d.clone();
+        let numerator = ann.mul(&s).div(&a_precision).add(&d_p.mul(&n)).mul(&d);
+        let denominator = ann
+            .sub(&a_precision)
+            .mul(&d)
+            .div(&a_precision)
+            .add(&n.add(&U256::from_u32(e, 1)).mul(&d_p));
+        d = numerator.div(&denominator);
+        if converged(&d, &d_prev) {
+            return to_i128(&d);
+        }
+    }
+    panic!("Invariant did not converge");
+}
+
+/// Balance of the other coin that keeps invariant `d` when one coin's normalized balance is `x`
+pub(crate) fn get_y(e: &Env, x: i128, d: i128, amp: u32) -> i128 {
+    assert!(x > 0, "Pool balances must be > 0");
+
+    let n = U256::from_u32(e, N_COINS);
+    let a_precision = U256::from_u32(e, A_PRECISION);
+    let x = to_u256(e, x);
+    let d = to_u256(e, d);
+    let ann = U256::from_u32(e, amp).mul(&n);
+
+    let c = d
+        .mul(&d)
+        .div(&x.mul(&n))
+        .mul(&d)
+        .mul(&a_precision)
+        .div(&ann.mul(&n));
+    let b = x.add(&d.mul(&a_precision).div(&ann));
+
+    let mut y = d.clone();
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y.clone();
+        let numerator = y.mul(&y).add(&c);
+        let denominator = y.mul(&U256::from_u32(e, 2)).add(&b).sub(&d);
+        y = numerator.div(&denominator);
+        if converged(&y, &y_prev) {
+            return to_i128(&y);
+        }
+    }
+    panic!("Invariant did not converge");
+}
+
+/// a * b / c without intermediate overflow
+pub(crate) fn mul_div(e: &Env, a: i128, b: i128, c: i128) -> i128 {
+    if c == 0 {
+        panic!("Division by zero");
+    }
+    to_i128(&to_u256(e, a).mul(&to_u256(e, b)).div(&to_u256(e, c)))
+}
@@ -0,0 +1,326 @@
+#![cfg(test)]
+extern crate std;
+use soroban_sdk::{
+    Env, String, Address, FromVal,
+    testutils::{Address as _, Ledger},
+};
+use crate::StableSwapPool;
+use crate::StableSwapPoolClient;
+use ::token::Token;
+use ::token::TokenClient;
+
+// Helper function to create a test token
+fn create_token<'a>(e: &Env, admin: &Address, decimals: u32) -> TokenClient<'a> {
+    let token_contract = e.register(
+        Token,
+        (
+            admin,
+            decimals,
+            String::from_val(e, &"name"),
+            String::from_val(e, &"symbol"),
+        ),
+    );
+    TokenClient::new(e, &token_contract)
+}
+
+fn deploy_pool<'a>(e: &Env, token_a: &TokenClient<'a>, token_b: &TokenClient<'a>, admin: &Address, amp: u32) -> StableSwapPoolClient<'a> {
+    let contract_id = e.register(
+        StableSwapPool,
+        (
+            &token_a.address,
+            &token_b.address,
+            String::from_val(e, &"StableLPToken"),
+            String::from_val(e, &"SLP"),
+            admin,
+            amp,
+        ),
+    );
+    StableSwapPoolClient::new(e, &contract_id)
+}
+
+fn add_liquidity(pool: &StableSwapPoolClient, token_a: &TokenClient, token_b: &TokenClient, user: &Address, amount_a: i128, amount_b: i128) -> i128 {
+    token_a.approve(user, &pool.address, &amount_a, &1000);
+    token_b.approve(user, &pool.address, &amount_b, &1000);
+    pool.add_liquidity(user, &amount_a, &amount_b)
+}
+
+// Keep contract storage alive across the multi-day ledger ranges amp ramps need
+fn extend_ledger_ttls(e: &Env) {
+    e.ledger().with_mut(|li| {
+        li.min_persistent_entry_ttl = 500_000;
+        li.min_temp_entry_ttl = 500_000;
+        li.max_entry_ttl = 1_000_000;
+    });
+}
+
+#[test]
+fn test_add_initial_liquidity() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user, 7);
+    let token_b = create_token(&env, &user, 7);
+    token_a.mint(&user, &10_000_000_000);
+    token_b.mint(&user, &10_000_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b, &user, 100);
+    let liquidity = add_liquidity(&pool, &token_a, &token_b, &user, 10_000_000_000, 10_000_000_000);
+
+    // A balanced pool's invariant equals the sum of its balances
+    assert_eq!(liquidity, 20_000_000_000);
+    assert_eq!(pool.balance_of(&user), 20_000_000_000);
+    assert_eq!(pool.get_reserves(), (10_000_000_000, 10_000_000_000));
+    assert_eq!(pool.get_virtual_price(), 1_000_000_000_000_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Initial deposit requires both tokens")]
+fn test_initial_single_sided_deposit_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user, 7);
+    let token_b = create_token(&env, &user, 7);
+    token_a.mint(&user, &10_000_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b, &user, 100);
+    add_liquidity(&pool, &token_a, &token_b, &user, 10_000_000_000, 0);
+}
+
+#[test]
+fn test_swap_stays_close_to_peg() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user, 7);
+    let token_b = create_token(&env, &user, 7);
+    token_a.mint(&user, &120_000_000_000);
+    token_b.mint(&user, &100_000_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b, &user, 200);
+    add_liquidity(&pool, &token_a, &token_b, &user, 100_000_000_000, 100_000_000_000);
+
+    // A 10% of reserves trade on x*y=k would lose ~9% to slippage; here it stays within 0.1%
+    let swap_amount = 10_000_000_000;
+    let quoted = pool.get_amount_out(&token_a.address, &swap_amount);
+    token_a.approve(&user, &pool.address, &swap_amount, &1000);
+    let amount_out = pool.swap(&user, &token_a.address, &swap_amount);
+
+    assert_eq!(amount_out, quoted);
+    assert!(amount_out < swap_amount);
+    assert!(amount_out > 9_990_000_000);
+    assert_eq!(pool.get_reserves(), (110_000_000_000, 100_000_000_000 - amount_out));
+    assert_eq!(pool.get_token_volumes(), (swap_amount, amount_out));
+
+    // Fees stay in the pool, so each LP token is worth more
+    assert!(pool.get_virtual_price() > 1_000_000_000_000_000_000);
+}
+
+#[test]
+fn test_swap_between_different_decimals() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user, 6);
+    let token_b = create_token(&env, &user, 7);
+    token_a.mint(&user, &11_000_000_000);
+    token_b.mint(&user, &100_000_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b, &user, 100);
+    add_liquidity(&pool, &token_a, &token_b, &user, 10_000_000_000, 100_000_000_000);
+
+    // 1 unit of a 6-decimal token trades for ~1 unit of a 7-decimal token
+    token_a.approve(&user, &pool.address, &1_000_000, &1000);
+    let amount_out = pool.swap(&user, &token_a.address, &1_000_000);
+    assert!(amount_out > 9_990_000 && amount_out < 10_000_000);
+}
+
+#[test]
+fn test_imbalanced_deposit_pays_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+
+    let token_a = create_token(&env, &user1, 7);
+    let token_b = create_token(&env, &user1, 7);
+    token_a.mint(&user1, &10_000_000_000);
+    token_b.mint(&user1, &10_000_000_000);
+    token_a.mint(&user2, &2_000_000_000);
+    token_b.mint(&user2, &1_000_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b, &user1, 100);
+    add_liquidity(&pool, &token_a, &token_b, &user1, 10_000_000_000, 10_000_000_000);
+
+    // Balanced deposits mint LP 1:1 with value added
+    let balanced = add_liquidity(&pool, &token_a, &token_b, &user2, 1_000_000_000, 1_000_000_000);
+    assert_eq!(balanced, 2_000_000_000);
+
+    // A single-sided deposit of the same value mints fewer LP tokens
+    let single_sided = add_liquidity(&pool, &token_a, &token_b, &user2, 1_000_000_000, 0);
+    assert!(single_sided > 0);
+    assert!(single_sided < 1_000_000_000);
+}
+
+#[test]
+fn test_remove_liquidity() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user, 7);
+    let token_b = create_token(&env, &user, 7);
+    token_a.mint(&user, &10_000_000_000);
+    token_b.mint(&user, &5_000_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b, &user, 100);
+    let liquidity = add_liquidity(&pool, &token_a, &token_b, &user, 10_000_000_000, 5_000_000_000);
+
+    let (amount_a, amount_b) = pool.remove_liquidity(&user, &(liquidity / 2));
+    assert_eq!(amount_a, 5_000_000_000);
+    assert_eq!(amount_b, 2_500_000_000);
+    assert_eq!(pool.balance_of(&user), liquidity - liquidity / 2);
+    assert_eq!(token_a.balance(&user), 5_000_000_000);
+    assert_eq!(token_b.balance(&user), 2_500_000_000);
+}
+
+#[test]
+fn test_transfer_lp_tokens() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    let spender = Address::generate(&env);
+
+    let token_a = create_token(&env, &user, 7);
+    let token_b = create_token(&env, &user, 7);
+    token_a.mint(&user, &10_000_000_000);
+    token_b.mint(&user, &10_000_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b, &user, 100);
+    let liquidity = add_liquidity(&pool, &token_a, &token_b, &user, 10_000_000_000, 10_000_000_000);
+
+    pool.transfer(&user, &user2, &5_000_000_000);
+    assert_eq!(pool.balance(&user), liquidity - 5_000_000_000);
+    assert_eq!(pool.balance(&user2), 5_000_000_000);
+
+    pool.approve(&user2, &spender, &2_000_000_000, &1000);
+    pool.transfer_from(&spender, &user2, &user, &2_000_000_000);
+    assert_eq!(pool.allowance(&user2, &spender), 0);
+    assert_eq!(pool.balance(&user), liquidity - 3_000_000_000);
+    assert_eq!(pool.symbol(), String::from_val(&env, &"SLP"));
+
+    // The new holder can redeem the transferred LP tokens
+    let (amount_a, amount_b) = pool.remove_liquidity(&user2, &3_000_000_000);
+    assert_eq!((amount_a, amount_b), (1_500_000_000, 1_500_000_000));
+}
+
+#[test]
+#[should_panic(expected = "Insufficient LP tokens")]
+fn test_remove_insufficient_lp_tokens_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user, 7);
+    let token_b = create_token(&env, &user, 7);
+    token_a.mint(&user, &1_000_000_000);
+    token_b.mint(&user, &1_000_000_000);
+
+    let pool = deploy_pool(&env, &token_a, &token_b, &user, 100);
+    let liquidity = add_liquidity(&pool, &token_a, &token_b, &user, 1_000_000_000, 1_000_000_000);
+    pool.remove_liquidity(&user, &(liquidity + 1));
+}
+
+#[test]
+#[should_panic(expected = "Invalid token address")]
+fn test_swap_invalid_token_address_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user, 7);
+    let token_b = create_token(&env, &user, 7);
+    let invalid_token = create_token(&env, &user, 7);
+
+    let pool = deploy_pool(&env, &token_a, &token_b, &user, 100);
+    pool.swap(&user, &invalid_token.address, &1_000_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Amplification out of range")]
+fn test_zero_amp_panics() {
+    let env = Env::default();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user, 7);
+    let token_b = create_token(&env, &user, 7);
+    deploy_pool(&env, &token_a, &token_b, &user, 0);
+}
+
+#[test]
+fn test_ramp_amp() {
+    let env = Env::default();
+    env.mock_all_auths();
+    extend_ledger_ttls(&env);
+    let admin = Address::generate(&env);
+
+    let token_a = create_token(&env, &admin, 7);
+    let token_b = create_token(&env, &admin, 7);
+    let pool = deploy_pool(&env, &token_a, &token_b, &admin, 100);
+    assert_eq!(pool.get_amp(), 100);
+
+    let start = env.ledger().sequence() + 20_000;
+    env.ledger().set_sequence_number(start);
+    pool.ramp_amp(&200, &(start + 40_000));
+
+    // Halfway through the ramp A is halfway between the two values
+    env.ledger().set_sequence_number(start + 20_000);
+    assert_eq!(pool.get_amp(), 150);
+
+    // Stopping freezes A where it is
+    pool.stop_ramp_amp();
+    env.ledger().set_sequence_number(start + 40_000);
+    assert_eq!(pool.get_amp(), 150);
+
+    let ramp = pool.get_amp_ramp();
+    assert_eq!(ramp.initial_amp, ramp.future_amp);
+}
+
+#[test]
+#[should_panic(expected = "Amplification change too large")]
+fn test_ramp_amp_too_large_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    extend_ledger_ttls(&env);
+    let admin = Address::generate(&env);
+
+    let token_a = create_token(&env, &admin, 7);
+    let token_b = create_token(&env, &admin, 7);
+    let pool = deploy_pool(&env, &token_a, &token_b, &admin, 100);
+
+    let start = env.ledger().sequence() + 20_000;
+    env.ledger().set_sequence_number(start);
+    pool.ramp_amp(&1_001, &(start + 20_000));
+}
+
+#[test]
+#[should_panic(expected = "Ramp duration too short")]
+fn test_ramp_amp_too_fast_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    extend_ledger_ttls(&env);
+    let admin = Address::generate(&env);
+
+    let token_a = create_token(&env, &admin, 7);
+    let token_b = create_token(&env, &admin, 7);
+    let pool = deploy_pool(&env, &token_a, &token_b, &admin, 100);
+
+    let start = env.ledger().sequence() + 20_000;
+    env.ledger().set_sequence_number(start);
+    pool.ramp_amp(&200, &(start + 100));
+}
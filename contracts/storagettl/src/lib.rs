@@ -0,0 +1,49 @@
+//! Storage helpers that keep entries alive while they are used, shared by the contracts that store
+//! per-user or per-item records.
+//!
+//! Persistent entries are bumped on every read and write to the same lifetime as token balances,
+//! and the instance to the token's instance lifetime, so anything a user still interacts with
+//! doesn't get archived.
+#![no_std]
+
+use soroban_sdk::{Env, IntoVal, TryFromVal, Val};
+
+pub const DAY_IN_LEDGERS: u32 = 17280;
+pub const INSTANCE_BUMP_AMOUNT: u32 = 7 * DAY_IN_LEDGERS;
+pub const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+pub const PERSISTENT_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub const PERSISTENT_LIFETIME_THRESHOLD: u32 = PERSISTENT_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+/// Read a persistent entry, extending its TTL if it exists
+pub fn read_persistent<K, V>(e: &Env, key: &K) -> Option<V>
+where
+    K: IntoVal<Env, Val>,
+    V: TryFromVal<Env, Val>,
+{
+    let value = e.storage().persistent().get::<K, V>(key);
+    if value.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(key, PERSISTENT_LIFETIME_THRESHOLD, PERSISTENT_BUMP_AMOUNT);
+    }
+    value
+}
+
+/// Write a persistent entry and extend its TTL
+pub fn write_persistent<K, V>(e: &Env, key: &K, value: &V)
+where
+    K: IntoVal<Env, Val>,
+    V: IntoVal<Env, Val>,
+{
+    e.storage().persistent().set(key, value);
+    e.storage()
+        .persistent()
+        .extend_ttl(key, PERSISTENT_LIFETIME_THRESHOLD, PERSISTENT_BUMP_AMOUNT);
+}
+
+/// Extend the TTL of the contract instance
+pub fn bump_instance(e: &Env) {
+    e.storage()
+        .instance()
+        .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
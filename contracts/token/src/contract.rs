@@ -1,13 +1,15 @@
 //! interface.
 use crate::admin::{read_administrator, write_administrator};
 use crate::allowance::{read_allowance, spend_allowance, write_allowance};
+use crate::authorization::{
+    check_authorized, read_authorization_required, read_authorized, write_authorization_required,
+    write_authorized,
+};
 use crate::balance::{read_balance, receive_balance, spend_balance};
 use crate::metadata::{read_decimal, read_name, read_symbol, write_metadata};
-#[cfg(test)]
-use crate::storage_types::{AllowanceDataKey, AllowanceValue, DataKey};
 use crate::storage_types::{INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD};
 use soroban_sdk::token::{self, Interface as _};
-use soroban_sdk::{contract, contractimpl, Address, Env, String};
+use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env, String};
 use soroban_token_sdk::metadata::TokenMetadata;
 use soroban_token_sdk::TokenUtils;
 
@@ -46,6 +48,8 @@ impl Token {
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
+        // Minting to an unauthorized account would make it look like an existing holder
+        check_authorized(&e, &to);
         receive_balance(&e, to.clone(), amount);
         TokenUtils::new(&e).events().mint(admin, to, amount);
     }
@@ -62,6 +66,40 @@ impl Token {
         TokenUtils::new(&e).events().set_admin(admin, new_admin);
     }
 
+    /// Freeze (`authorize = false`) or unfreeze an account (admin only)
+    pub fn set_authorized(e: Env, id: Address, authorize: bool) {
+        let admin = read_administrator(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_authorized(&e, id.clone(), authorize);
+        TokenUtils::new(&e).events().set_authorized(admin, id, authorize);
+    }
+
+    /// When required, new accounts the admin hasn't authorized yet start frozen (admin only)
+    pub fn set_authorization_required(e: Env, required: bool) {
+        let admin = read_administrator(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_authorization_required(&e, required);
+        e.events().publish((symbol_short!("auth_req"), admin), required);
+    }
+
+    pub fn authorized(e: Env, id: Address) -> bool {
+        read_authorized(&e, id)
+    }
+
+    pub fn authorization_required(e: Env) -> bool {
+        read_authorization_required(&e)
+    }
+
    
 }
 
@@ -105,6 +143,8 @@ impl token::Interface for Token {
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
+        check_authorized(&e, &from);
+        check_authorized(&e, &to);
         spend_balance(&e, from.clone(), amount);
         receive_balance(&e, to.clone(), amount);
         TokenUtils::new(&e).events().transfer(from, to, amount);
@@ -119,6 +159,8 @@ impl token::Interface for Token {
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
+        check_authorized(&e, &from);
+        check_authorized(&e, &to);
         spend_allowance(&e, from.clone(), spender, amount);
         spend_balance(&e, from.clone(), amount);
         receive_balance(&e, to.clone(), amount);
@@ -134,6 +176,7 @@ impl token::Interface for Token {
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
+        check_authorized(&e, &from);
         spend_balance(&e, from.clone(), amount);
         TokenUtils::new(&e).events().burn(from, amount);
     }
@@ -147,6 +190,7 @@ impl token::Interface for Token {
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
+        check_authorized(&e, &from);
         spend_allowance(&e, from.clone(), spender, amount);
         spend_balance(&e, from.clone(), amount);
         TokenUtils::new(&e).events().burn(from, amount)
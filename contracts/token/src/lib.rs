@@ -2,6 +2,7 @@
 
 pub mod admin;
 pub mod allowance;
+pub mod authorization;
 pub mod balance;
 pub mod contract;
 pub mod metadata;
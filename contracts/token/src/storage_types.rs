@@ -25,8 +25,9 @@ pub struct AllowanceValue {
 pub enum DataKey {
     Allowance(AllowanceDataKey),
     Balance(Address),
-    State(Address),
+    State(Address), // Whether the account may send and receive, see `authorization`
     Admin,
+    AuthorizationRequired,
     TotalSupply,
     TokenOwner,
 }
@@ -257,6 +257,124 @@ fn decimal_is_over_eighteen() {
     );
 }
 
+#[test]
+fn test_freeze_and_unfreeze_account() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let user1 = Address::generate(&e);
+    let user2 = Address::generate(&e);
+    let token = create_token(&e, &admin);
+    token.mint(&user1, &1000);
+
+    assert!(token.authorized(&user1));
+    token.set_authorized(&user1, &false);
+    let auths = e.auths();
+    assert_eq!(auths.len(), 1);
+    let (_contract, invocation) = &auths[0];
+    assert_eq!(invocation.function, AuthorizedFunction::Contract((
+        token.address.clone(),
+        Symbol::new(&e, "set_authorized"),
+        (&user1, false).into_val(&e),
+    )));
+    assert!(!token.authorized(&user1));
+
+    token.set_authorized(&user1, &true);
+    token.transfer(&user1, &user2, &400);
+    assert_eq!(token.balance(&user2), 400);
+}
+
+#[test]
+#[should_panic(expected = "account is frozen")]
+fn test_frozen_account_cannot_transfer() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let user1 = Address::generate(&e);
+    let user2 = Address::generate(&e);
+    let token = create_token(&e, &admin);
+    token.mint(&user1, &1000);
+
+    token.set_authorized(&user1, &false);
+    token.transfer(&user1, &user2, &400);
+}
+
+#[test]
+#[should_panic(expected = "account is frozen")]
+fn test_frozen_account_cannot_receive() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let user1 = Address::generate(&e);
+    let user2 = Address::generate(&e);
+    let user3 = Address::generate(&e);
+    let token = create_token(&e, &admin);
+    token.mint(&user1, &1000);
+
+    token.set_authorized(&user2, &false);
+    token.approve(&user1, &user3, &400, &200);
+    token.transfer_from(&user3, &user1, &user2, &400);
+}
+
+#[test]
+#[should_panic(expected = "account is frozen")]
+fn test_frozen_account_cannot_burn() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let user1 = Address::generate(&e);
+    let token = create_token(&e, &admin);
+    token.mint(&user1, &1000);
+
+    token.set_authorized(&user1, &false);
+    token.burn(&user1, &100);
+}
+
+#[test]
+#[should_panic(expected = "account is frozen")]
+fn test_authorization_required_freezes_new_accounts() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let user1 = Address::generate(&e);
+    let user2 = Address::generate(&e);
+    let token = create_token(&e, &admin);
+    token.mint(&user1, &1000);
+
+    token.set_authorization_required(&true);
+    assert!(token.authorization_required());
+    assert!(token.authorized(&user1));
+    assert!(!token.authorized(&user2));
+    token.transfer(&user1, &user2, &400);
+}
+
+#[test]
+#[should_panic(expected = "account is frozen")]
+fn test_mint_to_unauthorized_account_panics() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let user = Address::generate(&e);
+    let token = create_token(&e, &admin);
+
+    token.set_authorization_required(&true);
+    token.mint(&user, &1000);
+}
+
+#[test]
+fn test_mint_to_authorized_account_after_authorization_required() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let user = Address::generate(&e);
+    let token = create_token(&e, &admin);
+
+    token.set_authorization_required(&true);
+    token.set_authorized(&user, &true);
+    token.mint(&user, &1000);
+    assert_eq!(token.balance(&user), 1000);
+}
+
 #[test]
 fn test_zero_allowance() {
     // Here we test that transfer_from with a 0 amount does not create an empty allowance
@@ -1,19 +1,68 @@
 #![no_std]
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short, vec, Address, BytesN, Env, IntoVal, Map, String, Val, Vec
+    contract, contractclient, contractimpl, contracttype, symbol_short, Address, BytesN, Env, String, Vec
 };
+use storagettl::{read_persistent, write_persistent};
+
+// Roles the admin can hand out. A role nobody was given is held by the admin
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Role {
+    Upgrader, // Sets the token Wasm hash
+    Pauser,   // Pauses and resumes token creation
+}
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DataKey {
     Admin,
+    PendingAdmin, // Proposed admin, until it accepts
+    Role(Role), // Holder of each assigned role
+    Paused, // Token creation is paused
     TokenWasmHash,
     DeployedTokens(Address, Address), // (token_address, Deployer) sorted
     AllDeployedTokens,
     TokenMetadata(Address),
+    TokenOwner(Address), // Last known owner of a token, None once renounced
+    TokenClawbackEnabled(Address),
+}
+
+/// Parameters of a token deployed by `create_token`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenConfig {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u32,
+    pub initial_supply: i128,
+    pub max_supply: i128, // The token admin can mint up to this
+    pub owner: Address,
+    pub metadata: String,
+    pub clawback_enabled: bool, // Lets the token admin claw back balances, can't change later
+}
+
+// Mirror of `soroban_token_sdk::metadata::TokenMetadata`, passed to the token constructor
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct TokenMetadata {
+    decimal: u32,
+    name: String,
+    symbol: String,
+}
+
+// Mirror of `tokenlaunch::SupplyConfig`, passed to the token constructor
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct SupplyConfig {
+    initial_supply: i128,
+    max_supply: i128,
 }
 
+#[contractclient(name = "LaunchTokenClient")]
+pub trait LaunchTokenInterface {
+    fn owner(e: Env) -> Option<Address>;
+}
 
 #[contract]
 pub struct TokenFactory;
@@ -25,12 +74,59 @@ impl TokenFactory {
         env.storage().instance().set(&DataKey::Admin, &admin);
     }
 
-     /// Set the pool contract Wasm hash (admin only)
-     pub fn update_pool_wasm_hash(env: Env, admin_addr: Address, new_hash: BytesN<32>) {
-        let admin = env.storage().instance().get::<_, Address>(&DataKey::Admin).expect("not set");
-        assert!(admin == admin_addr, "Unauthorized");
+    fn require_admin(env: &Env, admin_addr: &Address) {
+        let admin = Self::get_admin(env.clone());
+        assert!(admin == *admin_addr, "Unauthorized");
         admin.require_auth();
+    }
+
+    fn require_role(env: &Env, role: Role, caller: &Address) {
+        let holder = Self::get_role(env.clone(), role);
+        assert!(holder == *caller, "Unauthorized");
+        caller.require_auth();
+    }
+
+    /// Propose a new admin (admin only). The transfer completes once the new admin accepts it
+    pub fn propose_admin(env: Env, admin_addr: Address, new_admin: Address) {
+        Self::require_admin(&env, &admin_addr);
+        env.storage().instance().set(&DataKey::PendingAdmin, &new_admin);
+        env.events().publish((symbol_short!("adm_prop"), admin_addr), new_admin);
+    }
+
+    /// Become admin after being proposed with `propose_admin`
+    pub fn accept_admin(env: Env, new_admin: Address) {
+        let pending: Address = env.storage().instance().get(&DataKey::PendingAdmin).expect("No pending admin");
+        assert!(pending == new_admin, "Unauthorized");
+        new_admin.require_auth();
+
+        let old_admin = Self::get_admin(env.clone());
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+        env.events().publish((symbol_short!("admin"), old_admin), new_admin);
+    }
+
+    /// Give a role to `holder`, or back to the admin with None (admin only)
+    pub fn set_role(env: Env, admin_addr: Address, role: Role, holder: Option<Address>) {
+        Self::require_admin(&env, &admin_addr);
+        match &holder {
+            Some(holder) => env.storage().instance().set(&DataKey::Role(role), holder),
+            None => env.storage().instance().remove(&DataKey::Role(role)),
+        }
+        env.events().publish((symbol_short!("role"), role), holder);
+    }
+
+    /// Pause or resume the creation of new tokens (pauser only)
+    pub fn set_paused(env: Env, caller: Address, paused: bool) {
+        Self::require_role(&env, Role::Pauser, &caller);
+        env.storage().instance().set(&DataKey::Paused, &paused);
+        env.events().publish((symbol_short!("paused"), caller), paused);
+    }
+
+     /// Set the token contract Wasm hash (upgrader only)
+     pub fn update_pool_wasm_hash(env: Env, caller: Address, new_hash: BytesN<32>) {
+        Self::require_role(&env, Role::Upgrader, &caller);
         env.storage().instance().set(&DataKey::TokenWasmHash, &new_hash);
+        env.events().publish((symbol_short!("wasm"),), new_hash);
     }
 
      /// Get the pool contract Wasm hash
@@ -38,13 +134,18 @@ impl TokenFactory {
         env.storage().instance().get(&DataKey::TokenWasmHash).expect("not set")
     }
     
-    pub fn create_token(env: Env, admin_addr: Address, token_name: String, token_symbol: String, token_decimals: u32, token_supply: i128, token_owner: Address, token_metadata: String, salt: BytesN<32>) -> Address {
+    pub fn create_token(env: Env, admin_addr: Address, config: TokenConfig, salt: BytesN<32>) -> Address {
+        assert!(!Self::is_paused(env.clone()), "Token creation paused");
         let wasm_hash = env
             .storage()
             .instance()
             .get::<_, BytesN<32>>(&DataKey::TokenWasmHash)
             .expect("Wasm hash not set");
-        let token_addr = env.deployer().with_address(env.current_contract_address(), salt).deploy_v2(wasm_hash, (admin_addr, token_decimals, token_name, token_symbol, token_supply, token_owner));
+        let metadata = TokenMetadata { decimal: config.decimals, name: config.name, symbol: config.symbol };
+        let supply = SupplyConfig { initial_supply: config.initial_supply, max_supply: config.max_supply };
+        let token_metadata = config.metadata;
+        let token_owner = config.owner.clone();
+        let token_addr = env.deployer().with_address(env.current_contract_address(), salt).deploy_v2(wasm_hash, (admin_addr, metadata, supply, config.owner, config.clawback_enabled));
         env.storage().instance().set(&DataKey::DeployedTokens(token_addr.clone(), env.current_contract_address()), &true);
         // Add the token to the list of all deployed tokens
     let mut tokens = env.storage().instance().get(&DataKey::AllDeployedTokens)
@@ -53,9 +154,49 @@ impl TokenFactory {
     tokens.push_back(token_addr.clone());
     env.storage().instance().set(&DataKey::AllDeployedTokens, &tokens);
     env.storage().instance().set(&DataKey::TokenMetadata(token_addr.clone()), &token_metadata);
+        write_persistent(&env, &DataKey::TokenOwner(token_addr.clone()), &Some(token_owner));
+        write_persistent(&env, &DataKey::TokenClawbackEnabled(token_addr.clone()), &config.clawback_enabled);
         token_addr
     }
 
+    /// Record the token's current owner after an ownership transfer or renouncement.
+    /// Anyone can call it; returns the recorded owner
+    pub fn sync_token_owner(env: Env, token_addr: Address) -> Option<Address> {
+        let key = DataKey::TokenOwner(token_addr.clone());
+        let recorded: Option<Address> = read_persistent(&env, &key).expect("Token not deployed by this factory");
+        let owner = LaunchTokenClient::new(&env, &token_addr).owner();
+        if owner != recorded {
+            write_persistent(&env, &key, &owner);
+            env.events().publish((symbol_short!("owner"), token_addr), owner.clone());
+        }
+        owner
+    }
+
+    /// Replace the metadata stored for a token (token owner only)
+    pub fn set_token_metadata(env: Env, caller: Address, token_addr: Address, token_metadata: String) {
+        let owner = Self::sync_token_owner(env.clone(), token_addr.clone()).expect("Ownership renounced");
+        assert!(owner == caller, "Unauthorized");
+        caller.require_auth();
+        env.storage().instance().set(&DataKey::TokenMetadata(token_addr), &token_metadata);
+    }
+
+    pub fn get_admin(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Admin).expect("not set")
+    }
+
+    pub fn get_pending_admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::PendingAdmin)
+    }
+
+    /// Get the holder of a role, the admin when the role wasn't given to anyone
+    pub fn get_role(env: Env, role: Role) -> Address {
+        env.storage().instance().get(&DataKey::Role(role)).unwrap_or_else(|| Self::get_admin(env))
+    }
+
+    pub fn is_paused(env: Env) -> bool {
+        env.storage().instance().get(&DataKey::Paused).unwrap_or(false)
+    }
+
     pub fn get_all_deployed_tokens(env: Env) -> Vec<Address> {
         env.storage().instance().get(&DataKey::AllDeployedTokens)
             .unwrap_or_else(|| Vec::new(&env))
@@ -66,6 +207,25 @@ impl TokenFactory {
             .expect("Token metadata not set")
     }
 
+    /// Owner recorded by the factory, as of the last `sync_token_owner`. Tokens don't notify the
+    /// factory, so this may lag an ownership change until someone syncs it
+    pub fn get_token_owner(env: Env, token_addr: Address) -> Option<Address> {
+        read_persistent(&env, &DataKey::TokenOwner(token_addr))
+            .expect("Token not deployed by this factory")
+    }
+
+    /// Whether the token's ownership has been renounced, read from the token itself so it is never stale
+    pub fn is_ownership_renounced(env: Env, token_addr: Address) -> bool {
+        Self::get_token_owner(env.clone(), token_addr.clone());
+        LaunchTokenClient::new(&env, &token_addr).owner().is_none()
+    }
+
+    /// Whether the token's admin can claw back balances, so wallets can warn holders
+    pub fn is_clawback_enabled(env: Env, token_addr: Address) -> bool {
+        read_persistent(&env, &DataKey::TokenClawbackEnabled(token_addr))
+            .expect("Token not deployed by this factory")
+    }
+
 }
 
 mod test;
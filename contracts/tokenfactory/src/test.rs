@@ -3,7 +3,7 @@ extern crate std;
 
 use super::*;
 use soroban_sdk::{
-    Env, String, Address, BytesN, testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
+    Env, String, Address, BytesN, testutils::{Address as _},
 };
 
 // Import the token contract WASM for upload (adjust path as needed)
@@ -38,11 +38,24 @@ fn test_token_factory() {
     let token_supply = 1_000_000i128;
     let token_metadata = String::from_str(&env, "TTKLKDJFDFJKJKJFDFDF");
 
-    let token_addr = factory.create_token(&admin, &token_name, &token_symbol, &token_decimals, &token_supply, &user, &token_metadata, &salt);
+    let config = TokenConfig {
+        name: token_name,
+        symbol: token_symbol,
+        decimals: token_decimals,
+        initial_supply: token_supply,
+        max_supply: 2 * token_supply,
+        owner: user.clone(),
+        metadata: token_metadata,
+        clawback_enabled: false,
+    };
+    let token_addr = factory.create_token(&admin, &config, &salt);
 
 
     // Check that the token address is not the user address (sanity check)
     assert!(token_addr != user);
+    let token = contract::Client::new(&env, &token_addr);
+    assert_eq!(token.total_supply(), token_supply);
+    assert_eq!(token.max_supply(), 2 * token_supply);
     // Verify token is tracked in deployed tokens
     let deployed_tokens = factory.get_all_deployed_tokens();
     assert_eq!(deployed_tokens.len(), 1);
@@ -86,12 +99,16 @@ fn test_get_all_deployed_tokens() {
     let token_metadata1 = String::from_str(&env, "TTKLKDJFDFJKJKJFDFDF");
     let token_addr1 = factory.create_token(
         &admin,
-        &token_name1,
-        &token_symbol1,
-        &6u32,
-        &1000i128,
-        &user1,
-        &token_metadata1,
+        &TokenConfig {
+            name: token_name1,
+            symbol: token_symbol1,
+            decimals: 6u32,
+            initial_supply: 1000i128,
+            max_supply: 1000i128,
+            owner: user1,
+            metadata: token_metadata1.clone(),
+            clawback_enabled: false,
+        },
         &salt1,
     );
 
@@ -113,12 +130,16 @@ fn test_get_all_deployed_tokens() {
     let token_symbol2 = String::from_str(&env, "TKN2");
     let token_addr2 = factory.create_token(
         &admin,
-        &token_name2,
-        &token_symbol2,
-        &8u32,
-        &2000i128,
-        &user2,
-        &token_metadata1,
+        &TokenConfig {
+            name: token_name2,
+            symbol: token_symbol2,
+            decimals: 8u32,
+            initial_supply: 2000i128,
+            max_supply: 2000i128,
+            owner: user2,
+            metadata: token_metadata1,
+            clawback_enabled: true,
+        },
         &salt2,
     );
 
@@ -135,4 +156,116 @@ fn test_get_all_deployed_tokens() {
     assert_eq!(deployed_tokens.len(), 2);
     assert_eq!(deployed_tokens.get(0).unwrap(), token_addr1);
     assert_eq!(deployed_tokens.get(1).unwrap(), token_addr2);
-}
\ No newline at end of file
+
+    // Only the second token can be clawed back
+    assert!(!factory.is_clawback_enabled(&token_addr1));
+    assert!(factory.is_clawback_enabled(&token_addr2));
+    assert!(contract::Client::new(&env, &token_addr2).clawback_enabled());
+}
+#[test]
+fn test_token_owner_changes_are_recorded() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let new_owner = Address::generate(&env);
+
+    let contract_id = env.register(TokenFactory, (&admin,));
+    let factory = TokenFactoryClient::new(&env, &contract_id);
+    factory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+
+    let config = TokenConfig {
+        name: String::from_str(&env, "TestToken"),
+        symbol: String::from_str(&env, "TTK"),
+        decimals: 7u32,
+        initial_supply: 1_000_000i128,
+        max_supply: 1_000_000i128,
+        owner: owner.clone(),
+        metadata: String::from_str(&env, "ipfs://token"),
+        clawback_enabled: false,
+    };
+    let token_addr = factory.create_token(&admin, &config, &BytesN::from_array(&env, &[0; 32]));
+    let token = contract::Client::new(&env, &token_addr);
+    assert_eq!(token.balance(&owner), 1_000_000);
+    assert_eq!(factory.get_token_owner(&token_addr), Some(owner.clone()));
+
+    factory.set_token_metadata(&owner, &token_addr, &String::from_str(&env, "ipfs://updated"));
+    assert_eq!(factory.get_token_metadata(&token_addr), String::from_str(&env, "ipfs://updated"));
+
+    token.transfer_ownership(&new_owner);
+    assert_eq!(factory.sync_token_owner(&token_addr), Some(new_owner.clone()));
+    assert_eq!(factory.get_token_owner(&token_addr), Some(new_owner.clone()));
+
+    // Renouncing shows up right away, before anyone syncs the recorded owner
+    token.renounce_ownership();
+    assert!(factory.is_ownership_renounced(&token_addr));
+    assert_eq!(factory.get_token_owner(&token_addr), Some(new_owner.clone()));
+    assert_eq!(factory.sync_token_owner(&token_addr), None);
+}
+
+#[test]
+fn test_two_step_admin_transfer_and_roles() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let new_admin = Address::generate(&env);
+    let upgrader = Address::generate(&env);
+
+    let contract_id = env.register(TokenFactory, (&admin,));
+    let factory = TokenFactoryClient::new(&env, &contract_id);
+
+    factory.propose_admin(&admin, &new_admin);
+    assert_eq!(factory.get_pending_admin(), Some(new_admin.clone()));
+    factory.accept_admin(&new_admin);
+    assert_eq!(factory.get_admin(), new_admin);
+    assert_eq!(factory.get_pending_admin(), None);
+
+    factory.set_role(&new_admin, &Role::Upgrader, &Some(upgrader.clone()));
+    assert_eq!(factory.get_role(&Role::Upgrader), upgrader);
+    assert_eq!(factory.get_role(&Role::Pauser), new_admin);
+    let wasm_hash = env.deployer().upload_contract_wasm(contract::WASM);
+    factory.update_pool_wasm_hash(&upgrader, &wasm_hash);
+    assert_eq!(factory.get_pool_wasm_hash(), wasm_hash);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized")]
+fn test_old_admin_after_transfer_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let new_admin = Address::generate(&env);
+
+    let contract_id = env.register(TokenFactory, (&admin,));
+    let factory = TokenFactoryClient::new(&env, &contract_id);
+    factory.propose_admin(&admin, &new_admin);
+    factory.accept_admin(&new_admin);
+    factory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+}
+
+#[test]
+#[should_panic(expected = "Token creation paused")]
+fn test_create_token_while_paused_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let guardian = Address::generate(&env);
+
+    let contract_id = env.register(TokenFactory, (&admin,));
+    let factory = TokenFactoryClient::new(&env, &contract_id);
+    factory.update_pool_wasm_hash(&admin, &env.deployer().upload_contract_wasm(contract::WASM));
+    factory.set_role(&admin, &Role::Pauser, &Some(guardian.clone()));
+    factory.set_paused(&guardian, &true);
+
+    let config = TokenConfig {
+        name: String::from_str(&env, "TestToken"),
+        symbol: String::from_str(&env, "TTK"),
+        decimals: 7u32,
+        initial_supply: 1_000_000i128,
+        max_supply: 1_000_000i128,
+        owner: admin.clone(),
+        metadata: String::from_str(&env, "ipfs://token"),
+        clawback_enabled: false,
+    };
+    factory.create_token(&admin, &config, &BytesN::from_array(&env, &[0; 32]));
+}
@@ -0,0 +1,40 @@
+//! Account freezing. Accounts without an explicit state are authorized unless authorization is required.
+
+use crate::storage_types::{DataKey, BALANCE_BUMP_AMOUNT, BALANCE_LIFETIME_THRESHOLD};
+use soroban_sdk::{Address, Env};
+
+/// Whether new accounts without an explicit state start frozen
+pub fn read_authorization_required(e: &Env) -> bool {
+    e.storage().instance().get(&DataKey::AuthorizationRequired).unwrap_or(false)
+}
+
+pub fn write_authorization_required(e: &Env, required: bool) {
+    e.storage().instance().set(&DataKey::AuthorizationRequired, &required);
+}
+
+pub fn read_authorized(e: &Env, addr: Address) -> bool {
+    let key = DataKey::State(addr.clone());
+    if let Some(authorized) = e.storage().persistent().get::<DataKey, bool>(&key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+        authorized
+    } else {
+        // Accounts that already hold a balance stay authorized when authorization becomes required
+        !read_authorization_required(e) || e.storage().persistent().has(&DataKey::Balance(addr))
+    }
+}
+
+pub fn write_authorized(e: &Env, addr: Address, authorized: bool) {
+    let key = DataKey::State(addr);
+    e.storage().persistent().set(&key, &authorized);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
+
+pub fn check_authorized(e: &Env, addr: &Address) {
+    if !read_authorized(e, addr.clone()) {
+        panic!("account is frozen");
+    }
+}
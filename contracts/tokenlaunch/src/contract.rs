@@ -1,14 +1,21 @@
 //! interface.
 use crate::admin::{read_administrator, write_administrator};
 use crate::allowance::{read_allowance, spend_allowance, write_allowance};
+use crate::authorization::{
+    check_authorized, read_authorization_required, read_authorized, write_authorization_required,
+    write_authorized,
+};
 use crate::balance::{read_balance, receive_balance, spend_balance};
 use crate::metadata::{read_decimal, read_name, read_symbol, write_metadata};
-#[cfg(test)]
-use crate::storage_types::{AllowanceDataKey, AllowanceValue};
-use crate::storage_types::DataKey;
+use crate::owner::{read_owner, remove_owner, require_owner, write_owner};
+use crate::supply::{
+    decrease_supply, increase_supply, is_minting_renounced, read_max_supply, read_minted,
+    read_total_supply, renounce_minting, write_max_supply,
+};
+use crate::storage_types::{DataKey, SupplyConfig};
 use crate::storage_types::{INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD};
 use soroban_sdk::token::{self, Interface as _};
-use soroban_sdk::{contract, contractimpl, Address, Env, String};
+use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env, String};
 use soroban_token_sdk::metadata::TokenMetadata;
 use soroban_token_sdk::TokenUtils;
 
@@ -23,52 +30,68 @@ pub struct Token;
 
 #[contractimpl]
 impl Token {
-    pub fn __constructor(e: Env, admin: Address, decimal: u32, name: String, symbol: String, total_supply: i128, owner: Address) {
-        if decimal > 18 {
+    pub fn __constructor(e: Env, admin: Address, metadata: TokenMetadata, supply: SupplyConfig, owner: Address, clawback_enabled: bool) {
+        if metadata.decimal > 18 {
             panic!("Decimal must not be greater than 18");
         }
+        let total_supply = supply.initial_supply;
         if total_supply == 0 {
             panic!("Total supply must not be 0");
         }
         check_nonnegative_amount(total_supply);
+        if supply.max_supply < total_supply {
+            panic!("Max supply must not be below the initial supply");
+        }
 
         write_administrator(&e, &admin);
-        write_metadata(
-            &e,
-            TokenMetadata {
-                decimal,
-                name,
-                symbol,
-            },
-        );
-        e.storage().instance().set(&DataKey::TotalSupply, &total_supply);
-        e.storage().instance().set(&DataKey::TokenOwner, &owner);
+        write_metadata(&e, metadata);
+        write_max_supply(&e, supply.max_supply);
+        increase_supply(&e, total_supply);
+        write_owner(&e, &owner);
+        e.storage().instance().set(&DataKey::ClawbackEnabled, &clawback_enabled);
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        receive_balance(&e, admin.clone(), total_supply);
-        TokenUtils::new(&e).events().mint(admin.clone(), admin, total_supply);
+        // The initial supply goes to the owner
+        receive_balance(&e, owner.clone(), total_supply);
+        TokenUtils::new(&e).events().mint(admin, owner, total_supply);
     }
 
+    /// Mint new tokens (admin only), up to the max supply and until minting is renounced
     pub fn mint(e: Env, to: Address, amount: i128) {
-        let total_supply = e.storage().instance().get::<_, u64>(&DataKey::TotalSupply).expect("not set");
-        if total_supply > 0 {
-            panic!("Total supply is already set");
-        }
         check_nonnegative_amount(amount);
         let admin = read_administrator(&e);
         admin.require_auth();
+        if is_minting_renounced(&e) {
+            panic!("Minting renounced");
+        }
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
+        increase_supply(&e, amount);
+        // Minting to an unauthorized account would make it look like an existing holder
+        check_authorized(&e, &to);
         receive_balance(&e, to.clone(), amount);
         TokenUtils::new(&e).events().mint(admin, to, amount);
     }
 
+    /// Give up minting for good (admin only). The supply can only go down from here
+    pub fn renounce_minting(e: Env) {
+        let admin = read_administrator(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        renounce_minting(&e);
+        e.events().publish((symbol_short!("no_mint"), admin), read_minted(&e));
+    }
+
     pub fn set_admin(e: Env, new_admin: Address) {
         let admin = read_administrator(&e);
         admin.require_auth();
@@ -81,6 +104,132 @@ impl Token {
         TokenUtils::new(&e).events().set_admin(admin, new_admin);
     }
 
+    /// Take `amount` from an account and destroy it (admin only). Only possible if the token
+    /// was created with clawback enabled; works on frozen accounts too
+    pub fn clawback(e: Env, from: Address, amount: i128) {
+        check_nonnegative_amount(amount);
+        let admin = read_administrator(&e);
+        admin.require_auth();
+        if !Self::clawback_enabled(e.clone()) {
+            panic!("Clawback not enabled");
+        }
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        spend_balance(&e, from.clone(), amount);
+        decrease_supply(&e, amount);
+        TokenUtils::new(&e).events().clawback(admin, from, amount);
+    }
+
+    pub fn clawback_enabled(e: Env) -> bool {
+        e.storage().instance().get(&DataKey::ClawbackEnabled).unwrap_or(false)
+    }
+
+    /// Freeze (`authorize = false`) or unfreeze an account (admin only)
+    pub fn set_authorized(e: Env, id: Address, authorize: bool) {
+        let admin = read_administrator(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_authorized(&e, id.clone(), authorize);
+        TokenUtils::new(&e).events().set_authorized(admin, id, authorize);
+    }
+
+    /// When required, new accounts the admin hasn't authorized yet start frozen (admin only)
+    pub fn set_authorization_required(e: Env, required: bool) {
+        let admin = read_administrator(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_authorization_required(&e, required);
+        e.events().publish((symbol_short!("auth_req"), admin), required);
+    }
+
+    pub fn authorized(e: Env, id: Address) -> bool {
+        read_authorized(&e, id)
+    }
+
+    pub fn authorization_required(e: Env) -> bool {
+        read_authorization_required(&e)
+    }
+
+    /// Hand the owner role to `new_owner` (owner only)
+    pub fn transfer_ownership(e: Env, new_owner: Address) {
+        let owner = require_owner(&e);
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_owner(&e, &new_owner);
+        e.events().publish((symbol_short!("owner"), owner), Some(new_owner));
+    }
+
+    /// Give up the owner role for good (owner only). Metadata can't change afterwards
+    pub fn renounce_ownership(e: Env) {
+        let owner = require_owner(&e);
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        remove_owner(&e);
+        e.events().publish((symbol_short!("owner"), owner), None::<Address>);
+    }
+
+    /// Change the token name and symbol (owner only). Decimals never change
+    pub fn set_metadata(e: Env, name: String, symbol: String) {
+        let owner = require_owner(&e);
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_metadata(
+            &e,
+            TokenMetadata {
+                decimal: read_decimal(&e),
+                name: name.clone(),
+                symbol: symbol.clone(),
+            },
+        );
+        e.events().publish((symbol_short!("metadata"), owner), (name, symbol));
+    }
+
+    pub fn owner(e: Env) -> Option<Address> {
+        read_owner(&e)
+    }
+
+    pub fn admin(e: Env) -> Address {
+        read_administrator(&e)
+    }
+
+    /// Tokens in circulation
+    pub fn total_supply(e: Env) -> i128 {
+        read_total_supply(&e)
+    }
+
+    /// Tokens ever minted, burned ones included
+    pub fn minted(e: Env) -> i128 {
+        read_minted(&e)
+    }
+
+    pub fn max_supply(e: Env) -> i128 {
+        read_max_supply(&e)
+    }
+
+    pub fn is_minting_renounced(e: Env) -> bool {
+        is_minting_renounced(&e)
+    }
+
     // #[cfg(test)]
     // pub fn get_allowance(e: Env, from: Address, spender: Address) -> Option<AllowanceValue> {
     //     let key = DataKey::Allowance(AllowanceDataKey { from, spender });
@@ -129,6 +278,8 @@ impl token::Interface for Token {
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
+        check_authorized(&e, &from);
+        check_authorized(&e, &to);
         spend_balance(&e, from.clone(), amount);
         receive_balance(&e, to.clone(), amount);
         TokenUtils::new(&e).events().transfer(from, to, amount);
@@ -143,6 +294,8 @@ impl token::Interface for Token {
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
+        check_authorized(&e, &from);
+        check_authorized(&e, &to);
         spend_allowance(&e, from.clone(), spender, amount);
         spend_balance(&e, from.clone(), amount);
         receive_balance(&e, to.clone(), amount);
@@ -158,7 +311,9 @@ impl token::Interface for Token {
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
+        check_authorized(&e, &from);
         spend_balance(&e, from.clone(), amount);
+        decrease_supply(&e, amount);
         TokenUtils::new(&e).events().burn(from, amount);
     }
 
@@ -171,8 +326,10 @@ impl token::Interface for Token {
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
+        check_authorized(&e, &from);
         spend_allowance(&e, from.clone(), spender, amount);
         spend_balance(&e, from.clone(), amount);
+        decrease_supply(&e, amount);
         TokenUtils::new(&e).events().burn(from, amount)
     }
 
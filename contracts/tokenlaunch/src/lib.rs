@@ -2,11 +2,15 @@
 
 pub mod admin;
 pub mod allowance;
+pub mod authorization;
 pub mod balance;
 pub mod contract;
 pub mod metadata;
+pub mod owner;
 pub mod storage_types;
+pub mod supply;
 pub mod test;
 
 
 pub use contract::{Token, TokenClient};
+pub use storage_types::SupplyConfig;
@@ -0,0 +1,26 @@
+use soroban_sdk::{Address, Env};
+
+use crate::storage_types::DataKey;
+
+/// The token owner, or None once ownership has been renounced
+pub fn read_owner(e: &Env) -> Option<Address> {
+    let key = DataKey::TokenOwner;
+    e.storage().instance().get(&key)
+}
+
+pub fn write_owner(e: &Env, id: &Address) {
+    let key = DataKey::TokenOwner;
+    e.storage().instance().set(&key, id);
+}
+
+pub fn remove_owner(e: &Env) {
+    let key = DataKey::TokenOwner;
+    e.storage().instance().remove(&key);
+}
+
+/// Require the current owner's authorization and return it
+pub fn require_owner(e: &Env) -> Address {
+    let owner = read_owner(e).expect("Ownership renounced");
+    owner.require_auth();
+    owner
+}
@@ -20,14 +20,28 @@ pub struct AllowanceValue {
     pub expiration_ledger: u32,
 }
 
+/// Supply set at construction: `initial_supply` goes out right away, the admin can mint
+/// the rest up to `max_supply`
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct SupplyConfig {
+    pub initial_supply: i128,
+    pub max_supply: i128,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
     Allowance(AllowanceDataKey),
     Balance(Address),
-    State(Address),
+    State(Address), // Whether the account may send and receive, see `authorization`
     Admin,
-    TotalSupply,
+    AuthorizationRequired,
+    TotalSupply, // Tokens in circulation: minted minus burned
+    Minted,      // Tokens ever minted, capped by MaxSupply
+    MaxSupply,
+    MintingRenounced,
+    ClawbackEnabled, // Set once at construction
     TokenOwner,
 }
 
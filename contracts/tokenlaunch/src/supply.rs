@@ -0,0 +1,40 @@
+use crate::storage_types::DataKey;
+use soroban_sdk::Env;
+
+pub fn read_total_supply(e: &Env) -> i128 {
+    e.storage().instance().get(&DataKey::TotalSupply).unwrap_or(0)
+}
+
+pub fn read_minted(e: &Env) -> i128 {
+    e.storage().instance().get(&DataKey::Minted).unwrap_or(0)
+}
+
+pub fn read_max_supply(e: &Env) -> i128 {
+    e.storage().instance().get(&DataKey::MaxSupply).unwrap()
+}
+
+pub fn write_max_supply(e: &Env, max_supply: i128) {
+    e.storage().instance().set(&DataKey::MaxSupply, &max_supply);
+}
+
+pub fn is_minting_renounced(e: &Env) -> bool {
+    e.storage().instance().get(&DataKey::MintingRenounced).unwrap_or(false)
+}
+
+pub fn renounce_minting(e: &Env) {
+    e.storage().instance().set(&DataKey::MintingRenounced, &true);
+}
+
+/// Mint against the cap. Minted tokens count towards it for good, burning doesn't free room
+pub fn increase_supply(e: &Env, amount: i128) {
+    let minted = read_minted(e) + amount;
+    if minted > read_max_supply(e) {
+        panic!("Max supply exceeded");
+    }
+    e.storage().instance().set(&DataKey::Minted, &minted);
+    e.storage().instance().set(&DataKey::TotalSupply, &(read_total_supply(e) + amount));
+}
+
+pub fn decrease_supply(e: &Env, amount: i128) {
+    e.storage().instance().set(&DataKey::TotalSupply, &(read_total_supply(e) - amount));
+}
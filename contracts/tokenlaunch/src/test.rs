@@ -4,21 +4,47 @@ extern crate std;
 use crate::{contract::Token, TokenClient};
 use soroban_sdk::{
     symbol_short,
-    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
+    testutils::{Address as _, AuthorizedFunction},
     Address, Env, FromVal, IntoVal, String, Symbol,
 };
-use crate::storage_types::{AllowanceValue, DataKey, AllowanceDataKey};
+use crate::SupplyConfig;
+use soroban_token_sdk::metadata::TokenMetadata;
 
-fn create_token<'a>(e: &Env, admin: &Address) -> TokenClient<'a> {
+fn create_capped_token<'a>(e: &Env, admin: &Address, initial_supply: i128, max_supply: i128) -> TokenClient<'a> {
     let token_contract = e.register(
         Token,
         (
             admin,
-            18_u32,
-            String::from_val(e, &"name"),
-            String::from_val(e, &"symbol"),
-            100000_i128,
+            TokenMetadata {
+                decimal: 18,
+                name: String::from_val(e, &"name"),
+                symbol: String::from_val(e, &"symbol"),
+            },
+            SupplyConfig { initial_supply, max_supply },
+            admin,
+            false,
+        ),
+    );
+    TokenClient::new(e, &token_contract)
+}
+
+fn create_token<'a>(e: &Env, admin: &Address) -> TokenClient<'a> {
+    create_capped_token(e, admin, 100000, 100000)
+}
+
+fn create_owned_token<'a>(e: &Env, admin: &Address, owner: &Address, clawback_enabled: bool) -> TokenClient<'a> {
+    let token_contract = e.register(
+        Token,
+        (
             admin,
+            TokenMetadata {
+                decimal: 18,
+                name: String::from_val(e, &"name"),
+                symbol: String::from_val(e, &"symbol"),
+            },
+            SupplyConfig { initial_supply: 100000, max_supply: 100000 },
+            owner,
+            clawback_enabled,
         ),
     );
     TokenClient::new(e, &token_contract)
@@ -112,7 +138,6 @@ fn test_burn() {
     let user2 = Address::generate(&e);
     assert_eq!(token.balance(&admin), 100000);  
 
- let auths = e.auths();
     token.transfer(&admin, &user2, &500);
     token.burn(&user2, &500);
     let auths = e.auths();
@@ -134,7 +159,6 @@ fn transfer_insufficient_balance() {
     e.mock_all_auths();
 
     let admin = Address::generate(&e);
-    let user1 = Address::generate(&e);
     let user2 = Address::generate(&e);
     let token = create_token(&e, &admin);
 
@@ -176,16 +200,282 @@ fn decimal_is_over_eighteen() {
             Token,
             (
                 admin.clone(),
-                19_u32,
-                String::from_val(&e, &"name"),
-                String::from_val(&e, &"symbol"),
-                1000000000000000000_i128,
+                TokenMetadata {
+                    decimal: 19,
+                    name: String::from_val(&e, &"name"),
+                    symbol: String::from_val(&e, &"symbol"),
+                },
+                SupplyConfig { initial_supply: 1000000000000000000, max_supply: 1000000000000000000 },
                 admin,
+                false,
             ),
         ),
     );
 }
 
+#[test]
+fn test_mint_up_to_max_supply() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let user = Address::generate(&e);
+    let token = create_capped_token(&e, &admin, 100000, 150000);
+    assert_eq!(token.total_supply(), 100000);
+    assert_eq!(token.max_supply(), 150000);
+
+    token.mint(&user, &50000);
+    assert_eq!(token.balance(&user), 50000);
+    assert_eq!(token.total_supply(), 150000);
+    assert_eq!(token.minted(), 150000);
+}
+
+#[test]
+#[should_panic(expected = "Max supply exceeded")]
+fn test_mint_over_max_supply_panics() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let user = Address::generate(&e);
+    let token = create_capped_token(&e, &admin, 100000, 150000);
+    token.mint(&user, &50001);
+}
+
+#[test]
+#[should_panic(expected = "Max supply exceeded")]
+fn test_burn_does_not_free_mint_room() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let token = create_capped_token(&e, &admin, 100000, 150000);
+
+    token.burn(&admin, &30000);
+    assert_eq!(token.total_supply(), 70000);
+    assert_eq!(token.minted(), 100000);
+    token.mint(&admin, &50001);
+}
+
+#[test]
+#[should_panic(expected = "Minting renounced")]
+fn test_mint_after_renounce_panics() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let token = create_capped_token(&e, &admin, 100000, 150000);
+
+    token.renounce_minting();
+    assert!(token.is_minting_renounced());
+    token.mint(&admin, &1);
+}
+
+#[test]
+#[should_panic(expected = "Max supply must not be below the initial supply")]
+fn test_max_supply_below_initial_panics() {
+    let e = Env::default();
+    let admin = Address::generate(&e);
+    create_capped_token(&e, &admin, 100000, 99999);
+}
+
+#[test]
+fn test_owner_receives_initial_supply() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let owner = Address::generate(&e);
+    let token = create_owned_token(&e, &admin, &owner, false);
+
+    assert_eq!(token.balance(&owner), 100000);
+    assert_eq!(token.balance(&admin), 0);
+    assert_eq!(token.owner(), Some(owner));
+    assert_eq!(token.admin(), admin);
+}
+
+#[test]
+fn test_owner_updates_metadata_and_transfers_ownership() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let owner = Address::generate(&e);
+    let new_owner = Address::generate(&e);
+    let token = create_owned_token(&e, &admin, &owner, false);
+
+    token.set_metadata(&String::from_val(&e, &"renamed"), &String::from_val(&e, &"RNM"));
+    let auths = e.auths();
+    assert_eq!(auths.len(), 1);
+    let (address, _) = &auths[0];
+    assert_eq!(*address, owner);
+    assert_eq!(token.name(), String::from_val(&e, &"renamed"));
+    assert_eq!(token.symbol(), String::from_val(&e, &"RNM"));
+    assert_eq!(token.decimals(), 18);
+
+    token.transfer_ownership(&new_owner);
+    assert_eq!(token.owner(), Some(new_owner));
+}
+
+#[test]
+#[should_panic(expected = "Ownership renounced")]
+fn test_set_metadata_after_renounce_panics() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let owner = Address::generate(&e);
+    let token = create_owned_token(&e, &admin, &owner, false);
+
+    token.renounce_ownership();
+    assert_eq!(token.owner(), None);
+    token.set_metadata(&String::from_val(&e, &"renamed"), &String::from_val(&e, &"RNM"));
+}
+
+#[test]
+fn test_freeze_and_unfreeze_account() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let user1 = Address::generate(&e);
+    let user2 = Address::generate(&e);
+    let token = create_token(&e, &admin);
+    token.transfer(&admin, &user1, &1000);
+
+    assert!(token.authorized(&user1));
+    token.set_authorized(&user1, &false);
+    let auths = e.auths();
+    assert_eq!(auths.len(), 1);
+    let (_contract, invocation) = &auths[0];
+    assert_eq!(invocation.function, AuthorizedFunction::Contract((
+        token.address.clone(),
+        Symbol::new(&e, "set_authorized"),
+        (&user1, false).into_val(&e),
+    )));
+    assert!(!token.authorized(&user1));
+
+    token.set_authorized(&user1, &true);
+    token.transfer(&user1, &user2, &400);
+    assert_eq!(token.balance(&user2), 400);
+}
+
+#[test]
+#[should_panic(expected = "account is frozen")]
+fn test_frozen_account_cannot_transfer() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let user1 = Address::generate(&e);
+    let user2 = Address::generate(&e);
+    let token = create_token(&e, &admin);
+    token.transfer(&admin, &user1, &1000);
+
+    token.set_authorized(&user1, &false);
+    token.transfer(&user1, &user2, &400);
+}
+
+#[test]
+#[should_panic(expected = "account is frozen")]
+fn test_frozen_account_cannot_receive() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let user1 = Address::generate(&e);
+    let user2 = Address::generate(&e);
+    let user3 = Address::generate(&e);
+    let token = create_token(&e, &admin);
+    token.transfer(&admin, &user1, &1000);
+
+    token.set_authorized(&user2, &false);
+    token.approve(&user1, &user3, &400, &200);
+    token.transfer_from(&user3, &user1, &user2, &400);
+}
+
+#[test]
+#[should_panic(expected = "account is frozen")]
+fn test_frozen_account_cannot_burn() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let user1 = Address::generate(&e);
+    let token = create_token(&e, &admin);
+    token.transfer(&admin, &user1, &1000);
+
+    token.set_authorized(&user1, &false);
+    token.burn(&user1, &100);
+}
+
+#[test]
+#[should_panic(expected = "account is frozen")]
+fn test_authorization_required_freezes_new_accounts() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let user1 = Address::generate(&e);
+    let user2 = Address::generate(&e);
+    let token = create_token(&e, &admin);
+    token.transfer(&admin, &user1, &1000);
+
+    token.set_authorization_required(&true);
+    assert!(token.authorization_required());
+    assert!(token.authorized(&user1));
+    assert!(!token.authorized(&user2));
+    token.transfer(&user1, &user2, &400);
+}
+
+#[test]
+#[should_panic(expected = "account is frozen")]
+fn test_mint_to_unauthorized_account_panics() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let user = Address::generate(&e);
+    let token = create_capped_token(&e, &admin, 100000, 200000);
+
+    token.set_authorization_required(&true);
+    token.mint(&user, &1000);
+}
+
+#[test]
+fn test_mint_to_authorized_account_after_authorization_required() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let user = Address::generate(&e);
+    let token = create_capped_token(&e, &admin, 100000, 200000);
+
+    token.set_authorization_required(&true);
+    token.set_authorized(&user, &true);
+    token.mint(&user, &1000);
+    assert_eq!(token.balance(&user), 1000);
+}
+
+#[test]
+fn test_clawback() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let owner = Address::generate(&e);
+    let user = Address::generate(&e);
+    let token = create_owned_token(&e, &admin, &owner, true);
+    assert!(token.clawback_enabled());
+
+    token.transfer(&owner, &user, &1000);
+    token.set_authorized(&user, &false);
+    token.clawback(&user, &600);
+    let auths = e.auths();
+    assert_eq!(auths.len(), 1);
+    let (address, _) = &auths[0];
+    assert_eq!(*address, admin);
+
+    assert_eq!(token.balance(&user), 400);
+    assert_eq!(token.total_supply(), 99400);
+}
+
+#[test]
+#[should_panic(expected = "Clawback not enabled")]
+fn test_clawback_when_disabled_panics() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let owner = Address::generate(&e);
+    let token = create_owned_token(&e, &admin, &owner, false);
+    token.clawback(&owner, &1);
+}
+
 #[test]
 fn test_zero_allowance() {
     // Here we test that transfer_from with a 0 amount does not create an empty allowance
@@ -0,0 +1,234 @@
+#![no_std]
+
+use soroban_sdk::{
+    contract, contractimpl, contracttype, symbol_short, Address, Env, Vec,
+    token::{self},
+};
+use storagettl::{read_persistent, write_persistent};
+
+// Overflow protection functions
+fn checked_sub(a: i128, b: i128) -> i128 {
+    a.checked_sub(b).expect("Underflow in subtraction")
+}
+
+fn checked_mul(a: i128, b: i128) -> i128 {
+    a.checked_mul(b).expect("Overflow in multiplication")
+}
+
+fn checked_div(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        panic!("Division by zero");
+    }
+    a.checked_div(b).expect("Overflow in division")
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum TimeUnit {
+    Ledger,    // Points are ledger sequence numbers
+    Timestamp, // Points are ledger close times, in seconds
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct VestingTerms {
+    pub start: u64,
+    pub cliff: u64, // Nothing vests before it, then everything vested since start unlocks at once
+    pub end: u64,   // Fully vested from here on
+    pub time_unit: TimeUnit,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Schedule {
+    pub beneficiary: Address,
+    pub funder: Address, // Receives the unvested tokens if the schedule is revoked
+    pub token: Address,
+    pub total_amount: i128,
+    pub released: i128,
+    pub terms: VestingTerms,
+    pub revocable: bool,
+    pub revoked: bool,
+}
+
+#[contract]
+pub struct Vesting;
+
+#[contractimpl]
+impl Vesting {
+
+    fn require_admin(e: &Env, admin: &Address) {
+        let stored: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        assert!(stored == *admin, "Unauthorized");
+        admin.require_auth();
+    }
+
+    fn load_schedule(e: &Env, schedule_id: u64) -> Schedule {
+        read_persistent(e, &DataKey::Schedule(schedule_id)).expect("Schedule not found")
+    }
+
+    fn save_schedule(e: &Env, schedule_id: u64, schedule: &Schedule) {
+        write_persistent(e, &DataKey::Schedule(schedule_id), schedule);
+    }
+
+    fn now(e: &Env, time_unit: &TimeUnit) -> u64 {
+        match time_unit {
+            TimeUnit::Ledger => e.ledger().sequence() as u64,
+            TimeUnit::Timestamp => e.ledger().timestamp(),
+        }
+    }
+
+    fn vested_amount(e: &Env, schedule: &Schedule) -> i128 {
+        let terms = &schedule.terms;
+        let now = Self::now(e, &terms.time_unit);
+        if schedule.revoked || now >= terms.end {
+            // A revoked schedule keeps what had vested when it was revoked
+            schedule.total_amount
+        } else if now < terms.cliff {
+            0
+        } else {
+            let elapsed = (now - terms.start) as i128;
+            let duration = (terms.end - terms.start) as i128;
+            checked_div(checked_mul(schedule.total_amount, elapsed), duration)
+        }
+    }
+
+    fn claimable_amount(e: &Env, schedule: &Schedule) -> i128 {
+        checked_sub(Self::vested_amount(e, schedule), schedule.released)
+    }
+
+    pub fn __constructor(e: Env, admin: Address) {
+        e.storage().instance().set(&DataKey::Admin, &admin);
+    }
+
+    /// Lock `amount` of `token` from the funder, vesting to the beneficiary on the given terms.
+    /// Returns the schedule ID
+    pub fn create_schedule(
+        e: Env,
+        funder: Address,
+        beneficiary: Address,
+        token: Address,
+        amount: i128,
+        terms: VestingTerms,
+        revocable: bool,
+    ) -> u64 {
+        funder.require_auth();
+        assert!(amount > 0, "Amount must be > 0");
+        assert!(terms.start < terms.end, "Invalid schedule");
+        assert!(terms.start <= terms.cliff && terms.cliff <= terms.end, "Invalid cliff");
+
+        token::Client::new(&e, &token).transfer(&funder, &e.current_contract_address(), &amount);
+
+        let schedule_id: u64 = e.storage().instance().get(&DataKey::NextScheduleId).unwrap_or(0);
+        e.storage().instance().set(&DataKey::NextScheduleId, &(schedule_id + 1));
+
+        let schedule = Schedule {
+            beneficiary: beneficiary.clone(),
+            funder,
+            token,
+            total_amount: amount,
+            released: 0,
+            terms,
+            revocable,
+            revoked: false,
+        };
+        Self::save_schedule(&e, schedule_id, &schedule);
+
+        let count = Self::get_schedule_count(e.clone(), beneficiary.clone());
+        write_persistent(&e, &DataKey::BeneficiaryScheduleAt(beneficiary.clone(), count), &schedule_id);
+        write_persistent(&e, &DataKey::BeneficiaryScheduleCount(beneficiary.clone()), &(count + 1));
+
+        e.events().publish((symbol_short!("created"), schedule_id), (beneficiary, amount));
+        schedule_id
+    }
+
+    /// Release everything vested so far to the beneficiary. Returns the amount released
+    pub fn claim(e: Env, beneficiary: Address, schedule_id: u64) -> i128 {
+        beneficiary.require_auth();
+        let mut schedule = Self::load_schedule(&e, schedule_id);
+        assert!(schedule.beneficiary == beneficiary, "Unauthorized");
+
+        let amount = Self::claimable_amount(&e, &schedule);
+        assert!(amount > 0, "Nothing to claim");
+
+        token::Client::new(&e, &schedule.token).transfer(&e.current_contract_address(), &beneficiary, &amount);
+        schedule.released += amount;
+        Self::save_schedule(&e, schedule_id, &schedule);
+
+        e.events().publish((symbol_short!("released"), schedule_id), (beneficiary, amount));
+        amount
+    }
+
+    /// Stop a revocable schedule (admin only). What has vested so far stays claimable by the
+    /// beneficiary and the rest goes back to the funder. Returns the amount returned
+    pub fn revoke(e: Env, admin: Address, schedule_id: u64) -> i128 {
+        Self::require_admin(&e, &admin);
+        let mut schedule = Self::load_schedule(&e, schedule_id);
+        assert!(schedule.revocable, "Schedule not revocable");
+        assert!(!schedule.revoked, "Schedule already revoked");
+
+        let vested = Self::vested_amount(&e, &schedule);
+        let unvested = checked_sub(schedule.total_amount, vested);
+        if unvested > 0 {
+            token::Client::new(&e, &schedule.token).transfer(&e.current_contract_address(), &schedule.funder, &unvested);
+        }
+
+        schedule.total_amount = vested;
+        schedule.revoked = true;
+        Self::save_schedule(&e, schedule_id, &schedule);
+
+        e.events().publish((symbol_short!("revoked"), schedule_id), unvested);
+        unvested
+    }
+
+    pub fn set_admin(e: Env, admin: Address, new_admin: Address) {
+        Self::require_admin(&e, &admin);
+        e.storage().instance().set(&DataKey::Admin, &new_admin);
+    }
+
+    // View functions
+    pub fn get_admin(e: Env) -> Address {
+        e.storage().instance().get(&DataKey::Admin).unwrap()
+    }
+
+    pub fn get_schedule(e: Env, schedule_id: u64) -> Schedule {
+        Self::load_schedule(&e, schedule_id)
+    }
+
+    /// Get up to `limit` of the beneficiary's schedule IDs, starting at `offset`, oldest first
+    pub fn get_schedules(e: Env, beneficiary: Address, offset: u32, limit: u32) -> Vec<u64> {
+        let end = offset.saturating_add(limit).min(Self::get_schedule_count(e.clone(), beneficiary.clone()));
+        let mut schedule_ids = Vec::new(&e);
+        for i in offset..end {
+            schedule_ids.push_back(read_persistent(&e, &DataKey::BeneficiaryScheduleAt(beneficiary.clone(), i)).unwrap());
+        }
+        schedule_ids
+    }
+
+    /// Get the number of schedules created for a beneficiary
+    pub fn get_schedule_count(e: Env, beneficiary: Address) -> u32 {
+        read_persistent(&e, &DataKey::BeneficiaryScheduleCount(beneficiary)).unwrap_or(0)
+    }
+
+    /// Total vested so far, released or not
+    pub fn vested(e: Env, schedule_id: u64) -> i128 {
+        Self::vested_amount(&e, &Self::load_schedule(&e, schedule_id))
+    }
+
+    /// Vested but not yet released
+    pub fn claimable(e: Env, schedule_id: u64) -> i128 {
+        Self::claimable_amount(&e, &Self::load_schedule(&e, schedule_id))
+    }
+}
+
+#[derive(Clone)]
+#[contracttype]
+enum DataKey {
+    Admin,
+    NextScheduleId,
+    Schedule(u64),
+    BeneficiaryScheduleCount(Address), // Number of schedules created for a beneficiary
+    BeneficiaryScheduleAt(Address, u32), // Per-beneficiary index to schedule ID
+}
+
+mod test;
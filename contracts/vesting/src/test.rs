@@ -0,0 +1,223 @@
+#![cfg(test)]
+extern crate std;
+use soroban_sdk::{
+    Env, String, Address, FromVal, IntoVal, symbol_short, vec,
+    testutils::{Address as _, Events, Ledger},
+};
+use crate::{TimeUnit, Vesting, VestingClient, VestingTerms};
+use ::token::Token;
+use ::token::TokenClient;
+
+// Helper function to create a test token
+fn create_token<'a>(e: &Env, admin: &Address) -> TokenClient<'a> {
+    let token_contract = e.register(
+        Token,
+        (
+            admin,
+            7_u32,
+            String::from_val(e, &"name"),
+            String::from_val(e, &"symbol"),
+        ),
+    );
+    TokenClient::new(e, &token_contract)
+}
+
+// 1000 tokens vesting from ledger 100 to 1100 with a cliff at 350
+fn ledger_terms() -> VestingTerms {
+    VestingTerms { start: 100, cliff: 350, end: 1100, time_unit: TimeUnit::Ledger }
+}
+
+fn setup<'a>(e: &Env, admin: &Address, funder: &Address) -> (TokenClient<'a>, VestingClient<'a>) {
+    let token = create_token(e, admin);
+    token.mint(funder, &100_000_000_000);
+    let vesting = VestingClient::new(e, &e.register(Vesting, (admin,)));
+    (token, vesting)
+}
+
+#[test]
+fn test_cliff_and_linear_release() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let funder = Address::generate(&env);
+    let beneficiary = Address::generate(&env);
+
+    let (token, vesting) = setup(&env, &admin, &funder);
+    let schedule_id = vesting.create_schedule(&funder, &beneficiary, &token.address, &10_000_000_000, &ledger_terms(), &false);
+    assert_eq!(vesting.get_schedules(&beneficiary, &0, &10), vec![&env, schedule_id]);
+    assert_eq!(token.balance(&vesting.address), 10_000_000_000);
+
+    // Nothing before the cliff, then everything vested since the start
+    env.ledger().set_sequence_number(349);
+    assert_eq!(vesting.claimable(&schedule_id), 0);
+    env.ledger().set_sequence_number(350);
+    assert_eq!(vesting.claimable(&schedule_id), 2_500_000_000);
+
+    env.ledger().set_sequence_number(600);
+    assert_eq!(vesting.claim(&beneficiary, &schedule_id), 5_000_000_000);
+    assert_eq!(token.balance(&beneficiary), 5_000_000_000);
+    assert_eq!(vesting.claimable(&schedule_id), 0);
+    assert_eq!(vesting.vested(&schedule_id), 5_000_000_000);
+
+    env.ledger().set_sequence_number(2000);
+    assert_eq!(vesting.claim(&beneficiary, &schedule_id), 5_000_000_000);
+    assert_eq!(token.balance(&beneficiary), 10_000_000_000);
+    assert_eq!(vesting.get_schedule(&schedule_id).released, 10_000_000_000);
+}
+
+#[test]
+fn test_timestamp_schedule() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let funder = Address::generate(&env);
+    let beneficiary = Address::generate(&env);
+
+    let (token, vesting) = setup(&env, &admin, &funder);
+    let terms = VestingTerms { start: 1_000_000, cliff: 1_000_000, end: 1_400_000, time_unit: TimeUnit::Timestamp };
+    let schedule_id = vesting.create_schedule(&funder, &beneficiary, &token.address, &4_000_000_000, &terms, &false);
+
+    // Ledger sequence numbers don't matter for a timestamp schedule
+    env.ledger().set_sequence_number(3_000);
+    env.ledger().set_timestamp(1_100_000);
+    assert_eq!(vesting.claimable(&schedule_id), 1_000_000_000);
+    env.ledger().set_timestamp(999_999);
+    assert_eq!(vesting.claimable(&schedule_id), 0);
+}
+
+#[test]
+fn test_claim_emits_release_event() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let funder = Address::generate(&env);
+    let beneficiary = Address::generate(&env);
+
+    let (token, vesting) = setup(&env, &admin, &funder);
+    let schedule_id = vesting.create_schedule(&funder, &beneficiary, &token.address, &10_000_000_000, &ledger_terms(), &false);
+
+    env.ledger().set_sequence_number(1100);
+    vesting.claim(&beneficiary, &schedule_id);
+
+    let (contract, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(contract, vesting.address);
+    assert_eq!(topics, (symbol_short!("released"), schedule_id).into_val(&env));
+    let (released_to, amount): (Address, i128) = FromVal::from_val(&env, &data);
+    assert_eq!(released_to, beneficiary);
+    assert_eq!(amount, 10_000_000_000);
+}
+
+#[test]
+fn test_revoke_returns_unvested() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let funder = Address::generate(&env);
+    let beneficiary = Address::generate(&env);
+
+    let (token, vesting) = setup(&env, &admin, &funder);
+    let schedule_id = vesting.create_schedule(&funder, &beneficiary, &token.address, &10_000_000_000, &ledger_terms(), &true);
+
+    env.ledger().set_sequence_number(600);
+    vesting.claim(&beneficiary, &schedule_id);
+    env.ledger().set_sequence_number(850);
+    assert_eq!(vesting.revoke(&admin, &schedule_id), 2_500_000_000);
+    assert_eq!(token.balance(&funder), 92_500_000_000);
+
+    // The part vested before revocation stays claimable, nothing more vests
+    env.ledger().set_sequence_number(1100);
+    assert_eq!(vesting.claimable(&schedule_id), 2_500_000_000);
+    assert_eq!(vesting.claim(&beneficiary, &schedule_id), 2_500_000_000);
+    assert_eq!(token.balance(&beneficiary), 7_500_000_000);
+    assert_eq!(token.balance(&vesting.address), 0);
+}
+
+#[test]
+#[should_panic(expected = "Schedule not revocable")]
+fn test_revoke_irrevocable_schedule_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let funder = Address::generate(&env);
+    let beneficiary = Address::generate(&env);
+
+    let (token, vesting) = setup(&env, &admin, &funder);
+    let schedule_id = vesting.create_schedule(&funder, &beneficiary, &token.address, &10_000_000_000, &ledger_terms(), &false);
+    vesting.revoke(&admin, &schedule_id);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized")]
+fn test_revoke_by_non_admin_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let funder = Address::generate(&env);
+    let beneficiary = Address::generate(&env);
+
+    let (token, vesting) = setup(&env, &admin, &funder);
+    let schedule_id = vesting.create_schedule(&funder, &beneficiary, &token.address, &10_000_000_000, &ledger_terms(), &true);
+    vesting.revoke(&funder, &schedule_id);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized")]
+fn test_claim_by_other_address_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let funder = Address::generate(&env);
+    let beneficiary = Address::generate(&env);
+
+    let (token, vesting) = setup(&env, &admin, &funder);
+    let schedule_id = vesting.create_schedule(&funder, &beneficiary, &token.address, &10_000_000_000, &ledger_terms(), &false);
+    env.ledger().set_sequence_number(1100);
+    vesting.claim(&funder, &schedule_id);
+}
+
+#[test]
+#[should_panic(expected = "Nothing to claim")]
+fn test_claim_before_cliff_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let funder = Address::generate(&env);
+    let beneficiary = Address::generate(&env);
+
+    let (token, vesting) = setup(&env, &admin, &funder);
+    let schedule_id = vesting.create_schedule(&funder, &beneficiary, &token.address, &10_000_000_000, &ledger_terms(), &false);
+    env.ledger().set_sequence_number(349);
+    vesting.claim(&beneficiary, &schedule_id);
+}
+
+#[test]
+#[should_panic(expected = "Invalid cliff")]
+fn test_cliff_after_end_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let funder = Address::generate(&env);
+    let beneficiary = Address::generate(&env);
+
+    let (token, vesting) = setup(&env, &admin, &funder);
+    let terms = VestingTerms { start: 100, cliff: 1200, end: 1100, time_unit: TimeUnit::Ledger };
+    vesting.create_schedule(&funder, &beneficiary, &token.address, &10_000_000_000, &terms, &false);
+}
+
+#[test]
+fn test_beneficiary_schedules_are_paged() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let funder = Address::generate(&env);
+    let beneficiary = Address::generate(&env);
+
+    let (token, vesting) = setup(&env, &admin, &funder);
+    let ids: std::vec::Vec<u64> = (0..3)
+        .map(|_| vesting.create_schedule(&funder, &beneficiary, &token.address, &1_000_000_000, &ledger_terms(), &false))
+        .collect();
+    assert_eq!(vesting.get_schedule_count(&beneficiary), 3);
+    assert_eq!(vesting.get_schedules(&beneficiary, &0, &2), vec![&env, ids[0], ids[1]]);
+    assert_eq!(vesting.get_schedules(&beneficiary, &2, &2), vec![&env, ids[2]]);
+    assert_eq!(vesting.get_schedules(&beneficiary, &3, &2).len(), 0);
+}
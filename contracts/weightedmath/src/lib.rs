@@ -0,0 +1,250 @@
+//! Weighted product invariant math in 18-decimal fixed point, shared by the weighted pool and the LBP.
+//!
+//! V = prod(b_i ^ w_i), with weights normalized to sum to ONE. Powers are computed as
+//! exp(y * ln(x)), so every result that depends on one is padded by MAX_POW_RELATIVE_ERROR
+//! in the pool's favour.
+#![no_std]
+
+use soroban_sdk::{Env, Vec, U256};
+
+pub const ONE: i128 = 1_000_000_000_000_000_000;
+const LN_2: i128 = 693_147_180_559_945_309;
+const MAX_POW_RELATIVE_ERROR: i128 = 10_000; // 1e-14
+
+// Trades and single-token joins/exits may move at most 30% of a balance at once,
+// which keeps every power's base close to ONE
+const MAX_IN_RATIO: i128 = 300_000_000_000_000_000;
+const MAX_OUT_RATIO: i128 = 300_000_000_000_000_000;
+
+fn to_u256(e: &Env, value: i128) -> U256 {
+    if value < 0 {
+        panic!("negative amount is not allowed: {}", value)
+    }
+    U256::from_u128(e, value as u128)
+}
+
+fn to_i128(value: &U256) -> i128 {
+    let value = value.to_u128().expect("Overflow in conversion");
+    i128::try_from(value).expect("Overflow in conversion")
+}
+
+/// a * b / c rounded down, without intermediate overflow
+pub fn mul_div(e: &Env, a: i128, b: i128, c: i128) -> i128 {
+    if c == 0 {
+        panic!("Division by zero");
+    }
+    to_i128(&to_u256(e, a).mul(&to_u256(e, b)).div(&to_u256(e, c)))
+}
+
+/// a * b / c rounded up, without intermediate overflow
+pub fn mul_div_up(e: &Env, a: i128, b: i128, c: i128) -> i128 {
+    if c == 0 {
+        panic!("Division by zero");
+    }
+    let product = to_u256(e, a).mul(&to_u256(e, b));
+    let c = to_u256(e, c);
+    let quotient = product.div(&c);
+    if product.rem_euclid(&c) == U256::from_u32(e, 0) {
+        to_i128(&quotient)
+    } else {
+        to_i128(&quotient.add(&U256::from_u32(e, 1)))
+    }
+}
+
+fn mul_down(e: &Env, a: i128, b: i128) -> i128 {
+    mul_div(e, a, b, ONE)
+}
+
+fn mul_up(e: &Env, a: i128, b: i128) -> i128 {
+    mul_div_up(e, a, b, ONE)
+}
+
+fn div_down(e: &Env, a: i128, b: i128) -> i128 {
+    mul_div(e, a, ONE, b)
+}
+
+fn div_up(e: &Env, a: i128, b: i128) -> i128 {
+    mul_div_up(e, a, ONE, b)
+}
+
+fn complement(x: i128) -> i128 {
+    if x < ONE { ONE - x } else { 0 }
+}
+
+/// Natural logarithm of a positive fixed point number
+fn ln(e: &Env, x: &U256) -> i128 {
+    let one = to_u256(e, ONE);
+    let two = to_u256(e, 2 * ONE);
+    assert!(*x > U256::from_u32(e, 0), "Logarithm of zero");
+
+    // x = m * 2^k with m in [1, 2)
+    let mut m = x.clone();
+    let mut k: i128 = 0;
+    while m >= two {
+        m = m.shr(1);
+        k += 1;
+    }
+    while m < one {
+        m = m.shl(1);
+        k -= 1;
+    }
+
+    // ln(m) = 2 * atanh(z) = 2 * (z + z^3/3 + z^5/5 + ...) with z = (m - 1) / (m + 1) <= 1/3
+    let m = to_i128(&m);
+    let z = (m - ONE) * ONE / (m + ONE);
+    let z_squared = z * z / ONE;
+    let mut term = z;
+    let mut sum = 0;
+    let mut n = 1;
+    while term != 0 {
+        sum += term / n;
+        term = term * z_squared / ONE;
+        n += 2;
+    }
+
+    k * LN_2 + 2 * sum
+}
+
+/// e^x for a fixed point exponent, as a fixed point number
+fn exp(e: &Env, x: i128) -> U256 {
+    // x = k * ln(2) + r with r in [0, ln(2)), so e^x = 2^k * e^r
+    let k = x.div_euclid(LN_2);
+    let r = x.rem_euclid(LN_2);
+
+    let mut term = ONE;
+    let mut sum = ONE;
+    let mut n = 1;
+    loop {
+        term = term * r / ONE / n;
+        if term == 0 {
+            break;
+        }
+        sum += term;
+        n += 1;
+    }
+
+    let result = to_u256(e, sum);
+    if k >= 0 {
+        result.shl(k as u32)
+    } else if k > -128 {
+        result.shr((-k) as u32)
+    } else {
+        U256::from_u32(e, 0)
+    }
+}
+
+fn pow(e: &Env, x: i128, y: i128) -> i128 {
+    if x == 0 {
+        return 0;
+    }
+    if y == ONE {
+        return x;
+    }
+    let exponent = y.checked_mul(ln(e, &to_u256(e, x))).expect("Overflow in multiplication") / ONE;
+    to_i128(&exp(e, exponent))
+}
+
+fn pow_down(e: &Env, x: i128, y: i128) -> i128 {
+    let raw = pow(e, x, y);
+    let max_error = mul_up(e, raw, MAX_POW_RELATIVE_ERROR) + 1;
+    if raw > max_error { raw - max_error } else { 0 }
+}
+
+fn pow_up(e: &Env, x: i128, y: i128) -> i128 {
+    let raw = pow(e, x, y);
+    raw + mul_up(e, raw, MAX_POW_RELATIVE_ERROR) + 1
+}
+
+/// Weighted product invariant of raw balances, in token units
+pub fn invariant(e: &Env, balances: &Vec<i128>, weights: &Vec<i128>) -> i128 {
+    let mut exponent: i128 = 0;
+    for (balance, weight) in balances.iter().zip(weights.iter()) {
+        assert!(balance > 0, "Pool balances must be > 0");
+        let ln_balance = ln(e, &to_u256(e, balance).mul(&to_u256(e, ONE)));
+        exponent += weight.checked_mul(ln_balance).expect("Overflow in multiplication") / ONE;
+    }
+    to_i128(&exp(e, exponent).div(&to_u256(e, ONE)))
+}
+
+/// Tokens out of `balance_out` for `amount_in` (after fees) added to `balance_in`:
+/// b_out * (1 - (b_in / (b_in + a_in)) ^ (w_in / w_out))
+pub fn out_given_in(
+    e: &Env,
+    balance_in: i128,
+    weight_in: i128,
+    balance_out: i128,
+    weight_out: i128,
+    amount_in: i128,
+) -> i128 {
+    assert!(amount_in <= mul_down(e, balance_in, MAX_IN_RATIO), "Max in ratio exceeded");
+
+    let base = div_up(e, balance_in, balance_in + amount_in);
+    let exponent = div_down(e, weight_in, weight_out);
+    let power = pow_up(e, base, exponent);
+    mul_down(e, balance_out, complement(power))
+}
+
+/// LP tokens minted for a single-token deposit. The part of the deposit beyond the
+/// token's weight is effectively swapped into the other tokens, so it pays the swap fee.
+pub fn lp_out_given_token_in(
+    e: &Env,
+    balance: i128,
+    weight: i128,
+    amount_in: i128,
+    total_supply: i128,
+    fee: i128,
+) -> i128 {
+    assert!(amount_in <= mul_down(e, balance, MAX_IN_RATIO), "Max in ratio exceeded");
+
+    let balance_ratio_with_fee = div_down(e, balance + amount_in, balance);
+    let invariant_ratio_with_fee = mul_down(e, balance_ratio_with_fee, weight) + complement(weight);
+
+    let amount_in_without_fee = if balance_ratio_with_fee > invariant_ratio_with_fee {
+        let non_taxable = mul_down(e, balance, invariant_ratio_with_fee - ONE);
+        let taxable = amount_in - non_taxable;
+        non_taxable + mul_down(e, taxable, complement(fee))
+    } else {
+        amount_in
+    };
+
+    let balance_ratio = div_down(e, balance + amount_in_without_fee, balance);
+    let invariant_ratio = pow_down(e, balance_ratio, weight);
+    if invariant_ratio > ONE {
+        mul_down(e, total_supply, invariant_ratio - ONE)
+    } else {
+        0
+    }
+}
+
+/// Tokens paid out for burning `lp_in` against a single token. As with deposits, the
+/// part beyond the token's weight pays the swap fee.
+pub fn token_out_given_lp_in(
+    e: &Env,
+    balance: i128,
+    weight: i128,
+    lp_in: i128,
+    total_supply: i128,
+    fee: i128,
+) -> i128 {
+    assert!(lp_in <= mul_down(e, total_supply, MAX_OUT_RATIO), "Max out ratio exceeded");
+
+    let invariant_ratio = div_up(e, total_supply - lp_in, total_supply);
+    let balance_ratio = pow_up(e, invariant_ratio, div_down(e, ONE, weight));
+    let amount_out_without_fee = mul_down(e, balance, complement(balance_ratio));
+
+    let taxable = mul_up(e, amount_out_without_fee, complement(weight));
+    let non_taxable = amount_out_without_fee - taxable;
+    non_taxable + mul_down(e, taxable, complement(fee))
+}
+
+/// Price of the output token in input tokens, ignoring fees: (b_in / w_in) / (b_out / w_out)
+pub fn spot_price(e: &Env, balance_in: i128, weight_in: i128, balance_out: i128, weight_out: i128) -> i128 {
+    let numerator = div_down(e, balance_in, weight_in);
+    let denominator = div_down(e, balance_out, weight_out);
+    div_down(e, numerator, denominator)
+}
+
+/// Fraction of ONE represented by `bps` basis points
+pub fn bps_to_fixed(bps: u32) -> i128 {
+    bps as i128 * ONE / 10000
+}
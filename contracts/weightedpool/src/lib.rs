@@ -0,0 +1,517 @@
+#![no_std]
+
+use soroban_sdk::{
+    contract, contractimpl, contracttype, symbol_short, Address, BytesN, Env, String, Vec,
+    token::{self, Interface as _},
+};
+
+use weightedmath::{
+    bps_to_fixed, invariant, lp_out_given_token_in, mul_div, mul_div_up, out_given_in, spot_price,
+    token_out_given_lp_in,
+};
+
+// Constants
+const BPS_DENOMINATOR: u32 = 10000;
+const MIN_TOKENS: u32 = 2;
+const MAX_TOKENS: u32 = 8;
+const MIN_WEIGHT_BPS: u32 = 100; // 1%
+const MAX_FEE_BPS: u32 = 1000; // 10%
+const SCHEMA_VERSION: u32 = 1; // Storage layout version, bump with every PoolInfo or DataKey change
+
+// Overflow protection functions
+fn checked_add(a: i128, b: i128) -> i128 {
+    a.checked_add(b).expect("Overflow in addition")
+}
+
+fn checked_sub(a: i128, b: i128) -> i128 {
+    a.checked_sub(b).expect("Underflow in subtraction")
+}
+
+fn checked_mul(a: i128, b: i128) -> i128 {
+    a.checked_mul(b).expect("Overflow in multiplication")
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PoolInfo {
+    pub tokens: Vec<Address>,
+    pub weights: Vec<u32>,   // Weight of each token in bps, summing to 10000
+    pub balances: Vec<i128>, // Pool balance of each token
+    pub fee_bps: u32,
+}
+
+#[contract]
+pub struct WeightedPool;
+
+#[contractimpl]
+impl WeightedPool {
+
+    fn check_nonnegative_amount(amount: i128) {
+        if amount < 0 {
+            panic!("negative amount is not allowed: {}", amount)
+        }
+    }
+
+    fn get_pool_info(e: &Env) -> PoolInfo {
+        e.storage().instance().get(&symbol_short!("pool")).unwrap()
+    }
+
+    fn set_pool_info(e: &Env, pool_info: &PoolInfo) {
+        e.storage().instance().set(&symbol_short!("pool"), pool_info);
+    }
+
+    fn token_index(pool_info: &PoolInfo, token: &Address) -> u32 {
+        pool_info.tokens.first_index_of(token).expect("Invalid token address")
+    }
+
+    fn normalized_weight(pool_info: &PoolInfo, index: u32) -> i128 {
+        bps_to_fixed(pool_info.weights.get(index).unwrap())
+    }
+
+    fn swap_fee(pool_info: &PoolInfo) -> i128 {
+        bps_to_fixed(pool_info.fee_bps)
+    }
+
+    fn calculate_amount_out(e: &Env, pool_info: &PoolInfo, index_in: u32, index_out: u32, amount_in: i128) -> i128 {
+        assert!(index_in != index_out, "Tokens must be different");
+
+        // The fee is taken from the input and stays in the pool
+        let fee = mul_div_up(e, amount_in, pool_info.fee_bps as i128, BPS_DENOMINATOR as i128);
+        out_given_in(
+            e,
+            pool_info.balances.get(index_in).unwrap(),
+            Self::normalized_weight(pool_info, index_in),
+            pool_info.balances.get(index_out).unwrap(),
+            Self::normalized_weight(pool_info, index_out),
+            checked_sub(amount_in, fee),
+        )
+    }
+
+    pub fn __constructor(
+        e: Env,
+        tokens: Vec<Address>,
+        weights: Vec<u32>,
+        fee_bps: u32,
+        lp_token_name: String,
+        lp_token_symbol: String,
+        factory: Address,
+    ) {
+        assert!(tokens.len() >= MIN_TOKENS && tokens.len() <= MAX_TOKENS, "Invalid token count");
+        assert!(weights.len() == tokens.len(), "Weights must match tokens");
+        assert!(fee_bps <= MAX_FEE_BPS, "Invalid fee");
+
+        let mut balances = Vec::new(&e);
+        let mut total_weight = 0;
+        for (i, token) in tokens.iter().enumerate() {
+            assert!(tokens.first_index_of(&token) == Some(i as u32), "Tokens must be different");
+            let weight = weights.get(i as u32).unwrap();
+            assert!(weight >= MIN_WEIGHT_BPS, "Weight too small");
+            total_weight += weight;
+            balances.push_back(0);
+        }
+        assert!(total_weight == BPS_DENOMINATOR, "Weights must sum to 10000 bps");
+
+        let pool_info = PoolInfo {
+            tokens,
+            weights,
+            balances,
+            fee_bps,
+        };
+
+        Self::set_pool_info(&e, &pool_info);
+        e.storage().instance().set(&symbol_short!("name"), &lp_token_name);
+        e.storage().instance().set(&symbol_short!("symbol"), &lp_token_symbol);
+        e.storage().instance().set(&symbol_short!("decimals"), &18u32);
+        e.storage().instance().set(&DataKey::Factory, &factory);
+        e.storage().instance().set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+    }
+
+    fn require_factory(e: &Env) {
+        let factory: Address = e.storage().instance().get(&DataKey::Factory).unwrap();
+        factory.require_auth();
+    }
+
+    /// Replace the pool code (factory only). The factory calls `migrate` right after, with the new code
+    pub fn upgrade(e: Env, new_wasm_hash: BytesN<32>) {
+        Self::require_factory(&e);
+        e.deployer().update_current_contract_wasm(new_wasm_hash.clone());
+        e.events().publish((symbol_short!("upgrade"),), new_wasm_hash);
+    }
+
+    /// Convert the storage written by older code to this code's layout (factory only).
+    /// Returns the schema version the storage is at
+    pub fn migrate(e: Env) -> u32 {
+        Self::require_factory(&e);
+        let version = Self::schema_version(e.clone());
+        assert!(version <= SCHEMA_VERSION, "Storage is newer than the code");
+        // A layout change adds its conversion here, reading the previous PoolInfo
+        // struct when `version` is below the one that introduced the change
+        if version < SCHEMA_VERSION {
+            e.storage().instance().set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+            e.events().publish((symbol_short!("migrate"),), (version, SCHEMA_VERSION));
+        }
+        SCHEMA_VERSION
+    }
+
+    /// Make the first deposit, which sets the pool's starting prices. Returns the LP tokens minted
+    pub fn initialize(e: Env, caller: Address, amounts_in: Vec<i128>) -> i128 {
+        caller.require_auth();
+
+        let mut pool_info = Self::get_pool_info(&e);
+        assert!(Self::total_supply(e.clone()) == 0, "Pool already initialized");
+        assert!(amounts_in.len() == pool_info.tokens.len(), "Amounts must match tokens");
+        for amount in amounts_in.iter() {
+            Self::check_nonnegative_amount(amount);
+            assert!(amount > 0, "Initial deposit requires all tokens");
+        }
+
+        let mut weights = Vec::new(&e);
+        for i in 0..pool_info.tokens.len() {
+            weights.push_back(Self::normalized_weight(&pool_info, i));
+        }
+        let liquidity = checked_mul(invariant(&e, &amounts_in, &weights), pool_info.tokens.len() as i128);
+        assert!(liquidity > 0, "Insufficient liquidity minted");
+
+        for (token, amount) in pool_info.tokens.iter().zip(amounts_in.iter()) {
+            token::Client::new(&e, &token).transfer_from(&e.current_contract_address(), &caller, &e.current_contract_address(), &amount);
+        }
+
+        Self::mint_lp_tokens(&e, &caller, liquidity);
+        pool_info.balances = amounts_in;
+        Self::set_pool_info(&e, &pool_info);
+
+        liquidity
+    }
+
+    /// Mint exactly `liquidity` LP tokens for a proportional deposit of every token.
+    /// Returns the amounts deposited
+    pub fn join_pool(e: Env, caller: Address, liquidity: i128) -> Vec<i128> {
+        caller.require_auth();
+        Self::check_nonnegative_amount(liquidity);
+        assert!(liquidity > 0, "Liquidity must be > 0");
+
+        let mut pool_info = Self::get_pool_info(&e);
+        let total_supply = Self::total_supply(e.clone());
+        assert!(total_supply > 0, "Pool not initialized");
+
+        // Round deposits up so joining can't dilute existing LPs
+        let mut amounts_in = Vec::new(&e);
+        let mut balances = Vec::new(&e);
+        for (token, balance) in pool_info.tokens.iter().zip(pool_info.balances.iter()) {
+            let amount = mul_div_up(&e, balance, liquidity, total_supply);
+            token::Client::new(&e, &token).transfer_from(&e.current_contract_address(), &caller, &e.current_contract_address(), &amount);
+            amounts_in.push_back(amount);
+            balances.push_back(checked_add(balance, amount));
+        }
+
+        Self::mint_lp_tokens(&e, &caller, liquidity);
+        pool_info.balances = balances;
+        Self::set_pool_info(&e, &pool_info);
+
+        amounts_in
+    }
+
+    /// Deposit a single token. Returns the LP tokens minted
+    pub fn join_single(e: Env, caller: Address, token_in: Address, amount_in: i128) -> i128 {
+        caller.require_auth();
+        Self::check_nonnegative_amount(amount_in);
+        assert!(amount_in > 0, "Amount in must be > 0");
+
+        let mut pool_info = Self::get_pool_info(&e);
+        let total_supply = Self::total_supply(e.clone());
+        assert!(total_supply > 0, "Pool not initialized");
+
+        let index = Self::token_index(&pool_info, &token_in);
+        let balance = pool_info.balances.get(index).unwrap();
+        let liquidity = lp_out_given_token_in(
+            &e,
+            balance,
+            Self::normalized_weight(&pool_info, index),
+            amount_in,
+            total_supply,
+            Self::swap_fee(&pool_info),
+        );
+        assert!(liquidity > 0, "Insufficient liquidity minted");
+
+        token::Client::new(&e, &token_in).transfer_from(&e.current_contract_address(), &caller, &e.current_contract_address(), &amount_in);
+
+        Self::mint_lp_tokens(&e, &caller, liquidity);
+        pool_info.balances.set(index, checked_add(balance, amount_in));
+        Self::set_pool_info(&e, &pool_info);
+
+        liquidity
+    }
+
+    /// Burn LP tokens for a proportional share of every token. Returns the amounts withdrawn
+    pub fn exit_pool(e: Env, caller: Address, liquidity: i128) -> Vec<i128> {
+        caller.require_auth();
+        Self::check_nonnegative_amount(liquidity);
+        assert!(liquidity > 0, "Liquidity must be > 0");
+
+        let mut pool_info = Self::get_pool_info(&e);
+        let total_supply = Self::total_supply(e.clone());
+        let caller_balance = Self::balance_of(e.clone(), caller.clone());
+        assert!(liquidity <= caller_balance, "Insufficient LP tokens");
+
+        // Proportional withdrawals pay no fee
+        let mut amounts_out = Vec::new(&e);
+        let mut balances = Vec::new(&e);
+        for (token, balance) in pool_info.tokens.iter().zip(pool_info.balances.iter()) {
+            let amount = mul_div(&e, balance, liquidity, total_supply);
+            if amount > 0 {
+                token::Client::new(&e, &token).transfer(&e.current_contract_address(), &caller, &amount);
+            }
+            amounts_out.push_back(amount);
+            balances.push_back(checked_sub(balance, amount));
+        }
+
+        Self::burn_lp_tokens(&e, &caller, liquidity);
+        pool_info.balances = balances;
+        Self::set_pool_info(&e, &pool_info);
+
+        amounts_out
+    }
+
+    /// Burn LP tokens for a single token. Returns the amount withdrawn
+    pub fn exit_single(e: Env, caller: Address, token_out: Address, liquidity: i128) -> i128 {
+        caller.require_auth();
+        Self::check_nonnegative_amount(liquidity);
+        assert!(liquidity > 0, "Liquidity must be > 0");
+
+        let mut pool_info = Self::get_pool_info(&e);
+        let total_supply = Self::total_supply(e.clone());
+        let caller_balance = Self::balance_of(e.clone(), caller.clone());
+        assert!(liquidity <= caller_balance, "Insufficient LP tokens");
+
+        let index = Self::token_index(&pool_info, &token_out);
+        let balance = pool_info.balances.get(index).unwrap();
+        let amount_out = token_out_given_lp_in(
+            &e,
+            balance,
+            Self::normalized_weight(&pool_info, index),
+            liquidity,
+            total_supply,
+            Self::swap_fee(&pool_info),
+        );
+        assert!(amount_out > 0, "Insufficient output amount");
+
+        Self::burn_lp_tokens(&e, &caller, liquidity);
+        token::Client::new(&e, &token_out).transfer(&e.current_contract_address(), &caller, &amount_out);
+
+        pool_info.balances.set(index, checked_sub(balance, amount_out));
+        Self::set_pool_info(&e, &pool_info);
+
+        amount_out
+    }
+
+    pub fn swap(e: Env, caller: Address, input_token: Address, output_token: Address, amount_in: i128) -> i128 {
+        caller.require_auth();
+        Self::check_nonnegative_amount(amount_in);
+        assert!(amount_in > 0, "Amount in must be > 0");
+
+        let mut pool_info = Self::get_pool_info(&e);
+        assert!(Self::total_supply(e.clone()) > 0, "Pool not initialized");
+
+        let index_in = Self::token_index(&pool_info, &input_token);
+        let index_out = Self::token_index(&pool_info, &output_token);
+        let amount_out = Self::calculate_amount_out(&e, &pool_info, index_in, index_out, amount_in);
+        assert!(amount_out > 0, "Insufficient output amount");
+
+        token::Client::new(&e, &input_token).transfer_from(&e.current_contract_address(), &caller, &e.current_contract_address(), &amount_in);
+        token::Client::new(&e, &output_token).transfer(&e.current_contract_address(), &caller, &amount_out);
+
+        // The fee stays in the balances and accrues to LPs
+        let balance_in = pool_info.balances.get(index_in).unwrap();
+        let balance_out = pool_info.balances.get(index_out).unwrap();
+        pool_info.balances.set(index_in, checked_add(balance_in, amount_in));
+        pool_info.balances.set(index_out, checked_sub(balance_out, amount_out));
+        Self::set_pool_info(&e, &pool_info);
+
+        let mut volumes = Self::get_token_volumes(e.clone());
+        volumes.set(index_in, checked_add(volumes.get(index_in).unwrap(), amount_in));
+        volumes.set(index_out, checked_add(volumes.get(index_out).unwrap(), amount_out));
+        e.storage().instance().set(&DataKey::TokenVolumes, &volumes);
+
+        amount_out
+    }
+
+    // View functions
+    pub fn get_factory(e: Env) -> Address {
+        e.storage().instance().get(&DataKey::Factory).unwrap()
+    }
+
+    pub fn schema_version(e: Env) -> u32 {
+        e.storage().instance().get(&DataKey::SchemaVersion).unwrap_or(1)
+    }
+
+    pub fn get_tokens(e: Env) -> Vec<Address> {
+        Self::get_pool_info(&e).tokens
+    }
+
+    pub fn get_weights(e: Env) -> Vec<u32> {
+        Self::get_pool_info(&e).weights
+    }
+
+    pub fn get_balances(e: Env) -> Vec<i128> {
+        Self::get_pool_info(&e).balances
+    }
+
+    /// All-time swap volume of each token, in token order, counting what was swapped in and out in that token
+    pub fn get_token_volumes(e: Env) -> Vec<i128> {
+        e.storage().instance().get(&DataKey::TokenVolumes).unwrap_or_else(|| {
+            let mut volumes = Vec::new(&e);
+            for _ in Self::get_pool_info(&e).tokens.iter() {
+                volumes.push_back(0);
+            }
+            volumes
+        })
+    }
+
+    pub fn get_fee_bps(e: Env) -> u32 {
+        Self::get_pool_info(&e).fee_bps
+    }
+
+    pub fn get_amount_out(e: Env, input_token: Address, output_token: Address, amount_in: i128) -> i128 {
+        Self::check_nonnegative_amount(amount_in);
+        let pool_info = Self::get_pool_info(&e);
+        let index_in = Self::token_index(&pool_info, &input_token);
+        let index_out = Self::token_index(&pool_info, &output_token);
+        Self::calculate_amount_out(&e, &pool_info, index_in, index_out, amount_in)
+    }
+
+    /// Price of `output_token` in `input_token` without fees, scaled by 1e18
+    pub fn get_spot_price(e: Env, input_token: Address, output_token: Address) -> i128 {
+        let pool_info = Self::get_pool_info(&e);
+        let index_in = Self::token_index(&pool_info, &input_token);
+        let index_out = Self::token_index(&pool_info, &output_token);
+        spot_price(
+            &e,
+            pool_info.balances.get(index_in).unwrap(),
+            Self::normalized_weight(&pool_info, index_in),
+            pool_info.balances.get(index_out).unwrap(),
+            Self::normalized_weight(&pool_info, index_out),
+        )
+    }
+
+    pub fn supply(e: Env) -> i128 {
+        Self::total_supply(e)
+    }
+
+    fn total_supply(e: Env) -> i128 {
+        e.storage().instance().get(&DataKey::TotalSupply).unwrap_or(0)
+    }
+
+    fn mint_lp_tokens(e: &Env, to: &Address, amount: i128) {
+        let total_supply = Self::total_supply(e.clone());
+        e.storage().instance().set(&DataKey::TotalSupply, &checked_add(total_supply, amount));
+
+        let balance = e.storage().instance().get(&DataKey::Balance(to.clone())).unwrap_or(0);
+        e.storage().instance().set(&DataKey::Balance(to.clone()), &checked_add(balance, amount));
+    }
+
+    fn burn_lp_tokens(e: &Env, from: &Address, amount: i128) {
+        let total_supply = Self::total_supply(e.clone());
+        e.storage().instance().set(&DataKey::TotalSupply, &checked_sub(total_supply, amount));
+
+        let balance = e.storage().instance().get(&DataKey::Balance(from.clone())).unwrap_or(0);
+        e.storage().instance().set(&DataKey::Balance(from.clone()), &checked_sub(balance, amount));
+    }
+
+    pub fn balance_of(e: Env, id: Address) -> i128 {
+        <WeightedPool as token::Interface>::balance(e, id)
+    }
+}
+
+#[contractimpl]
+impl token::Interface for WeightedPool {
+    fn allowance(e: Env, from: Address, spender: Address) -> i128 {
+        e.storage().instance().get(&DataKey::Allowance(from, spender)).unwrap_or(0)
+    }
+
+    fn approve(e: Env, from: Address, spender: Address, amount: i128, _expiration_ledger: u32) {
+        from.require_auth();
+        Self::check_nonnegative_amount(amount);
+        e.storage().instance().set(&DataKey::Allowance(from, spender), &amount);
+    }
+
+    fn balance(e: Env, id: Address) -> i128 {
+        e.storage().instance().get(&DataKey::Balance(id)).unwrap_or(0)
+    }
+
+    fn transfer(e: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        Self::check_nonnegative_amount(amount);
+
+        let balance = Self::balance(e.clone(), from.clone());
+        assert!(balance >= amount, "Insufficient balance");
+
+        let to_balance = Self::balance(e.clone(), to.clone());
+        e.storage().instance().set(&DataKey::Balance(from), &checked_sub(balance, amount));
+        e.storage().instance().set(&DataKey::Balance(to), &checked_add(to_balance, amount));
+    }
+
+    fn transfer_from(e: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        spender.require_auth();
+        Self::check_nonnegative_amount(amount);
+
+        let allowance = Self::allowance(e.clone(), from.clone(), spender.clone());
+        assert!(allowance >= amount, "Insufficient allowance");
+
+        let balance = Self::balance(e.clone(), from.clone());
+        assert!(balance >= amount, "Insufficient balance");
+
+        let to_balance = Self::balance(e.clone(), to.clone());
+        e.storage().instance().set(&DataKey::Allowance(from.clone(), spender), &checked_sub(allowance, amount));
+        e.storage().instance().set(&DataKey::Balance(from), &checked_sub(balance, amount));
+        e.storage().instance().set(&DataKey::Balance(to), &checked_add(to_balance, amount));
+    }
+
+    fn burn(e: Env, from: Address, amount: i128) {
+        from.require_auth();
+        Self::check_nonnegative_amount(amount);
+
+        let balance = Self::balance(e.clone(), from.clone());
+        assert!(balance >= amount, "Insufficient balance");
+
+        Self::burn_lp_tokens(&e, &from, amount);
+    }
+
+    fn burn_from(e: Env, spender: Address, from: Address, amount: i128) {
+        spender.require_auth();
+        Self::check_nonnegative_amount(amount);
+
+        let allowance = Self::allowance(e.clone(), from.clone(), spender.clone());
+        assert!(allowance >= amount, "Insufficient allowance");
+
+        let balance = Self::balance(e.clone(), from.clone());
+        assert!(balance >= amount, "Insufficient balance");
+
+        e.storage().instance().set(&DataKey::Allowance(from.clone(), spender), &checked_sub(allowance, amount));
+        Self::burn_lp_tokens(&e, &from, amount);
+    }
+
+    fn decimals(e: Env) -> u32 {
+        e.storage().instance().get(&symbol_short!("decimals")).unwrap_or(18)
+    }
+
+    fn name(e: Env) -> String {
+        e.storage().instance().get(&symbol_short!("name")).unwrap_or(String::from_str(&e, "Weighted Pool Token"))
+    }
+
+    fn symbol(e: Env) -> String {
+        e.storage().instance().get(&symbol_short!("symbol")).unwrap_or(String::from_str(&e, "WLP"))
+    }
+}
+
+#[derive(Clone)]
+#[contracttype]
+enum DataKey {
+    Balance(Address),
+    Allowance(Address, Address),
+    TotalSupply,
+    TokenVolumes, // All-time swap volume of each token, in token order
+    Factory, // Factory allowed to upgrade the pool
+    SchemaVersion, // Storage layout version, see SCHEMA_VERSION
+}
+
+mod test;
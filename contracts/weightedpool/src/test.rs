@@ -0,0 +1,289 @@
+#![cfg(test)]
+extern crate std;
+use soroban_sdk::{
+    Env, String, Address, FromVal, Vec, vec,
+    testutils::Address as _,
+};
+use crate::WeightedPool;
+use crate::WeightedPoolClient;
+use ::token::Token;
+use ::token::TokenClient;
+
+// Helper function to create a test token
+fn create_token<'a>(e: &Env, admin: &Address) -> TokenClient<'a> {
+    let token_contract = e.register(
+        Token,
+        (
+            admin,
+            7_u32,
+            String::from_val(e, &"name"),
+            String::from_val(e, &"symbol"),
+        ),
+    );
+    TokenClient::new(e, &token_contract)
+}
+
+fn deploy_pool<'a>(e: &Env, tokens: &[&TokenClient<'a>], weights: Vec<u32>, fee_bps: u32) -> WeightedPoolClient<'a> {
+    let mut addresses = Vec::new(e);
+    for token in tokens {
+        addresses.push_back(token.address.clone());
+    }
+    let contract_id = e.register(
+        WeightedPool,
+        (
+            addresses,
+            weights,
+            fee_bps,
+            String::from_val(e, &"WeightedLPToken"),
+            String::from_val(e, &"WLP"),
+            Address::generate(e),
+        ),
+    );
+    WeightedPoolClient::new(e, &contract_id)
+}
+
+// 80/20 pool holding 8000 token_a and 2000 token_b, so both are priced 1:1
+fn setup_80_20<'a>(e: &Env, user: &Address) -> (TokenClient<'a>, TokenClient<'a>, WeightedPoolClient<'a>) {
+    let token_a = create_token(e, user);
+    let token_b = create_token(e, user);
+    token_a.mint(user, &100_000_000_000);
+    token_b.mint(user, &100_000_000_000);
+
+    let pool = deploy_pool(e, &[&token_a, &token_b], vec![e, 8000, 2000], 30);
+    token_a.approve(user, &pool.address, &80_000_000_000, &1000);
+    token_b.approve(user, &pool.address, &20_000_000_000, &1000);
+    pool.initialize(user, &vec![e, 80_000_000_000, 20_000_000_000]);
+
+    (token_a, token_b, pool)
+}
+
+fn assert_close(actual: i128, expected: f64, tolerance: f64) {
+    let difference = (actual as f64 - expected).abs() / expected;
+    assert!(difference < tolerance, "{} vs {}", actual, expected);
+}
+
+#[test]
+fn test_initialize() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let (token_a, token_b, pool) = setup_80_20(&env, &user);
+
+    // LP supply is the invariant times the number of tokens
+    let invariant = 80_000_000_000_f64.powf(0.8) * 20_000_000_000_f64.powf(0.2);
+    assert_close(pool.supply(), invariant * 2.0, 1e-9);
+    assert_eq!(pool.balance_of(&user), pool.supply());
+    assert_eq!(pool.get_balances(), vec![&env, 80_000_000_000, 20_000_000_000]);
+    assert_eq!(pool.get_weights(), vec![&env, 8000, 2000]);
+    assert_eq!(token_a.balance(&pool.address), 80_000_000_000);
+
+    // Balances in proportion to the weights mean equal prices
+    assert_eq!(pool.get_spot_price(&token_a.address, &token_b.address), 1_000_000_000_000_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Pool already initialized")]
+fn test_initialize_twice_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let (_, _, pool) = setup_80_20(&env, &user);
+    pool.initialize(&user, &vec![&env, 1_000, 1_000]);
+}
+
+#[test]
+fn test_swap_follows_weighted_invariant() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+    let trader = Address::generate(&env);
+
+    let (token_a, token_b, pool) = setup_80_20(&env, &user);
+    token_b.mint(&trader, &1_000_000_000);
+
+    let amount_in = 1_000_000_000;
+    let quoted = pool.get_amount_out(&token_b.address, &token_a.address, &amount_in);
+    token_b.approve(&trader, &pool.address, &amount_in, &1000);
+    let amount_out = pool.swap(&trader, &token_b.address, &token_a.address, &amount_in);
+    assert_eq!(amount_out, quoted);
+
+    // b_out * (1 - (b_in / (b_in + a_in)) ^ (w_in / w_out)), with the 0.3% fee taken from the input
+    let amount_in_after_fee = amount_in as f64 * 0.997;
+    let expected = 80_000_000_000_f64 * (1.0 - (20_000_000_000_f64 / (20_000_000_000_f64 + amount_in_after_fee)).powf(0.25));
+    assert_close(amount_out, expected, 1e-9);
+    assert!(amount_out as f64 <= expected);
+
+    assert_eq!(token_a.balance(&trader), amount_out);
+    assert_eq!(pool.get_balances(), vec![&env, 80_000_000_000 - amount_out, 20_000_000_000 + amount_in]);
+    assert_eq!(pool.get_token_volumes(), vec![&env, amount_out, amount_in]);
+}
+
+#[test]
+fn test_three_token_pool_swap() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user);
+    let token_b = create_token(&env, &user);
+    let token_c = create_token(&env, &user);
+    for token in [&token_a, &token_b, &token_c] {
+        token.mint(&user, &10_000_000_000);
+    }
+
+    let pool = deploy_pool(&env, &[&token_a, &token_b, &token_c], vec![&env, 5000, 2500, 2500], 30);
+    token_a.approve(&user, &pool.address, &10_000_000_000, &1000);
+    token_b.approve(&user, &pool.address, &5_000_000_000, &1000);
+    token_c.approve(&user, &pool.address, &5_000_000_000, &1000);
+    pool.initialize(&user, &vec![&env, 10_000_000_000, 5_000_000_000, 5_000_000_000]);
+
+    // Equal weights and balances between b and c behave like a 50/50 pool
+    token_b.approve(&user, &pool.address, &100_000_000, &1000);
+    let amount_out = pool.swap(&user, &token_b.address, &token_c.address, &100_000_000);
+    let expected = 5_000_000_000_f64 * 99_700_000.0 / (5_000_000_000.0 + 99_700_000.0);
+    assert_close(amount_out, expected, 1e-9);
+
+    // token_a is untouched
+    assert_eq!(pool.get_balances().get(0).unwrap(), 10_000_000_000);
+}
+
+#[test]
+fn test_proportional_join_and_exit() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+
+    let (token_a, token_b, pool) = setup_80_20(&env, &user1);
+    token_a.mint(&user2, &8_000_000_000);
+    token_b.mint(&user2, &2_000_000_000);
+
+    // 10% of the supply costs 10% of each balance, rounded up
+    let liquidity = pool.supply() / 10;
+    token_a.approve(&user2, &pool.address, &8_000_000_000, &1000);
+    token_b.approve(&user2, &pool.address, &2_000_000_000, &1000);
+    let amounts_in = pool.join_pool(&user2, &liquidity);
+    assert!(amounts_in.get(0).unwrap() - 8_000_000_000 <= 1);
+    assert!(amounts_in.get(1).unwrap() - 2_000_000_000 <= 1);
+    assert_eq!(pool.balance_of(&user2), liquidity);
+
+    // Exiting returns the deposit, less rounding
+    let amounts_out = pool.exit_pool(&user2, &liquidity);
+    assert!(amounts_in.get(0).unwrap() - amounts_out.get(0).unwrap() <= 2);
+    assert!(amounts_in.get(1).unwrap() - amounts_out.get(1).unwrap() <= 2);
+    assert_eq!(pool.balance_of(&user2), 0);
+}
+
+#[test]
+fn test_transfer_lp_tokens() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    let spender = Address::generate(&env);
+
+    let (token_a, token_b, pool) = setup_80_20(&env, &user1);
+    let supply = pool.supply();
+
+    pool.transfer(&user1, &user2, &(supply / 10));
+    assert_eq!(pool.balance(&user1), supply - supply / 10);
+    assert_eq!(pool.balance(&user2), supply / 10);
+
+    pool.approve(&user2, &spender, &(supply / 20), &1000);
+    pool.transfer_from(&spender, &user2, &user1, &(supply / 20));
+    assert_eq!(pool.allowance(&user2, &spender), 0);
+    assert_eq!(pool.balance(&user2), supply / 10 - supply / 20);
+    assert_eq!(pool.symbol(), String::from_val(&env, &"WLP"));
+
+    // The new holder can exit with the transferred LP tokens
+    pool.exit_pool(&user2, &pool.balance(&user2));
+    assert_eq!(pool.balance(&user2), 0);
+    assert!(token_a.balance(&user2) > 0 && token_b.balance(&user2) > 0);
+}
+
+#[test]
+fn test_single_token_join_and_exit() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+
+    let (token_a, token_b, pool) = setup_80_20(&env, &user1);
+    token_b.mint(&user2, &1_000_000_000);
+    let supply_before = pool.supply();
+    let swap_quote = pool.get_amount_out(&token_b.address, &token_a.address, &1_000_000_000);
+
+    // Joining with token_b only: (1 + 1000/20000)^0.2 - 1 of the supply, less the fee
+    // on the 80% of the deposit that is effectively swapped into token_a
+    token_b.approve(&user2, &pool.address, &1_000_000_000, &1000);
+    let liquidity = pool.join_single(&user2, &token_b.address, &1_000_000_000);
+    let no_fee = supply_before as f64 * (1.05_f64.powf(0.2) - 1.0);
+    assert!((liquidity as f64) < no_fee);
+    assert!((liquidity as f64) > no_fee * 0.997);
+
+    // Exiting to token_a pays the fee again, so the round trip is worse than a plain swap
+    let amount_out = pool.exit_single(&user2, &token_a.address, &liquidity);
+    assert_eq!(token_a.balance(&user2), amount_out);
+    assert!(amount_out < swap_quote && amount_out > swap_quote * 99 / 100);
+    assert_eq!(pool.balance_of(&user2), 0);
+}
+
+#[test]
+#[should_panic(expected = "Max in ratio exceeded")]
+fn test_swap_above_max_in_ratio_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let (token_a, token_b, pool) = setup_80_20(&env, &user);
+    token_b.approve(&user, &pool.address, &7_000_000_000, &1000);
+    pool.swap(&user, &token_b.address, &token_a.address, &7_000_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient LP tokens")]
+fn test_exit_insufficient_lp_tokens_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+    let other = Address::generate(&env);
+
+    let (_, _, pool) = setup_80_20(&env, &user);
+    pool.exit_pool(&other, &1_000);
+}
+
+#[test]
+#[should_panic(expected = "Weights must sum to 10000 bps")]
+fn test_weights_must_sum_to_one() {
+    let env = Env::default();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user);
+    let token_b = create_token(&env, &user);
+    deploy_pool(&env, &[&token_a, &token_b], vec![&env, 8000, 1000], 30);
+}
+
+#[test]
+#[should_panic(expected = "Tokens must be different")]
+fn test_duplicate_tokens_panics() {
+    let env = Env::default();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user);
+    deploy_pool(&env, &[&token_a, &token_a], vec![&env, 5000, 5000], 30);
+}
+
+#[test]
+#[should_panic(expected = "Pool not initialized")]
+fn test_swap_before_initialize_panics() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let token_a = create_token(&env, &user);
+    let token_b = create_token(&env, &user);
+    let pool = deploy_pool(&env, &[&token_a, &token_b], vec![&env, 5000, 5000], 30);
+    pool.swap(&user, &token_a.address, &token_b.address, &1_000);
+}